@@ -1,5 +1,5 @@
 use proc_macro::TokenStream;
-use quote::{quote, quote_spanned};
+use quote::{quote, quote_spanned, ToTokens};
 use syn::spanned::Spanned;
 
 /// Enables an async main function in tokio's [`Localset`](tokio::task::LocalSet).
@@ -116,33 +116,396 @@ pub fn test(_attr: TokenStream, item: TokenStream) -> TokenStream {
     result.into()
 }
 
-/// Enables async method in [Handler](actix_async::prelude::Handler) trait.
+/// Generate [Handler](actix_async::prelude::Handler) impls from async methods of actor.
+///
+/// On an inherent impl block every async method marked with `#[handle]` in the form of
+/// `async fn name(&self, msg: Msg, ctx: Context<'_, Self>) -> Msg::Result` becomes
+/// `Handler::handle` of the message type. A method taking `&mut self` becomes
+/// `Handler::handle_wait`. Methods without the marker are left untouched.
+///
+/// *. A message with `&mut self` method must also have a `&self` method. `Handler::handle` is
+/// required for handling message concurrently (e.g. `Addr::send`). Mark the `&mut self` method
+/// with `#[handle(exclusive)]` instead when the message is only sent exclusively
+/// (e.g. `Addr::wait`). Sending such message concurrently would panic.
+///
+/// On a `Handler` trait impl the attribute is a no-op kept for compatibility.
 ///
 /// # Examples
 ///
 /// ```ignore
 /// #[actix_async::handler]
-/// impl Handler<Msg> for Actor {
-///     async fn handle(&self, msg: Msg, ctx: Context<'_, Self>) {}
+/// impl Actor {
+///     #[handle]
+///     async fn on_msg(&self, msg: Msg, ctx: Context<'_, Self>) -> u32 {
+///         996
+///     }
+///
+///     #[handle]
+///     async fn on_msg_mut(&mut self, msg: Msg, ctx: Context<'_, Self>) -> u32 {
+///         251
+///     }
+///
+///     #[handle(exclusive)]
+///     async fn on_msg2(&mut self, msg: Msg2, ctx: Context<'_, Self>) {}
 /// }
 /// ```
 /// # Expend
 ///
 /// macro would expend into following code
 /// ```ignore
-/// #[async_trait::async_trait(?Send)]
-/// impl Handler<Msg> for Actor {
-///     async fn handle(&self, msg: Msg, ctx: Context<'_, Self>) {}
+/// impl Actor {
+///     async fn on_msg(&self, msg: Msg, ctx: Context<'_, Self>) -> u32 {
+///         996
+///     }
+///
+///     async fn on_msg_mut(&mut self, msg: Msg, ctx: Context<'_, Self>) -> u32 {
+///         251
+///     }
+/// }
+///
+/// impl actix_async::prelude::Handler<Msg> for Actor {
+///     fn handle<'a>(
+///         &'a self,
+///         msg: Msg,
+///         ctx: actix_async::prelude::Context<'a, Self>,
+///     ) -> impl core::future::Future<Output = u32> + 'a {
+///         Self::on_msg(self, msg, ctx)
+///     }
+///
+///     fn handle_wait<'a>(
+///         &'a mut self,
+///         msg: Msg,
+///         ctx: actix_async::prelude::Context<'a, Self>,
+///     ) -> impl core::future::Future<Output = u32> + 'a {
+///         Self::on_msg_mut(self, msg, ctx)
+///     }
+/// }
+///
+/// impl actix_async::prelude::Handler<Msg2> for Actor {
+///     async fn handle(&self, _: Msg2, _: actix_async::prelude::Context<'_, Self>) {
+///         unreachable!("..")
+///     }
+///
+///     fn handle_wait<'a>(
+///         &'a mut self,
+///         msg: Msg2,
+///         ctx: actix_async::prelude::Context<'a, Self>,
+///     ) -> impl core::future::Future<Output = ()> + 'a {
+///         Self::on_msg2(self, msg, ctx)
+///     }
 /// }
 /// ```
 #[proc_macro_attribute]
 pub fn handler(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    let input = syn::parse_macro_input!(item as syn::ItemImpl);
+    let mut input = syn::parse_macro_input!(item as syn::ItemImpl);
+
+    // Handler trait impl. async method is supported natively.
+    if input.trait_.is_some() {
+        return quote!(#input).into();
+    }
+
+    // handler methods grouped by message type.
+    let mut handlers: Vec<HandlerMethods> = Vec::new();
+
+    for item in input.items.iter_mut() {
+        let method = match item {
+            syn::ImplItem::Method(method) => method,
+            _ => continue,
+        };
+
+        let exclusive = match take_handle_attr(&mut method.attrs) {
+            Ok(Some(exclusive)) => exclusive,
+            Ok(None) => continue,
+            Err(e) => return e.to_compile_error().into(),
+        };
+
+        let (mutable, msg) = match handler_method(&method.sig) {
+            Some(res) => res,
+            None => return syn::Error::new_spanned(
+                &method.sig,
+                "handler method must be in the form of `async fn(&self, Msg, Context<'_, Self>)`",
+            )
+            .to_compile_error()
+            .into(),
+        };
+
+        if exclusive && !mutable {
+            return syn::Error::new_spanned(
+                &method.sig,
+                "`#[handle(exclusive)]` method must take `&mut self`",
+            )
+            .to_compile_error()
+            .into();
+        }
+
+        let key = msg.to_token_stream().to_string();
+        let idx = match handlers.iter().position(|h| h.key == key) {
+            Some(idx) => idx,
+            None => {
+                handlers.push(HandlerMethods {
+                    key,
+                    msg: msg.clone(),
+                    output: match method.sig.output {
+                        syn::ReturnType::Default => syn::parse_quote!(()),
+                        syn::ReturnType::Type(_, ref ty) => (**ty).clone(),
+                    },
+                    handle: None,
+                    handle_wait: None,
+                    exclusive: false,
+                });
+                handlers.len() - 1
+            }
+        };
+
+        let slot = if mutable {
+            &mut handlers[idx].handle_wait
+        } else {
+            &mut handlers[idx].handle
+        };
+
+        if slot.is_some() {
+            return syn::Error::new_spanned(
+                &method.sig,
+                "duplicate handler method for the same message type",
+            )
+            .to_compile_error()
+            .into();
+        }
+
+        *slot = Some(method.sig.ident.clone());
+        handlers[idx].exclusive |= exclusive;
+    }
+
+    // Handler::handle is required. message can not be handled by &mut self method alone unless
+    // it's exclusive.
+    for h in handlers.iter() {
+        let msg = match (h.handle.is_some(), h.exclusive) {
+            (false, false) => "message handled by a `&mut self` method must also have a `&self` method or be marked with `#[handle(exclusive)]`",
+            (true, true) => "exclusive message can not have a `&self` method",
+            _ => continue,
+        };
+        return syn::Error::new_spanned(&h.msg, msg)
+            .to_compile_error()
+            .into();
+    }
+
+    let self_ty = &input.self_ty;
+    let (impl_generics, _, where_clause) = input.generics.split_for_impl();
+
+    let impls = handlers.iter().map(|h| {
+        let msg = &h.msg;
+        let output = &h.output;
+
+        let handle = match h.handle {
+            Some(ref ident) => quote! {
+                fn handle<'a>(
+                    &'a self,
+                    msg: #msg,
+                    ctx: actix_async::prelude::Context<'a, Self>,
+                ) -> impl ::core::future::Future<Output = #output> + 'a {
+                    Self::#ident(self, msg, ctx)
+                }
+            },
+            None => quote! {
+                async fn handle(
+                    &self,
+                    _: #msg,
+                    _: actix_async::prelude::Context<'_, Self>,
+                ) -> #output {
+                    unreachable!("exclusive message must be sent with Addr::wait or Addr::do_wait")
+                }
+            },
+        };
+
+        let handle_wait = h.handle_wait.as_ref().map(|ident| {
+            quote! {
+                fn handle_wait<'a>(
+                    &'a mut self,
+                    msg: #msg,
+                    ctx: actix_async::prelude::Context<'a, Self>,
+                ) -> impl ::core::future::Future<Output = #output> + 'a {
+                    Self::#ident(self, msg, ctx)
+                }
+            }
+        });
+
+        quote! {
+            impl #impl_generics actix_async::prelude::Handler<#msg> for #self_ty #where_clause {
+                #handle
+                #handle_wait
+            }
+        }
+    });
 
     let result = quote! {
-        #[actix_async::__async_trait(?Send)]
         #input
+        #(#impls)*
+    };
+
+    result.into()
+}
+
+struct HandlerMethods {
+    key: String,
+    msg: syn::Type,
+    output: syn::Type,
+    handle: Option<syn::Ident>,
+    handle_wait: Option<syn::Ident>,
+    // message is only handled by Handler::handle_wait.
+    exclusive: bool,
+}
+
+// remove #[handle] marker from method attributes. return if it's marked exclusive or None when
+// method is not marked.
+fn take_handle_attr(attrs: &mut Vec<syn::Attribute>) -> syn::Result<Option<bool>> {
+    let idx = match attrs.iter().position(|attr| attr.path.is_ident("handle")) {
+        Some(idx) => idx,
+        None => return Ok(None),
+    };
+
+    let attr = attrs.remove(idx);
+    if attr.tokens.is_empty() {
+        return Ok(Some(false));
+    }
+
+    let ident = attr.parse_args::<syn::Ident>()?;
+    if ident != "exclusive" {
+        return Err(syn::Error::new_spanned(ident, "expected `exclusive`"));
+    }
+
+    Ok(Some(true))
+}
+
+// check if method is a handler. return if it takes &mut self and the message type.
+fn handler_method(sig: &syn::Signature) -> Option<(bool, &syn::Type)> {
+    if sig.asyncness.is_none() || sig.inputs.len() != 3 {
+        return None;
+    }
+
+    let mut inputs = sig.inputs.iter();
+
+    let mutable = match inputs.next()? {
+        syn::FnArg::Receiver(recv) if recv.reference.is_some() => recv.mutability.is_some(),
+        _ => return None,
+    };
+
+    let msg = match inputs.next()? {
+        syn::FnArg::Typed(pat) => &*pat.ty,
+        _ => return None,
+    };
+
+    // last argument must be Context.
+    match inputs.next()? {
+        syn::FnArg::Typed(pat) => match *pat.ty {
+            syn::Type::Path(ref path) if path.path.segments.last()?.ident == "Context" => {}
+            _ => return None,
+        },
+        _ => return None,
+    }
+
+    Some((mutable, msg))
+}
+
+/// Derive [Message](actix_async::prelude::Message) trait for a type.
+///
+/// The result type is set by `rtype` attribute and default to `()` when it's omitted.
+///
+/// # Examples
+///
+/// ```ignore
+/// #[derive(Message)]
+/// #[rtype(result = "u32")]
+/// struct Msg;
+///
+/// // short form of rtype attribute.
+/// #[derive(Message)]
+/// #[rtype(Vec<T>)]
+/// struct GenericMsg<T: Clone>(T);
+/// ```
+/// # Expend
+///
+/// macro would expend into following code
+/// ```ignore
+/// impl actix_async::prelude::Message for Msg {
+///     type Result = u32;
+/// }
+///
+/// impl<T: Clone> actix_async::prelude::Message for GenericMsg<T>
+/// where
+///     T: 'static,
+///     Vec<T>: Send + 'static,
+/// {
+///     type Result = Vec<T>;
+/// }
+/// ```
+#[proc_macro_derive(Message, attributes(rtype))]
+pub fn message(item: TokenStream) -> TokenStream {
+    let mut input = syn::parse_macro_input!(item as syn::DeriveInput);
+
+    let mut rtype = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("rtype"))
+    {
+        if rtype.is_some() {
+            return syn::Error::new_spanned(attr, "duplicate #[rtype] attribute")
+                .to_compile_error()
+                .into();
+        }
+
+        match attr.parse_args_with(parse_rtype) {
+            Ok(ty) => rtype = Some(ty),
+            Err(e) => return e.to_compile_error().into(),
+        }
+    }
+
+    let rtype = rtype.unwrap_or_else(|| syn::parse_quote!(()));
+
+    // generic message must be 'static and so does it's result.
+    if !input.generics.params.is_empty() {
+        let idents = input
+            .generics
+            .type_params()
+            .map(|param| param.ident.clone())
+            .collect::<Vec<_>>();
+        let where_clause = input.generics.make_where_clause();
+        for ident in idents {
+            where_clause
+                .predicates
+                .push(syn::parse_quote!(#ident: 'static));
+        }
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#rtype: Send + 'static));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let result = quote! {
+        impl #impl_generics actix_async::prelude::Message for #name #ty_generics #where_clause {
+            type Result = #rtype;
+        }
     };
 
     result.into()
 }
+
+// parse `result = "Type"` or `Type` in rtype attribute.
+fn parse_rtype(input: syn::parse::ParseStream) -> syn::Result<syn::Type> {
+    if input.peek(syn::Ident) && input.peek2(syn::Token![=]) {
+        let ident = input.parse::<syn::Ident>()?;
+        if ident != "result" {
+            return Err(syn::Error::new(
+                ident.span(),
+                "expected `result = \"Type\"` or `Type`",
+            ));
+        }
+        input.parse::<syn::Token![=]>()?;
+        let lit = input.parse::<syn::LitStr>()?;
+        lit.parse()
+    } else {
+        input.parse()
+    }
+}
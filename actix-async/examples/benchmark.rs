@@ -49,6 +49,8 @@ fn main() -> std::io::Result<()> {
             let res = addr.stop(true).await;
 
             assert!(res.is_ok());
+
+            bench_channel().await;
         }));
 
     POLL.store(0, Ordering::SeqCst);
@@ -80,12 +82,51 @@ fn main() -> std::io::Result<()> {
     Ok(())
 }
 
+// A benchmark for the cost of sending messages through the channel of Addr and LocalAddr.
+async fn bench_channel() {
+    const COUNT: usize = 100_000;
+
+    let addr = actix_async::prelude::Actor::start(MyActor {
+        state: Cell::new(0),
+    });
+
+    let now = Instant::now();
+
+    for _ in 0..COUNT {
+        addr.send(Ping).await.unwrap();
+    }
+
+    println!(
+        "actix-async Addr channel bench finished.\r\nTotal time used for {} messages: {:?}\r\n",
+        COUNT,
+        now.elapsed()
+    );
+
+    let addr = <MyActor as actix_async::prelude::Actor>::builder().start_local(MyActor {
+        state: Cell::new(0),
+    });
+
+    let now = Instant::now();
+
+    for _ in 0..COUNT {
+        addr.send(Ping).await.unwrap();
+    }
+
+    println!(
+        "actix-async LocalAddr channel bench finished.\r\nTotal time used for {} messages: {:?}\r\n",
+        COUNT,
+        now.elapsed()
+    );
+}
+
 struct MyActor {
     state: Cell<u64>,
 }
 
 struct Msg;
 
+struct Ping;
+
 mod impl_actix_async {
     use super::*;
 
@@ -112,6 +153,13 @@ mod impl_actix_async {
             CountedFuture(sleep(Duration::from_millis(state))).await;
         }
     }
+
+    message!(Ping, ());
+
+    #[actix_async::handler]
+    impl Handler<Ping> for MyActor {
+        async fn handle(&self, _: Ping, _: Context<'_, Self>) {}
+    }
 }
 
 mod impl_actix {
@@ -1,12 +1,32 @@
-use core::future::{ready, Future};
+use core::{
+    any::Any,
+    fmt,
+    future::{ready, Future},
+    marker::PhantomData,
+    mem,
+    pin::Pin,
+    task::{Context as StdContext, Poll},
+    time::Duration,
+};
 
 use alloc::boxed::Box;
 
-use super::address::Addr;
+use super::address::{dead_letter_hook, Addr, DeadLetter, DeadLetterReason, LocalAddr};
 use super::context::Context;
 use super::context_future::{ContextFuture, ContextInner};
-use super::runtime::RuntimeService;
-use super::util::{channel::channel, futures::LocalBoxFuture};
+use super::error::StartError;
+use super::lifecycle::Lifecycle;
+use super::message::{ActorMessage, RateLimiter};
+#[cfg(feature = "std")]
+use super::observer::MailboxObserver;
+use super::runtime::{MissedTickBehavior, RuntimeService};
+#[cfg(feature = "tokio-rt")]
+use super::util::channel::{Receiver, Sender};
+use super::util::{
+    channel::{local_channel, oneshot, ChannelBuilder, OneshotReceiver},
+    futures::{poll_fn, ready, LocalBoxFuture},
+    smart_pointer::RefCounter,
+};
 
 /// trait for stateful async actor.
 pub trait Actor: Sized + 'static {
@@ -14,31 +34,81 @@ pub trait Actor: Sized + 'static {
     type Runtime: RuntimeService;
 
     /// async hook before actor start to run.
-    fn on_start<'act, 'ctx, 'res>(
-        &'act mut self,
-        ctx: Context<'ctx, Self>,
-    ) -> LocalBoxFuture<'res, ()>
-    where
-        'act: 'res,
-        'ctx: 'res,
-    {
-        Box::pin(async move {
+    fn on_start<'a>(&'a mut self, ctx: Context<'a, Self>) -> impl Future<Output = ()> + 'a {
+        async move {
             let _ = ctx;
-        })
+        }
     }
 
-    /// async hook before actor stops
-    fn on_stop<'act, 'ctx, 'res>(
-        &'act mut self,
-        ctx: Context<'ctx, Self>,
-    ) -> LocalBoxFuture<'res, ()>
-    where
-        'act: 'res,
-        'ctx: 'res,
-    {
-        Box::pin(async move {
-            let _ = ctx;
-        })
+    /// fallible async hook before actor start to run.
+    ///
+    /// Returning an error would abort the startup of actor. `Actor::on_stop` would not be called
+    /// and the actor's channel would be closed with all the pending messages dropped.
+    /// The error can be observed by starting actor with `Actor::try_start`.
+    ///
+    /// Default to call `Actor::on_start` and always succeed.
+    fn on_start_fallible<'a>(
+        &'a mut self,
+        ctx: Context<'a, Self>,
+    ) -> impl Future<Output = Result<(), StartError>> + 'a {
+        async move {
+            self.on_start(ctx).await;
+            Ok(())
+        }
+    }
+
+    /// async hook before actor stops. `StopReason` tells why the actor is stopping.
+    fn on_stop<'a>(
+        &'a mut self,
+        reason: StopReason,
+        ctx: Context<'a, Self>,
+    ) -> impl Future<Output = ()> + 'a {
+        async move {
+            let _ = (reason, ctx);
+        }
+    }
+
+    /// async hook when actor is idle. See `Actor::idle_timeout`.
+    ///
+    /// It's called as an exclusive task and would be called again for every idle timeout if the
+    /// actor stays idle.
+    ///
+    /// Default to stop the actor gracefully.
+    fn on_idle<'a>(&'a mut self, ctx: Context<'a, Self>) -> impl Future<Output = ()> + 'a {
+        async move {
+            ctx.stop();
+        }
+    }
+
+    /// async hook when actor's channel is saturated. See `Actor::mailbox_full_threshold`.
+    ///
+    /// It's called as an exclusive task and would not be called again until the count of queued
+    /// messages drops below the threshold.
+    ///
+    /// Default to do nothing.
+    fn on_mailbox_full<'a>(&'a mut self, _: Context<'a, Self>) -> impl Future<Output = ()> + 'a {
+        async {}
+    }
+
+    #[cfg(feature = "std")]
+    /// async hook when a task of actor panicked. Only called when `Actor::trap_panic` is enabled.
+    ///
+    /// It's called after all concurrent tasks are resolved and before any new message is handled.
+    /// The returned `PanicAction` decides what happens to the actor next.
+    ///
+    /// *. `PanicInfo` is borrowed so it's original payload can be resumed by
+    /// `PanicAction::Restart`.
+    ///
+    /// Default to `PanicAction::Continue`.
+    fn on_panic<'a>(
+        &'a mut self,
+        info: &'a PanicInfo,
+        ctx: Context<'a, Self>,
+    ) -> impl Future<Output = PanicAction> + 'a {
+        async move {
+            let _ = (info, ctx);
+            PanicAction::Continue
+        }
     }
 
     #[cfg(feature = "tokio-rt")]
@@ -54,12 +124,103 @@ pub trait Actor: Sized + 'static {
         }
     }
 
+    #[cfg(feature = "std")]
+    /// trap panic happen in `Handler::handle` and `Handler::handle_wait`.
+    ///
+    /// When `true` is returned every concurrent and exclusive task of actor is wrapped in
+    /// `std::panic::catch_unwind`. A panicked task is dropped with it's message and the actor
+    /// keeps running. The sender of the message would receive an error.
+    ///
+    /// *. `Actor::on_start` and `Actor::on_stop` are not trapped.
+    ///
+    /// *. Actor state mutated before the panic happened is not rolled back.
+    ///
+    /// Default to `false`.
+    #[inline]
+    fn trap_panic() -> bool {
+        false
+    }
+
     /// start the actor on current thread and return it's address
     #[inline]
     fn start(self) -> Addr<Self> {
         Self::create(|_| self)
     }
 
+    /// start the actor on current thread with given capacity of it's channel.
+    ///
+    /// See `ActorBuilder::capacity` for detail.
+    #[inline]
+    fn start_with_capacity(self, cap: usize) -> Addr<Self> {
+        Self::builder().capacity(cap).start(self)
+    }
+
+    /// builder of actor with configuration of the instance.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     // capacity is read from config and not a constant.
+    ///     let cap = std::env::var("MAILBOX_CAPACITY")
+    ///         .ok()
+    ///         .and_then(|cap| cap.parse().ok())
+    ///         .unwrap_or(1024);
+    ///
+    ///     let addr = TestActor::builder().capacity(cap).start(TestActor);
+    ///
+    ///     let res = addr.run(|_, _| Box::pin(async { 996 })).await;
+    ///     assert_eq!(res.unwrap(), 996);
+    /// }
+    /// ```
+    #[inline]
+    fn builder() -> ActorBuilder<Self> {
+        ActorBuilder::new()
+    }
+
+    /// start the actor on current thread and return a future resolving to it's address after
+    /// `Actor::on_start_fallible` finished.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::error::StartError;
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    ///
+    /// #[actix_async::handler]
+    /// impl Actor for TestActor {
+    ///     type Runtime = TokioRuntime;
+    ///
+    ///     async fn on_start_fallible(&mut self, _: Context<'_, Self>) -> Result<(), StartError> {
+    ///         Err(StartError::new("can not open file"))
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let res = TestActor.try_start().await;
+    ///     assert!(res.is_err());
+    /// }
+    /// ```
+    fn try_start(self) -> StartHandle<Self> {
+        let (tx, rx) = oneshot();
+
+        let (addr, fut) = _create_context(ActorBuilder::new(), |_| ready(self));
+
+        <Self::Runtime as RuntimeService>::spawn(async move {
+            let ctx_fut = fut.await;
+            ctx_fut.run_with(Some(tx)).await;
+        });
+
+        StartHandle::new(addr, rx)
+    }
+
     /// create actor with closure
     #[inline]
     fn create<F>(f: F) -> Addr<Self>
@@ -111,17 +272,128 @@ pub trait Actor: Sized + 'static {
         F: for<'c> FnOnce(Context<'c, Self>) -> Fut + 'static,
         Fut: Future<Output = Self> + 'static,
     {
-        let (tx, fut) = _create_context(f);
+        Self::builder().create_async(f)
+    }
+
+    /// create actor with fallible async closure.
+    ///
+    /// Return the address of actor and a future constructing the actor. The actor is spawned
+    /// to `Actor::Runtime` when the closure succeed and the future resolves with `Ok(())`.
+    /// When the closure failed the future resolves with it's error and all messages sent to
+    /// the address are resolved with `ActixAsyncError::Closed`.
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let (addr, fut) = TestActor::try_create_async(|_| async { Err::<TestActor, _>(996) });
+    ///
+    ///     assert_eq!(fut.await, Err(996));
+    ///     assert_eq!(
+    ///         addr.run(|_, _| Box::pin(async {})).await,
+    ///         Err(ActixAsyncError::Closed)
+    ///     );
+    /// }
+    /// ```
+    fn try_create_async<F, Fut, E>(f: F) -> (Addr<Self>, LocalBoxFuture<'static, Result<(), E>>)
+    where
+        F: for<'c> FnOnce(Context<'c, Self>) -> Fut + 'static,
+        Fut: Future<Output = Result<Self, E>> + 'static,
+        E: 'static,
+    {
+        let (tx, ctx) = _create_inner(ActorBuilder::new());
+
+        let fut = Box::pin(async move {
+            let ctx_fut = ContextFuture::try_start(f, ctx).await?;
+            <Self::Runtime as RuntimeService>::spawn(ctx_fut.run());
+            Ok(())
+        });
+
+        (tx, fut)
+    }
+
+    /// create actor with async closure lazily.
+    ///
+    /// The address is returned immediately and the closure would not be called until the first
+    /// message arrives at actor. When all addresses are dropped before any message is sent the
+    /// closure would never be called.
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    /// use futures_util::FutureExt;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor::start_lazy(|_| async {
+    ///         // only constructed when addr.run is called.
+    ///         TestActor
+    ///     });
+    ///
+    ///     let res = addr.run(|_, _| async { 996 }.boxed_local()).await;
+    ///     assert_eq!(996, res.unwrap());
+    /// }
+    /// ```
+    fn start_lazy<F, Fut>(f: F) -> Addr<Self>
+    where
+        F: for<'c> FnOnce(Context<'c, Self>) -> Fut + 'static,
+        Fut: Future<Output = Self> + 'static,
+    {
+        let (tx, ctx) = _create_inner(ActorBuilder::new());
 
         <Self::Runtime as RuntimeService>::spawn(async move {
-            let ctx_fut = fut.await;
-            ctx_fut.run().await;
+            if poll_fn(|cx| ctx.rx.borrow_mut().poll_wait(cx)).await {
+                let ctx_fut = ContextFuture::start(f, ctx).await;
+                ctx_fut.run().await;
+            } else {
+                ctx.set_stop_reason(StopReason::AddrDropped);
+            }
         });
 
         tx
     }
 
-    /// create actor with async closure and expose it's [`ContextFuture`](super::context_future::ContextFuture).
+    /// construct the actor and return it's address with the future driving it without spawning
+    /// it to `Actor::Runtime`.
+    ///
+    /// The future can be embedded into other executors, selected against other futures or polled
+    /// step by step. The actor would not handle any message until the future is polled and would
+    /// stop when the future is dropped.
+    /// # example:
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use actix_async::prelude::*;
+    /// use futures_util::FutureExt;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let (addr, fut) = TestActor.into_future();
+    ///
+    ///     let res = tokio::select! {
+    ///         _ = fut => panic!("actor should not stop"),
+    ///         res = addr.run(|_, _| async { 996 }.boxed_local()) => res
+    ///     };
+    ///
+    ///     assert_eq!(996, res.unwrap());
+    /// }
+    /// ```
+    fn into_future(self) -> (Addr<Self>, LocalBoxFuture<'static, ()>) {
+        let (tx, fut) = _create_context(ActorBuilder::new(), |_| ready(self));
+
+        (tx, Box::pin(async move { fut.await.run().await }))
+    }
+
+    /// create actor with async closure and expose it's [`ContextFuture`](crate::context::ContextFuture).
     /// ContextFuture can be used to delay the start of actor and take control of when and where it would be polled.
     /// # example:
     /// ```rust
@@ -172,7 +444,7 @@ pub trait Actor: Sized + 'static {
         F: for<'c> FnOnce(Context<'c, Self>) -> Fut + 'static,
         Fut: Future<Output = Self> + 'static,
     {
-        let (tx, fut) = _create_context(f);
+        let (tx, fut) = _create_context(ActorBuilder::new(), f);
 
         (tx, Box::pin(fut))
     }
@@ -186,21 +458,262 @@ pub trait Actor: Sized + 'static {
     fn size_hint() -> usize {
         256
     }
+
+    /// lower and upper bound of actor's channel capacity.
+    ///
+    /// When `Some((min, max))` is returned the channel capacity becomes adaptive. It starts from
+    /// `Actor::size_hint` and doubles(up to max) when a sender observes a full channel. It halves
+    /// (down to min) when the actor keeps draining the channel to empty.
+    ///
+    /// *. The limit of concurrent async tasks is not affected and stays as `Actor::size_hint`.
+    ///
+    /// Default to `None`. (Fixed capacity.)
+    #[inline]
+    fn size_hint_bounds() -> Option<(usize, usize)> {
+        None
+    }
+
+    /// idle timeout of actor.
+    ///
+    /// When `Some(dur)` is returned `Actor::on_idle` would be called when the actor has received
+    /// no message from it's `Addr`, streams and delayed closures for the duration.
+    ///
+    /// *. The idle timer is not reset on every message for performance reason. The idle state
+    /// is detected between one and two times of the duration after the last message.
+    ///
+    /// Default to `None`. (No idle timeout.)
+    #[inline]
+    fn idle_timeout() -> Option<Duration> {
+        None
+    }
+
+    /// time limit of handling a message.
+    ///
+    /// When `Some(dur)` is returned a `Handler::handle` or `Handler::handle_wait` task not
+    /// finished in the duration is dropped and the sender of message would receive
+    /// `ActixAsyncError::HandlerTimeout`. A stuck concurrent task would no longer block the
+    /// following exclusive messages.
+    ///
+    /// *. The duration starts when actor starts handling the message. Time spent in actor's
+    /// channel is not counted.
+    ///
+    /// *. Actor state mutated before the task is dropped is not rolled back.
+    ///
+    /// Default to `None`. (No time limit.)
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    ///
+    /// impl Actor for TestActor {
+    ///     type Runtime = TokioRuntime;
+    ///
+    ///     fn handler_timeout() -> Option<Duration> {
+    ///         Some(Duration::from_millis(100))
+    ///     }
+    /// }
+    ///
+    /// struct Stuck;
+    /// message!(Stuck, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Stuck> for TestActor {
+    ///     async fn handle(&self, _: Stuck, _: Context<'_, Self>) {
+    ///         std::future::pending::<()>().await;
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let res = addr.send(Stuck).await;
+    ///     assert_eq!(res, Err(ActixAsyncError::HandlerTimeout));
+    /// }
+    /// ```
+    #[inline]
+    fn handler_timeout() -> Option<Duration> {
+        None
+    }
+
+    /// behavior of intervals on context when ticks are missed. See `MissedTickBehavior`.
+    ///
+    /// Default to `MissedTickBehavior::Delay`.
+    #[inline]
+    fn missed_tick_behavior() -> MissedTickBehavior {
+        MissedTickBehavior::Delay
+    }
+
+    /// count of consecutive polls of actor without running concurrent task before shrinking
+    /// actor's task queue and caches back to their initial capacity.
+    ///
+    /// After a burst of messages the task queue and caches keep their peak capacity. Shrinking
+    /// them would release the memory for actors stay idle afterward.
+    ///
+    /// Default to `None`. (Never shrink.)
+    #[inline]
+    fn shrink_policy() -> Option<usize> {
+        None
+    }
+
+    /// memory budget in bytes of actor's channel.
+    ///
+    /// When `Some(bytes)` is returned the total size of queued messages is limited and a send
+    /// operation would wait when the budget is exceeded, even if the channel still has capacity.
+    ///
+    /// *. The size of a message is estimated with `Message::size_hint_bytes`. By default heap
+    /// allocations owned by message are not counted.
+    ///
+    /// *. A message is always accepted by an empty channel regardless of it's size.
+    ///
+    /// *. `Addr::do_send` and `Addr::do_wait` ignore the budget like they ignore the capacity.
+    ///
+    /// Default to `None`. (No memory budget.)
+    #[inline]
+    fn mailbox_budget() -> Option<usize> {
+        None
+    }
+
+    /// count of queued messages in actor's channel that triggers `Actor::on_mailbox_full`.
+    ///
+    /// *. The count is checked when actor polls it's channel. Messages sent by `Addr::do_send`
+    /// and `Addr::do_wait` are counted.
+    ///
+    /// Senders can observe a full channel without waiting with `MessageRequest::fail_fast`.
+    ///
+    /// Default to `None`. (Never called.)
+    #[inline]
+    fn mailbox_full_threshold() -> Option<usize> {
+        None
+    }
+
+    /// count of queued messages actor's channel must drop below before `Addr::ready` resolves.
+    ///
+    /// *. Messages sent by `Addr::do_send` and `Addr::do_wait` are counted.
+    ///
+    /// Default to `None`. (Half of the channel capacity.)
+    #[inline]
+    fn mailbox_low_watermark() -> Option<usize> {
+        None
+    }
+
+    /// policy applied when a message is sent to a full channel of actor. See `OverflowPolicy`
+    /// for detail.
+    ///
+    /// Default to `OverflowPolicy::Block`.
+    #[inline]
+    fn overflow_policy() -> OverflowPolicy {
+        OverflowPolicy::Block
+    }
+
+    /// hook receiving messages can not be delivered to actor. See `DeadLetterReason` for when it
+    /// happens.
+    ///
+    /// *. It's called on the thread where message is dropped and not on the actor.
+    ///
+    /// *. Messages sent through `LocalAddr` can be `!Send` and are never passed to this hook.
+    /// They are dropped on actor's thread instead.
+    ///
+    /// Default to pass the message to global hook set by `address::set_dead_letter_hook` and
+    /// drop it when no hook is set.
+    #[inline]
+    fn on_dead_letter(letter: DeadLetter<Self>) {
+        dead_letter_hook(letter.into_any())
+    }
+}
+
+#[cfg(feature = "tokio-rt")]
+pub(crate) fn actor_channel<A: Actor>(
+    cap: usize,
+) -> (Sender<ActorMessage<A>>, Receiver<ActorMessage<A>>) {
+    actor_channel_builder::<A>(cap).build()
+}
+
+fn actor_channel_builder<A: Actor>(cap: usize) -> ChannelBuilder<ActorMessage<A>> {
+    let mut builder = ChannelBuilder::new(cap);
+
+    if let Some((min, max)) = A::size_hint_bounds() {
+        builder = builder.adaptive(min, max);
+    }
+
+    if let Some(bytes) = A::mailbox_budget() {
+        builder = builder.budget(bytes, ActorMessage::size);
+    }
+
+    if let Some(low_watermark) = A::mailbox_low_watermark() {
+        builder = builder.low_watermark(low_watermark);
+    }
+
+    builder
+        .overflow(A::overflow_policy())
+        .dead_letter(dead_letter::<A>)
 }
 
-fn _create_context<A, F, Fut>(f: F) -> (Addr<A>, impl Future<Output = ContextFuture<A>>)
+// notify observer with messages pushed to actor's channel.
+#[cfg(feature = "std")]
+fn observe_enqueue<A: Actor>(
+    observer: RefCounter<dyn MailboxObserver>,
+) -> impl Fn(&ActorMessage<A>) + Send + Sync + 'static {
+    move |msg| {
+        // actor state messages are internal and not observed.
+        if !matches!(msg, ActorMessage::State(..)) {
+            observer.on_enqueue(msg.type_name());
+        }
+    }
+}
+
+pub(crate) fn dead_letter<A: Actor>(msg: ActorMessage<A>, reason: DeadLetterReason) {
+    // actor state messages are internal and not dead letters.
+    if !matches!(msg, ActorMessage::State(..)) {
+        A::on_dead_letter(DeadLetter::new(msg, reason))
+    }
+}
+
+fn _create_context<A, F, Fut>(
+    builder: ActorBuilder<A>,
+    f: F,
+) -> (Addr<A>, impl Future<Output = ContextFuture<A>>)
 where
     A: Actor,
     F: for<'c> FnOnce(Context<'c, A>) -> Fut + 'static,
     Fut: Future<Output = A>,
 {
-    let (tx, rx) = channel(A::size_hint());
+    let (tx, ctx) = _create_inner(builder);
+
+    (tx, ContextFuture::start(f, ctx))
+}
 
-    let tx = Addr::new(tx);
+fn _create_inner<A: Actor>(builder: ActorBuilder<A>) -> (Addr<A>, ContextInner<A>) {
+    let channel = actor_channel_builder::<A>(builder.capacity);
 
-    let ctx = ContextInner::new(rx);
+    #[cfg(feature = "std")]
+    let channel = match builder.observer.clone() {
+        Some(observer) => channel.on_push(observe_enqueue::<A>(observer)),
+        None => channel,
+    };
 
-    (tx, ContextFuture::start(f, ctx))
+    let (tx, rx) = channel.build();
+
+    let lifecycle = RefCounter::new(Lifecycle::new());
+
+    let tx = Addr::new(tx, lifecycle.clone());
+
+    let limiter = builder
+        .rate_limit
+        .map(|(count, dur)| RateLimiter::new(count, dur));
+
+    let ctx = ContextInner::new(rx, lifecycle)
+        .rate_limit(limiter)
+        .ordered(builder.ordered);
+
+    #[cfg(feature = "std")]
+    let ctx = ctx.observer(builder.observer);
+
+    (tx, ctx)
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
@@ -209,3 +722,394 @@ pub enum ActorState {
     Stop,
     StopGraceful,
 }
+
+/// the reason actor is stopping. Passed to `Actor::on_stop`.
+///
+/// *. `StopReason::Custom` is never equal to any reason. Compare it's payload with
+/// `CustomReason::downcast_ref` instead.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum StopReason {
+    /// stopped gracefully by `Context::stop` or `Addr::stop(true)`.
+    Graceful,
+    /// stopped forcefully by `Addr::stop(false)`.
+    Forced,
+    /// all `Addr` of actor are dropped.
+    AddrDropped,
+    /// stopped by `PanicAction::Stop` returned from `Actor::on_panic`.
+    Panic,
+    /// stopping for restart by `PanicAction::Restart` returned from `Actor::on_panic`.
+    Restart,
+    /// `Actor::on_start_fallible` returned an error.
+    StartFailed,
+    /// actor finished it's work. stopped by `Context::stop_with`.
+    Finished,
+    /// actor run into an unrecoverable error. stopped by `Context::stop_with`.
+    Failed,
+    /// application defined reason. stopped by `Context::stop_with`. See `CustomReason`.
+    ///
+    /// *. Implement `From<T> for StopReason` to stop actor with a typed reason.
+    Custom(CustomReason),
+}
+
+impl StopReason {
+    /// construct `StopReason::Custom` with given typed reason.
+    #[inline]
+    pub fn custom<T>(reason: T) -> Self
+    where
+        T: Any + Send + Sync,
+    {
+        Self::Custom(CustomReason(RefCounter::new(reason)))
+    }
+}
+
+impl PartialEq for StopReason {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Custom(_), _) | (_, Self::Custom(_)) => false,
+            _ => mem::discriminant(self) == mem::discriminant(other),
+        }
+    }
+}
+
+/// application defined stop reason with a typed payload. See `StopReason::custom`.
+#[derive(Clone)]
+pub struct CustomReason(RefCounter<dyn Any + Send + Sync>);
+
+impl CustomReason {
+    /// check if the payload is type `T`.
+    #[inline]
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+
+    /// reference of the payload when it's type `T`.
+    #[inline]
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+}
+
+impl fmt::Debug for CustomReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CustomReason {{ .. }}")
+    }
+}
+
+/// policy applied when a message is sent to a full channel of actor.
+///
+/// *. Messages sent by `Addr::stop`, `Addr::send_priority` and other control messages are not
+/// affected.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum OverflowPolicy {
+    /// `Addr::send` waits for the channel to have space. `Addr::do_send` ignores the capacity
+    /// of channel.
+    Block,
+    /// the new message is dropped. `Addr::send` resolves with `ActixAsyncError::MailboxFull` and
+    /// message sent by `Addr::do_send` is passed to `Actor::on_dead_letter`.
+    Fail,
+    /// the oldest message in channel is evicted and passed to `Actor::on_dead_letter` to make
+    /// space for the new message. The sender of evicted message would receive an error.
+    DropOldest,
+    /// the new message is dropped and passed to `Actor::on_dead_letter`. `Addr::send` resolves
+    /// with `ActixAsyncError::MailboxFull`.
+    DropNewest,
+}
+
+/// Builder of actor with configuration of the instance. See `Actor::builder`.
+pub struct ActorBuilder<A> {
+    capacity: usize,
+    rate_limit: Option<(usize, Duration)>,
+    ordered: bool,
+    #[cfg(feature = "std")]
+    observer: Option<RefCounter<dyn MailboxObserver>>,
+    _act: PhantomData<A>,
+}
+
+impl<A: Actor> ActorBuilder<A> {
+    fn new() -> Self {
+        Self {
+            capacity: A::size_hint(),
+            rate_limit: None,
+            ordered: false,
+            #[cfg(feature = "std")]
+            observer: None,
+            _act: PhantomData,
+        }
+    }
+
+    /// capacity of actor's channel. Override `Actor::size_hint` for the channel of this instance.
+    ///
+    /// *. The limit of concurrent async tasks is not affected and stays as `Actor::size_hint`.
+    ///
+    /// *. When `Actor::size_hint_bounds` is set the capacity is clamped to the bounds.
+    pub fn capacity(mut self, cap: usize) -> Self {
+        self.capacity = cap;
+        self
+    }
+
+    /// limit the rate of messages actor takes from it's channel to count of messages per given
+    /// duration. Messages exceeding the rate stay in channel and apply backpressure to senders.
+    ///
+    /// *. Messages sent by `Addr::send_priority` are limited. Actor state messages like
+    /// `Addr::stop` are not counted but can be delayed until the rate allows.
+    ///
+    /// # Panics
+    /// panic when count is zero.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     // actor handles at most 100 messages per second.
+    ///     let addr = TestActor::builder()
+    ///         .rate_limit(100, Duration::from_secs(1))
+    ///         .start(TestActor);
+    ///
+    ///     let res = addr.run(|_, _| Box::pin(async { 996 })).await;
+    ///     assert_eq!(res.unwrap(), 996);
+    /// }
+    /// ```
+    pub fn rate_limit(mut self, count: usize, per: Duration) -> Self {
+        assert!(count > 0, "rate limit count cannot be zero");
+        self.rate_limit = Some((count, per));
+        self
+    }
+
+    /// complete concurrent messages sent from the same `Addr` in the order they are received.
+    ///
+    /// Messages are still handled concurrently. A message finished early holds it's result
+    /// until all previous messages from the same `Addr` are completed.
+    ///
+    /// *. Every clone of `Addr` is a different sender. Messages from different clones are not
+    /// ordered.
+    ///
+    /// *. Messages sent by `Addr::do_send` are ordered too. A message dropped before it's
+    /// handled(See `MessageRequest::ttl`) does not block the following messages.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Sleep(u64);
+    /// message!(Sleep, u64);
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Sleep> for TestActor {
+    ///     async fn handle(&self, msg: Sleep, _: Context<'_, Self>) -> u64 {
+    ///         tokio::time::sleep(Duration::from_millis(msg.0)).await;
+    ///         msg.0
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor::builder().ordered().start(TestActor);
+    ///
+    ///     let now = Instant::now();
+    ///     let first = addr.try_send(Sleep(200)).ok().unwrap();
+    ///     let second = addr.try_send(Sleep(100)).ok().unwrap();
+    ///
+    ///     // second message completes after the first one.
+    ///     assert_eq!(second.await.unwrap(), 100);
+    ///     assert!(now.elapsed() >= Duration::from_millis(200));
+    ///     assert_eq!(first.await.unwrap(), 200);
+    /// }
+    /// ```
+    pub fn ordered(mut self) -> Self {
+        self.ordered = true;
+        self
+    }
+
+    /// install an observer of actor's mailbox. See `MailboxObserver` for example.
+    ///
+    /// *. Messages from streams and tasks added to `Context` do not go through mailbox and are
+    /// not observed.
+    #[cfg(feature = "std")]
+    pub fn observer<O: MailboxObserver>(mut self, observer: O) -> Self {
+        self.observer = Some(RefCounter::new(observer));
+        self
+    }
+
+    /// start the actor on current thread and return it's address. See `Actor::start`.
+    pub fn start(self, act: A) -> Addr<A> {
+        self.create_async(|_| ready(act))
+    }
+
+    /// create actor with async closure. See `Actor::create_async`.
+    pub fn create_async<F, Fut>(self, f: F) -> Addr<A>
+    where
+        F: for<'c> FnOnce(Context<'c, A>) -> Fut + 'static,
+        Fut: Future<Output = A> + 'static,
+    {
+        let (tx, fut) = _create_context(self, f);
+
+        <A::Runtime as RuntimeService>::spawn(async move {
+            let ctx_fut = fut.await;
+            ctx_fut.run().await;
+        });
+
+        tx
+    }
+
+    /// start the actor on current thread and return it's `LocalAddr`.
+    ///
+    /// Besides the channel of `Addr` the actor receives messages from a single thread channel
+    /// with the same capacity. Messages sent by `LocalAddr` skip the synchronization cost of
+    /// `Addr` when all senders live on the thread actor runs on.
+    ///
+    /// *. `OverflowPolicy`, `Actor::mailbox_budget` and `Actor::size_hint_bounds` only apply to
+    /// the channel of `Addr`.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::rc::Rc;
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// // local message does not have to be Send.
+    /// struct TestMessage(Rc<usize>);
+    /// message!(TestMessage, usize);
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<TestMessage> for TestActor {
+    ///     async fn handle(&self, msg: TestMessage, _: Context<'_, Self>) -> usize {
+    ///         *msg.0
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor::builder().start_local(TestActor);
+    ///
+    ///     let res = addr.send(TestMessage(Rc::new(996))).await;
+    ///     assert_eq!(res.unwrap(), 996);
+    ///
+    ///     // LocalAddr dereferences to Addr.
+    ///     assert!(addr.stop(true).await.is_ok());
+    /// }
+    /// ```
+    pub fn start_local(self, act: A) -> LocalAddr<A> {
+        let cap = self.capacity;
+        let (tx, rx) = local_channel(cap);
+
+        #[cfg(feature = "std")]
+        if let Some(observer) = self.observer.clone() {
+            tx.on_push(observe_enqueue::<A>(observer));
+        }
+
+        let (addr, ctx) = _create_inner(self);
+        let ctx = ctx.local(rx);
+
+        <A::Runtime as RuntimeService>::spawn(async move {
+            let ctx_fut = ContextFuture::start(|_| ready(act), ctx).await;
+            ctx_fut.run().await;
+        });
+
+        LocalAddr::new(addr, tx)
+    }
+}
+
+/// Future returned by `Actor::try_start`. Resolves with the address of actor when it's started
+/// or the error aborted the startup.
+pub struct StartHandle<A: Actor> {
+    addr: Option<Addr<A>>,
+    rx: OneshotReceiver<Result<(), StartError>>,
+}
+
+impl<A: Actor> StartHandle<A> {
+    pub(crate) fn new(addr: Addr<A>, rx: OneshotReceiver<Result<(), StartError>>) -> Self {
+        Self {
+            addr: Some(addr),
+            rx,
+        }
+    }
+}
+
+impl<A: Actor> Future for StartHandle<A> {
+    type Output = Result<Addr<A>, StartError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match ready!(Pin::new(&mut this.rx).poll(cx)) {
+            Ok(Ok(())) => Poll::Ready(Ok(this
+                .addr
+                .take()
+                .expect("StartHandle polled after finish"))),
+            Ok(Err(e)) => Poll::Ready(Err(e)),
+            // actor is dropped before it started.
+            Err(_) => Poll::Ready(Err(StartError::new("Actor is dropped before start"))),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// the decision made by `Actor::on_panic`.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum PanicAction {
+    /// keep the actor running.
+    Continue,
+    /// resume the panic with it's original payload. The actor future would exit with the panic
+    /// and a supervised actor would be restarted.
+    Restart,
+    /// stop the actor gracefully.
+    Stop,
+}
+
+#[cfg(feature = "std")]
+/// information of a panic trapped by actor.
+pub struct PanicInfo {
+    payload: Box<dyn core::any::Any + Send>,
+}
+
+#[cfg(feature = "std")]
+impl PanicInfo {
+    pub(crate) fn new(payload: Box<dyn core::any::Any + Send>) -> Self {
+        Self { payload }
+    }
+
+    /// the payload of panic.
+    #[inline]
+    pub fn payload(&self) -> &(dyn core::any::Any + Send) {
+        &*self.payload
+    }
+
+    /// the message of panic if the payload is a string.
+    pub fn message(&self) -> Option<&str> {
+        self.payload.downcast_ref::<&str>().copied().or_else(|| {
+            self.payload
+                .downcast_ref::<alloc::string::String>()
+                .map(|s| s.as_str())
+        })
+    }
+
+    /// take the payload of panic.
+    #[inline]
+    pub fn into_payload(self) -> Box<dyn core::any::Any + Send> {
+        self.payload
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Debug for PanicInfo {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PanicInfo")
+            .field("message", &self.message())
+            .finish()
+    }
+}
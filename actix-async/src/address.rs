@@ -1,30 +1,96 @@
-use core::ops::Deref;
+use core::{
+    any::Any,
+    fmt,
+    future::Future,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::Deref,
+    pin::Pin,
+    ptr,
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+    task::Poll,
+    time::Duration,
+};
+
+use alloc::{boxed::Box, vec::Vec};
 
-use alloc::boxed::Box;
+pub use super::lifecycle::{
+    ActorId, LifecycleEvent, LifecycleEvents, LifecycleState, Subscription, Watch,
+};
 
-use super::actor::{Actor, ActorState};
-use super::context::Context;
-use super::error::ActixAsyncError;
+use super::actor::{dead_letter, Actor, ActorState, StopReason};
+use super::context::{join_handle, Context, ContextJoinHandle};
+use super::error::{ActixAsyncError, TrySendError};
 use super::handler::Handler;
+use super::lifecycle::Lifecycle;
 use super::message::{
-    message_send_check, ActorMessage, FunctionMessage, FunctionMutMessage, Message,
+    message_send_check, ActorMessage, ActorMessageClone, BatchMessage, ConvertMessage,
+    FunctionMessage, FunctionMutMessage, FutureMessage, IntervalMessage, Message, MessageFactory,
+    StreamRequest,
+};
+use super::request::{
+    _MessageRequest, join_requests, BoxedMessageRequest, LocalMessageRequest, MessageRequest,
+    ResponseStream,
 };
-use super::request::{BoxedMessageRequest, MessageRequest, _MessageRequest};
 use super::runtime::RuntimeService;
+#[cfg(feature = "tokio-rt")]
+use super::system::SystemActor;
+#[cfg(feature = "futures-sink")]
+use super::util::channel::SenderSink;
+#[cfg(feature = "std")]
+use super::util::futures::block_on;
 use super::util::{
-    channel::{oneshot, OneshotSender, Receiver, Sender, WeakSender},
-    futures::LocalBoxFuture,
+    channel::{oneshot, LocalSender, OneshotSender, Receiver, Sender, WeakSender},
+    futures::{poll_fn, BoxFuture, LocalBoxFuture, Stream},
+    smart_pointer::RefCounter,
 };
 
 /// The message sink of `Actor` type. `Message` and boxed async blocks are sent to Actor through it.
-pub struct Addr<A>(Sender<ActorMessage<A>>);
+///
+/// Every clone of `Addr` has it's own id. See `ActorBuilder::ordered`.
+///
+/// `Addr` is compared and hashed by the identity of actor's channel. Clones of `Addr` are equal
+/// regardless their ids.
+pub struct Addr<A>(Sender<ActorMessage<A>>, RefCounter<Lifecycle>, usize);
 
 impl<A> Clone for Addr<A> {
     fn clone(&self) -> Self {
-        Self(self.0.clone())
+        Self(self.0.clone(), self.1.clone(), sender_id())
+    }
+}
+
+impl<A> PartialEq for Addr<A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.channel_id() == other.0.channel_id()
+    }
+}
+
+impl<A> Eq for Addr<A> {}
+
+impl<A> Hash for Addr<A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.channel_id().hash(state)
+    }
+}
+
+impl<A> fmt::Debug for Addr<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Addr")
+            .field("actor", &core::any::type_name::<A>())
+            .field("id", &self.2)
+            .field("state", &self.1.state())
+            .field("len", &self.0.len())
+            .field("capacity", &self.0.capacity())
+            .finish()
     }
 }
 
+// id of `Addr` tagged to the messages it sends. 0 is reserved for messages not from an `Addr`.
+fn sender_id() -> usize {
+    static ID: AtomicUsize = AtomicUsize::new(1);
+    ID.fetch_add(1, Ordering::Relaxed)
+}
+
 impl<A> Deref for Addr<A> {
     type Target = Sender<ActorMessage<A>>;
 
@@ -45,6 +111,491 @@ impl<A: Actor> Addr<A> {
         self._send(|tx| ActorMessage::new_ref(msg, Some(tx)))
     }
 
+    /// try to send a concurrent message to actor without waiting.
+    ///
+    /// Fails immediately and gives the message back when actor's channel is full or closed.
+    /// On success the message is already in actor's mailbox and the returned `MessageRequest` only
+    /// waits for the result.
+    ///
+    /// *. `Actor::overflow_policy` other than `OverflowPolicy::DropOldest` is ignored.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Ping;
+    /// message!(Ping, usize);
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Ping> for TestActor {
+    ///     async fn handle(&self, _: Ping, _: Context<'_, Self>) -> usize {
+    ///         996
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     match addr.try_send(Ping) {
+    ///         Ok(req) => assert_eq!(req.await.unwrap(), 996),
+    ///         // shed load when actor is busy.
+    ///         Err(e) => drop(e.into_inner()),
+    ///     }
+    /// }
+    /// ```
+    pub fn try_send<M>(&self, msg: M) -> Result<MessageRequest<A, M::Result>, TrySendError<M>>
+    where
+        M: Message + Send,
+        A: Handler<M>,
+    {
+        message_send_check::<M>();
+        let (tx, rx) = oneshot();
+        let msg = ActorMessage::new_ref(msg, Some(tx)).with_sender(self.2);
+        match self.deref().try_send(msg) {
+            Ok(()) => Ok(_MessageRequest::new(self.deref().sent(), rx)),
+            Err(e) => Err(e.map(ActorMessage::into_message)),
+        }
+    }
+
+    /// send a batch of concurrent messages to actor. The batch takes one slot of actor's channel
+    /// and resolves with the results in the same order of messages.
+    ///
+    /// Messages in batch are handled one by one with `Handler::handle` in a single concurrent
+    /// task.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Double(usize);
+    /// message!(Double, usize);
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Double> for TestActor {
+    ///     async fn handle(&self, msg: Double, _: Context<'_, Self>) -> usize {
+    ///         msg.0 * 2
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let res = addr.send_batch((1..4).map(Double).collect()).await;
+    ///     assert_eq!(res.unwrap(), vec![2, 4, 6]);
+    /// }
+    /// ```
+    #[inline]
+    pub fn send_batch<M>(&self, msgs: Vec<M>) -> MessageRequest<A, Vec<M::Result>>
+    where
+        M: Message + Send,
+        A: Handler<M>,
+    {
+        self._send::<BatchMessage<M>, _>(|tx| ActorMessage::new_ref(BatchMessage(msgs), Some(tx)))
+    }
+
+    /// send all items of stream to actor as concurrent messages and ignore the results.
+    ///
+    /// Every item waits for actor's channel to have space before the next one is pulled from
+    /// stream. Resolves when the stream is exhausted or with error when actor is closed.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    /// use futures_util::stream;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Log(usize);
+    /// message!(Log, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Log> for TestActor {
+    ///     async fn handle(&self, msg: Log, _: Context<'_, Self>) {
+    ///         println!("{}", msg.0);
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let res = addr.send_all(stream::iter((0..16).map(Log))).await;
+    ///     assert!(res.is_ok());
+    /// }
+    /// ```
+    pub async fn send_all<M, S>(&self, stream: S) -> Result<(), ActixAsyncError>
+    where
+        M: Message + Send,
+        A: Handler<M>,
+        S: Stream<Item = M>,
+    {
+        message_send_check::<M>();
+
+        let mut stream = Box::pin(stream);
+
+        while let Some(msg) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+            self.deref().send(ActorMessage::new_ref(msg, None)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// send a concurrent message to actor and block current thread until the result is
+    /// received. See `Addr::send`.
+    ///
+    /// It's for synchronous code running outside of actor's runtime. The thread is parked
+    /// when waiting for actor's channel and the result.
+    ///
+    /// *. Calling it on the thread actor runs on would block the actor and never return.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct TestMessage;
+    /// message!(TestMessage, usize);
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<TestMessage> for TestActor {
+    ///     async fn handle(&self, _: TestMessage, _: Context<'_, Self>) -> usize {
+    ///         996
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let res = tokio::task::spawn_blocking(move || addr.blocking_send(TestMessage))
+    ///         .await
+    ///         .unwrap();
+    ///     assert_eq!(res.unwrap(), 996);
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn blocking_send<M>(&self, msg: M) -> Result<M::Result, ActixAsyncError>
+    where
+        M: Message + Send,
+        A: Handler<M>,
+    {
+        block_on(self.send(msg))
+    }
+
+    /// convert address into a `futures_sink::Sink` sending concurrent messages to actor and
+    /// ignoring the results.
+    ///
+    /// `Sink::poll_ready` waits for actor's channel to have space so the sink honors the
+    /// backpressure of actor's mailbox.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    /// use futures_util::{stream, SinkExt, StreamExt};
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Log(usize);
+    /// message!(Log, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Log> for TestActor {
+    ///     async fn handle(&self, msg: Log, _: Context<'_, Self>) {
+    ///         println!("{}", msg.0);
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let mut sink = addr.clone().into_sink::<Log>();
+    ///     let res = sink.send_all(&mut stream::iter((0..16).map(|i| Ok(Log(i))))).await;
+    ///     assert!(res.is_ok());
+    /// }
+    /// ```
+    #[cfg(feature = "futures-sink")]
+    pub fn into_sink<M>(self) -> AddrSink<A, M>
+    where
+        M: Message + Send,
+        A: Handler<M>,
+    {
+        AddrSink {
+            sink: SenderSink::new(self.0),
+            _msg: PhantomData,
+        }
+    }
+
+    /// send a concurrent message to actor's high priority queue. The message is handled before
+    /// all messages waiting in actor's channel.
+    ///
+    /// *. The priority queue is unbounded. The send ignores the capacity and memory budget of
+    /// channel and never waits.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Health;
+    /// message!(Health, bool);
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Health> for TestActor {
+    ///     async fn handle(&self, _: Health, _: Context<'_, Self>) -> bool {
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     assert!(addr.send_priority(Health).await.unwrap());
+    /// }
+    /// ```
+    #[inline]
+    pub fn send_priority<M>(&self, msg: M) -> MessageRequest<A, M::Result>
+    where
+        M: Message + Send,
+        A: Handler<M>,
+    {
+        send(
+            |tx| ActorMessage::new_ref(msg, Some(tx)),
+            |msg| self.deref().send_priority(msg.with_sender(self.2)),
+        )
+    }
+
+    /// send a concurrent message to actor after given duration and ignore the result.
+    ///
+    /// Unlike `Context::run_later` it can be called outside of actor. The delayed message is
+    /// scheduled on actor's context through it's mailbox like `Addr::do_send` and the returned
+    /// handle can be used to cancel it.
+    ///
+    /// *. The duration starts when actor receives the schedule. It's delayed when actor has
+    /// messages queued before it, is paused or blocked by exclusive message.
+    ///
+    /// *. The handle is terminated immediately when actor is already closed.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Reminder;
+    /// message!(Reminder, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Reminder> for TestActor {
+    ///     async fn handle(&self, _: Reminder, _: Context<'_, Self>) {
+    ///         println!("reminded");
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let handle = addr.send_later(Duration::from_secs(1), Reminder);
+    ///
+    ///     // cancel the reminder before it's sent.
+    ///     handle.cancel();
+    /// }
+    /// ```
+    pub fn send_later<M>(&self, dur: Duration, msg: M) -> ContextJoinHandle
+    where
+        M: Message + Send,
+        A: Handler<M>,
+    {
+        message_send_check::<M>();
+        let (handle, task) = join_handle();
+        self.schedule(move |_, ctx| {
+            let msg = ActorMessage::new_ref(msg, None);
+            ctx.add_later(FutureMessage::new(dur, task, msg));
+            Box::pin(async {})
+        });
+        handle
+    }
+
+    /// send a concurrent message to actor at given deadline and ignore the result.
+    ///
+    /// Unlike `Addr::send_later` the deadline is not delayed by the time actor takes to receive
+    /// the schedule. Message is sent immediately when the deadline is already passed.
+    ///
+    /// *. See `Addr::send_later` for detail.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Reminder;
+    /// message!(Reminder, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Reminder> for TestActor {
+    ///     async fn handle(&self, _: Reminder, _: Context<'_, Self>) {
+    ///         println!("reminded");
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let deadline = Instant::now() + Duration::from_secs(1);
+    ///     let handle = addr.send_at(deadline, Reminder);
+    ///
+    ///     handle.cancel();
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn send_at<M>(&self, deadline: std::time::Instant, msg: M) -> ContextJoinHandle
+    where
+        M: Message + Send,
+        A: Handler<M>,
+    {
+        message_send_check::<M>();
+        let (handle, task) = join_handle();
+        self.schedule(move |_, ctx| {
+            let msg = ActorMessage::new_ref(msg, None);
+            ctx.add_later(FutureMessage::new_at(deadline, task, msg));
+            Box::pin(async {})
+        });
+        handle
+    }
+
+    /// send concurrent messages produced by given closure to actor on every interval and ignore
+    /// the results.
+    ///
+    /// Unlike `Context::run_interval` it can be called outside of actor. The interval is
+    /// scheduled on actor's context the same way as `Addr::send_later` and the returned handle
+    /// can be used to cancel it.
+    ///
+    /// *. The closure is called on actor's thread every time the interval ticks.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Tick;
+    /// message!(Tick, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Tick> for TestActor {
+    ///     async fn handle(&self, _: Tick, _: Context<'_, Self>) {
+    ///         println!("tick");
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let handle = addr.send_interval(Duration::from_millis(100), || Tick);
+    ///
+    ///     tokio::time::sleep(Duration::from_millis(250)).await;
+    ///
+    ///     // stop the ticking.
+    ///     handle.cancel();
+    /// }
+    /// ```
+    pub fn send_interval<M, F>(&self, dur: Duration, f: F) -> ContextJoinHandle
+    where
+        M: Message + Send,
+        A: Handler<M>,
+        F: Fn() -> M + Send + 'static,
+    {
+        message_send_check::<M>();
+        let (handle, task) = join_handle();
+        self.schedule(move |_, ctx| {
+            let msg = ActorMessageClone::Ref(Box::new(MessageFactory::new(f)));
+            ctx.add_interval(IntervalMessage::new(dur, task, msg));
+            Box::pin(async {})
+        });
+        handle
+    }
+
+    /// send a concurrent message to actor and receive a stream of items as response.
+    ///
+    /// Handler writes items to the stream through `ResponseSink` obtained from
+    /// `Context::response_sink` with the same item type. The stream ends when handler is
+    /// finished and the result of handler is ignored.
+    ///
+    /// *. The stream buffers a limited count of items. `ResponseSink::send` waits when the
+    /// buffer is full and the stream is not polled.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    /// use futures_util::stream::StreamExt;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Page(usize);
+    /// message!(Page, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Page> for TestActor {
+    ///     async fn handle(&self, msg: Page, ctx: Context<'_, Self>) {
+    ///         let sink = ctx.response_sink::<usize>().unwrap();
+    ///         for i in 0..msg.0 {
+    ///             if sink.send(i).await.is_err() {
+    ///                 return;
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let items = addr.send_stream::<_, usize>(Page(3)).collect::<Vec<_>>().await;
+    ///     assert_eq!(items, vec![0, 1, 2]);
+    /// }
+    /// ```
+    pub fn send_stream<M, R>(&self, msg: M) -> ResponseStream<'_, R>
+    where
+        M: Message + Send,
+        A: Handler<M>,
+        R: Send + 'static,
+    {
+        message_send_check::<M>();
+        ResponseStream::new(|sink| {
+            let msg = ActorMessage::new_ref(StreamRequest { msg, sink }, None);
+            Box::pin(self.deref().send(msg.with_sender(self.2)))
+        })
+    }
+
     /// send an exclusive message to actor. `Handler::handle_wait` will be called for exclusive
     /// message processing.
     /// If `Handler::handle_wait` is not override then it would use `Handler::handle` as fallback.
@@ -81,6 +632,86 @@ impl<A: Actor> Addr<A> {
         self.wait(FunctionMutMessage::new(func))
     }
 
+    /// wait until every message sent to actor before the call is handled without stopping it.
+    ///
+    /// A no-op exclusive message is sent to actor. It resolves after all concurrent tasks of
+    /// previous messages are finished and previous exclusive messages are handled.
+    ///
+    /// *. Messages sent after the call and tasks added to context by interval, delayed and
+    /// stream are not waited.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::cell::Cell;
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor(Cell<usize>);
+    /// actor!(TestActor);
+    ///
+    /// struct Incr;
+    /// message!(Incr, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Incr> for TestActor {
+    ///     async fn handle(&self, _: Incr, _: Context<'_, Self>) {
+    ///         self.0.set(self.0.get() + 1);
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor(Cell::new(0)).start();
+    ///
+    ///     for _ in 0..8 {
+    ///         addr.do_send(Incr);
+    ///     }
+    ///
+    ///     addr.drain().await.unwrap();
+    ///
+    ///     let res = addr.run(|act, _| Box::pin(async move { act.0.get() })).await;
+    ///     assert_eq!(res.unwrap(), 8);
+    /// }
+    /// ```
+    pub fn drain(&self) -> MessageRequest<A, ()> {
+        self.run_wait(|_, _| Box::pin(async {}))
+    }
+
+    /// restart actor in place with a new instance constructed by given closure.
+    ///
+    /// The restart is an exclusive message. It waits for all running tasks of actor to finish,
+    /// calls `Actor::on_stop` with `StopReason::Restart` on the current instance, replaces it with
+    /// the new one and calls `Actor::on_start_fallible` on it. Messages in the mailbox and all the
+    /// addresses of actor are kept and would be handled by the new instance.
+    ///
+    /// *. Interval, delayed and stream tasks added to context by the previous instance are kept.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    /// use futures_util::FutureExt;
+    ///
+    /// struct TestActor(usize);
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor(996).start();
+    ///
+    ///     addr.restart(|_| async { TestActor(251) }).await.unwrap();
+    ///
+    ///     let res = addr.run(|act, _| async move { act.0 }.boxed_local()).await;
+    ///     assert_eq!(251, res.unwrap());
+    /// }
+    /// ```
+    pub fn restart<F, Fut>(&self, f: F) -> MessageRequest<A, ()>
+    where
+        F: for<'c> FnOnce(Context<'c, A>) -> Fut + Send + 'static,
+        Fut: Future<Output = A> + 'static,
+    {
+        self.run_wait(move |act, ctx| Box::pin(async move { ctx.restart(act, f).await }))
+    }
+
     /// send a message to actor and ignore the result.
     ///
     /// This is a synchronous operation that would always queue to actor's mailbox.
@@ -97,7 +728,7 @@ impl<A: Actor> Addr<A> {
         M: Message + Send,
         A: Handler<M>,
     {
-        self._do_send(|| ActorMessage::new_ref(msg, None))
+        let _ = self._do_send(|| ActorMessage::new_ref(msg, None));
     }
 
     /// send an exclusive message to actor and ignore the result.
@@ -115,13 +746,17 @@ impl<A: Actor> Addr<A> {
         M: Message + Send,
         A: Handler<M>,
     {
-        self._do_send(|| ActorMessage::new_mut(msg, None))
+        let _ = self._do_send(|| ActorMessage::new_mut(msg, None));
     }
 
     /// stop actor.
     ///
     /// When graceful is true the actor would shut it's channel and drain all remaining messages
     /// and exit. When false the actor would exit as soon as the stop message is handled.
+    ///
+    /// The stop message goes through a dedicated control queue of actor's channel. It's received
+    /// before all other messages and never waits for a full channel. A graceful stop still
+    /// handles the messages queued before it.
     pub fn stop(&self, graceful: bool) -> MessageRequest<A, ()> {
         let state = if graceful {
             ActorState::StopGraceful
@@ -131,7 +766,72 @@ impl<A: Actor> Addr<A> {
 
         let (tx, rx) = oneshot();
 
-        _MessageRequest::new(self.deref().send(ActorMessage::State(state, tx)), rx)
+        _MessageRequest::new(
+            self.deref().send_control(ActorMessage::State(state, tx)),
+            rx,
+        )
+    }
+
+    /// stop actor gracefully and force it to stop when it's not stopped within given duration.
+    ///
+    /// When forced, running tasks and pending exclusive task of actor are dropped and
+    /// `Actor::on_stop` is called with `StopReason::Forced`. Resolves with the reason actor
+    /// stopped with.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Block;
+    /// message!(Block, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Block> for TestActor {
+    ///     async fn handle(&self, _: Block, _: Context<'_, Self>) {
+    ///         // a handler never completes.
+    ///         std::future::pending::<()>().await
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     addr.do_wait(Block);
+    ///
+    ///     let reason = addr.stop_with_timeout(Duration::from_millis(100)).await;
+    ///     assert_eq!(reason, StopReason::Forced);
+    /// }
+    /// ```
+    pub async fn stop_with_timeout(&self, dur: Duration) -> StopReason {
+        let (tx, _) = oneshot();
+        let _ = self
+            .deref()
+            .do_send_control(ActorMessage::State(ActorState::StopGraceful, tx));
+
+        let mut watch = self.watch();
+        let mut timeout = Box::pin(<A::Runtime as RuntimeService>::sleep(dur));
+
+        let res = poll_fn(|cx| {
+            if let Poll::Ready(reason) = Pin::new(&mut watch).poll(cx) {
+                return Poll::Ready(Some(reason));
+            }
+            timeout.as_mut().poll(cx).map(|_| None)
+        })
+        .await;
+
+        match res {
+            Some(reason) => reason,
+            None => {
+                self.1.force_stop();
+                watch.await
+            }
+        }
     }
 
     /// Weak version of Addr that can be upgraded.
@@ -139,50 +839,440 @@ impl<A: Actor> Addr<A> {
     /// The upgrade would fail if no `Addr` is alive anywhere.
     #[inline]
     pub fn downgrade(&self) -> WeakAddr<A> {
-        WeakAddr(Sender::downgrade(&self.0))
+        WeakAddr(Sender::downgrade(&self.0), self.1.clone())
     }
 
-    /// Recipient bound to message type and not actor.
+    /// watch the termination of actor. The returned future resolves with the `StopReason` when
+    /// the actor's `ContextFuture` is dropped.
+    ///
+    /// It resolves immediately if the actor is already stopped.
+    ///
+    /// *. Watching does not keep the actor alive.
+    ///
+    /// *. For actor started by `Supervisor` it resolves when any instance of the actor stops.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let watch = addr.watch();
+    ///
+    ///     let _ = addr.stop(true).await;
+    ///
+    ///     assert_eq!(watch.await, StopReason::Graceful);
+    /// }
+    /// ```
     #[inline]
-    pub fn recipient<M>(&self) -> Recipient<A::Runtime, M>
-    where
-        M: Message + Send,
-        A: Handler<M>,
-    {
-        Recipient(Box::new(self.clone()))
+    pub fn watch(&self) -> Watch {
+        self.1.watch()
     }
 
-    /// weak version of `Recipient`.
+    /// snapshot of actor's runtime state.
     ///
-    /// *. `RecipientWeak` will stay usable as long as the actor and it's `Addr` are alive.
-    /// It DOES NOT care if a strong Recipient is alive or not.
+    /// *. For actor started by `Supervisor` it's the state of last instance changed it's state.
     #[inline]
-    pub fn recipient_weak<M>(&self) -> RecipientWeak<A::Runtime, M>
-    where
-        M: Message + Send,
-        A: Handler<M>,
-    {
-        RecipientWeak(Box::new(self.downgrade()))
+    pub fn state(&self) -> LifecycleState {
+        self.1.state()
     }
 
-    pub(crate) fn new(tx: Sender<ActorMessage<A>>) -> Self {
-        Self(tx)
+    /// snapshot of count of running concurrent tasks plus pending exclusive task of actor.
+    ///
+    /// *. The count is updated when actor polls it's tasks and messages. Messages in mailbox are
+    /// not counted.
+    #[inline]
+    pub fn in_flight(&self) -> usize {
+        self.1.in_flight()
     }
 
-    pub(crate) fn from_recv(rx: &Receiver<ActorMessage<A>>) -> Result<Self, ActixAsyncError> {
-        match rx.as_sender() {
-            Some(tx) => Ok(Addr::new(tx)),
-            None => Err(ActixAsyncError::Closed),
-        }
+    /// snapshot of count of messages waiting in actor's mailbox.
+    ///
+    /// *. Messages in high priority queue and control messages like `Addr::stop` are counted.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.deref().len()
     }
 
-    fn _send<M, F>(&self, f: F) -> MessageRequest<A, M::Result>
-    where
-        A: Handler<M>,
-        M: Message + Send,
-        F: FnOnce(OneshotSender<M::Result>) -> ActorMessage<A>,
+    /// snapshot of the capacity of actor's mailbox.
+    ///
+    /// *. The capacity can change at runtime when `Actor::size_hint_bounds` is set or
+    /// `Context::set_mailbox_capacity` is called.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.deref().capacity()
+    }
+
+    /// check if actor's mailbox is full. A full mailbox would make `Addr::send` wait and
+    /// `Addr::try_send` fail.
+    ///
+    /// *. An adaptive mailbox could still grow it's capacity when next message is sent.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.deref().is_full()
+    }
+
+    /// check if actor's mailbox is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// id of this `Addr`. every clone of `Addr` has it's own id.
+    ///
+    /// Messages sent from it carry the id as `Envelope::sender`. See `Context::current_envelope`.
+    #[inline]
+    pub fn id(&self) -> usize {
+        self.2
+    }
+
+    /// erase the actor type of `Addr`. See `AnyAddr`.
+    #[inline]
+    pub fn into_any(self) -> AnyAddr {
+        AnyAddr::from(self)
+    }
+
+    /// unique id of actor. Every clone of `Addr` shares the same id.
+    ///
+    /// *. For actor started by `Supervisor` the generation is increased by every restart of
+    /// it's instances.
+    #[inline]
+    pub fn actor_id(&self) -> ActorId {
+        self.1.actor_id()
+    }
+
+    /// check if actor's channel is still open.
+    ///
+    /// Return false when actor is stopped and no longer accepts messages.
+    #[inline]
+    pub fn is_connected(&self) -> bool {
+        !self.deref().is_closed()
+    }
+
+    /// wait for actor's channel closed. Resolves immediately when it's already closed.
+    ///
+    /// Producers can race it with their work to stop producing as soon as actor is stopped.
+    /// Unlike `Addr::wait_for_stop` it resolves when actor stops accepting messages and not
+    /// after the actor is fully terminated.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///     assert!(addr.is_connected());
+    ///
+    ///     let addr1 = addr.clone();
+    ///     tokio::task::spawn_local(async move {
+    ///         let _ = addr1.stop(false).await;
+    ///     });
+    ///
+    ///     addr.closed().await;
+    ///     assert!(!addr.is_connected());
+    /// }
+    /// ```
+    pub async fn closed(&self) {
+        self.deref().closed().await
+    }
+
+    /// wait for actor's mailbox dropping below it's low watermark. See
+    /// `Actor::mailbox_low_watermark`.
+    ///
+    /// Producers can wait for the returned future before sending a batch of messages so the
+    /// mailbox is refilled before it's drained instead of waiting on every send of a full
+    /// mailbox.
+    ///
+    /// *. Resolves with `ActixAsyncError::Closed` when actor is stopped.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct TestMessage;
+    /// message!(TestMessage, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<TestMessage> for TestActor {
+    ///     async fn handle(&self, _: TestMessage, _: Context<'_, Self>) {}
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor::builder().capacity(8).start(TestActor);
+    ///
+    ///     for _ in 0..4 {
+    ///         addr.ready().await.unwrap();
+    ///         while !addr.is_full() {
+    ///             addr.do_send(TestMessage);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn ready(&self) -> Result<(), ActixAsyncError> {
+        self.deref().ready().await
+    }
+
+    /// wait for actor fully terminated. The returned future resolves after the actor's
+    /// `ContextFuture` is dropped.
+    ///
+    /// It's the same as `Addr::watch` and can be used by callers who did not initiate the stop.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let addr1 = addr.clone();
+    ///     tokio::task::spawn_local(async move {
+    ///         let _ = addr1.stop(true).await;
+    ///     });
+    ///
+    ///     addr.wait_for_stop().await;
+    /// }
+    /// ```
+    #[inline]
+    pub fn wait_for_stop(&self) -> Watch {
+        self.watch()
+    }
+
+    /// subscribe to the state transitions of actor. See `LifecycleEvent` for the events emitted.
+    ///
+    /// Events happened before subscribing are not observed. The stream ends after
+    /// `LifecycleEvent::Stopped` is emitted or immediately if the actor is already stopped.
+    ///
+    /// *. Subscribing does not keep the actor alive.
+    ///
+    /// *. For actor started by `Supervisor` events of all instances are observed and the stream
+    /// ends when all addresses and instances of the actor are dropped.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::address::LifecycleEvent;
+    /// use actix_async::prelude::*;
+    /// use futures_util::StreamExt;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let mut events = addr.lifecycle_events();
+    ///
+    ///     assert_eq!(events.next().await, Some(LifecycleEvent::Started));
+    ///
+    ///     let _ = addr.stop(true).await;
+    ///
+    ///     assert_eq!(events.next().await, Some(LifecycleEvent::Stopping));
+    ///     assert_eq!(
+    ///         events.next().await,
+    ///         Some(LifecycleEvent::Stopped(StopReason::Graceful))
+    ///     );
+    ///     assert_eq!(events.next().await, None);
+    /// }
+    /// ```
+    #[inline]
+    pub fn lifecycle_events(&self) -> LifecycleEvents {
+        self.1.events()
+    }
+
+    /// subscribe to events of type `E` emitted by actor with `Context::emit`.
+    ///
+    /// Events emitted before subscribing are not observed. The stream ends when actor is stopped
+    /// or immediately if the actor is already stopped.
+    ///
+    /// *. Subscribing does not keep the actor alive.
+    ///
+    /// *. Events are buffered in stream until they are yielded. A slow subscriber would not block
+    /// actor but grows it's buffer.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    /// use futures_util::StreamExt;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Deposited(usize);
+    ///
+    /// struct Deposit(usize);
+    /// message!(Deposit, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Deposit> for TestActor {
+    ///     async fn handle(&self, msg: Deposit, ctx: Context<'_, Self>) {
+    ///         // broadcast event to all subscribers.
+    ///         ctx.emit(Deposited(msg.0));
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let mut events1 = addr.subscribe::<Deposited>();
+    ///     let mut events2 = addr.subscribe::<Deposited>();
+    ///
+    ///     let _ = addr.send(Deposit(996)).await;
+    ///
+    ///     assert_eq!(events1.next().await, Some(Deposited(996)));
+    ///     assert_eq!(events2.next().await, Some(Deposited(996)));
+    ///
+    ///     let _ = addr.stop(true).await;
+    ///
+    ///     assert_eq!(events1.next().await, None);
+    /// }
+    /// ```
+    #[inline]
+    pub fn subscribe<E>(&self) -> Subscription<E>
+    where
+        E: Clone + Send + 'static,
+    {
+        self.1.subscribe()
+    }
+
+    /// Recipient bound to message type and not actor.
+    #[inline]
+    pub fn recipient<M>(&self) -> Recipient<A::Runtime, M>
+    where
+        M: Message + Send,
+        A: Handler<M>,
+    {
+        Recipient(Box::new(self.clone()))
+    }
+
+    /// weak version of `Recipient`.
+    ///
+    /// *. `RecipientWeak` will stay usable as long as the actor and it's `Addr` are alive.
+    /// It DOES NOT care if a strong Recipient is alive or not.
+    #[inline]
+    pub fn recipient_weak<M>(&self) -> RecipientWeak<A::Runtime, M>
+    where
+        M: Message + Send,
+        A: Handler<M>,
+    {
+        RecipientWeak(Box::new(self.downgrade()))
+    }
+
+    /// Recipient bound to message type `M` where actor handles message type `M2`.
+    ///
+    /// `M` is converted into `M2` before sent to actor and the result of `M2` is converted
+    /// into the result of `M`. This is useful when multiple actor types with slightly
+    /// different message/result types have to be used through the same `Recipient` type.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Query;
+    /// message!(Query, u64);
+    ///
+    /// struct TestQuery;
+    /// message!(TestQuery, u32);
+    ///
+    /// impl From<Query> for TestQuery {
+    ///     fn from(_: Query) -> Self {
+    ///         TestQuery
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<TestQuery> for TestActor {
+    ///     async fn handle(&self, _: TestQuery, _: Context<'_, Self>) -> u32 {
+    ///         996
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let recipient = addr.recipient_convert::<Query, TestQuery>();
+    ///     let res: u64 = recipient.send(Query).await.unwrap();
+    ///     assert_eq!(996, res);
+    /// }
+    /// ```
+    #[inline]
+    pub fn recipient_convert<M, M2>(&self) -> Recipient<A::Runtime, M>
+    where
+        M: Message + Send + Into<M2>,
+        M2: Message + Send,
+        M::Result: From<M2::Result>,
+        A: Handler<M2>,
+    {
+        Recipient(Box::new(ConvertAddr::<_, M2>::new(self.clone())))
+    }
+
+    /// weak version of `Addr::recipient_convert`.
+    #[inline]
+    pub fn recipient_weak_convert<M, M2>(&self) -> RecipientWeak<A::Runtime, M>
+    where
+        M: Message + Send + Into<M2>,
+        M2: Message + Send,
+        M::Result: From<M2::Result>,
+        A: Handler<M2>,
+    {
+        RecipientWeak(Box::new(ConvertAddr::<_, M2>::new(self.downgrade())))
+    }
+
+    pub(crate) fn new(tx: Sender<ActorMessage<A>>, lifecycle: RefCounter<Lifecycle>) -> Self {
+        Self(tx, lifecycle, sender_id())
+    }
+
+    pub(crate) fn from_recv(
+        rx: &Receiver<ActorMessage<A>>,
+        lifecycle: &RefCounter<Lifecycle>,
+    ) -> Result<Self, ActixAsyncError> {
+        match rx.as_sender() {
+            Some(tx) => Ok(Addr::new(tx, lifecycle.clone())),
+            None => Err(ActixAsyncError::Closed),
+        }
+    }
+
+    // send closure to actor's mailbox for adding tasks to it's context. The send follows the
+    // overflow policy of mailbox like `Addr::do_send`.
+    pub(crate) fn schedule<F>(&self, f: F)
+    where
+        F: for<'a> FnOnce(&'a A, Context<'a, A>) -> LocalBoxFuture<'a, ()> + Send + 'static,
+    {
+        let msg = ActorMessage::new_ref(FunctionMessage::new(f), None);
+        // closure is dropped with the task handles it owns when actor is closed or mailbox
+        // overflowed.
+        let _ = self.deref().do_send(msg.with_sender(self.2));
+    }
+
+    fn _send<M, F>(&self, f: F) -> MessageRequest<A, M::Result>
+    where
+        A: Handler<M>,
+        M: Message + Send,
+        F: FnOnce(OneshotSender<M::Result>) -> ActorMessage<A>,
     {
-        send(f, |msg| self.deref().send(msg))
+        send(f, |msg| self.deref().send(msg.with_sender(self.2)))
     }
 
     fn _send_box<M, F>(&self, f: F) -> BoxedMessageRequest<A::Runtime, M::Result>
@@ -191,17 +1281,24 @@ impl<A: Actor> Addr<A> {
         M: Message + Send,
         F: FnOnce(OneshotSender<M::Result>) -> ActorMessage<A>,
     {
-        send(f, |msg| Box::pin(self.deref().send(msg)) as _)
+        send(f, |msg| {
+            Box::pin(self.deref().send(msg.with_sender(self.2))) as _
+        })
     }
 
-    fn _do_send<M, F>(&self, f: F)
+    fn _do_send<M, F>(&self, f: F) -> Result<(), ActixAsyncError>
     where
         A: Handler<M>,
         M: Message + Send,
         F: FnOnce() -> ActorMessage<A> + 'static,
     {
         message_send_check::<M>();
-        let _ = self.deref().do_send(f());
+        self.deref()
+            .do_send(f().with_sender(self.2))
+            .map_err(|msg| {
+                dead_letter(msg, DeadLetterReason::Closed);
+                ActixAsyncError::Closed
+            })
     }
 }
 
@@ -218,133 +1315,1278 @@ where
     _MessageRequest::new(fs(msg), rx)
 }
 
-/// weak version `Addr`. Can upgrade to `Addr` when at least one instance of `Addr` is still in
-/// scope.
-pub struct WeakAddr<A>(WeakSender<ActorMessage<A>>);
+// type erased Addr of child actor. See `Context::spawn_child`.
+pub(crate) trait ChildAddr {
+    // stop child actor gracefully.
+    fn stop(&self);
 
-impl<A> Clone for WeakAddr<A> {
-    fn clone(&self) -> Self {
-        Self(self.0.clone())
+    fn is_stopped(&self) -> bool;
+}
+
+impl<A: Actor> ChildAddr for Addr<A> {
+    fn stop(&self) {
+        let (tx, _) = oneshot();
+        let _ = self
+            .deref()
+            .do_send_control(ActorMessage::State(ActorState::StopGraceful, tx));
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.1.is_stopped()
     }
 }
 
-impl<A: Actor> WeakAddr<A> {
-    /// Try to upgrade to a `Addr`
-    ///
-    /// The upgrade would fail if no `Addr` is alive anywhere.
-    #[inline]
-    pub fn upgrade(&self) -> Option<Addr<A>> {
-        self.0.upgrade().map(Addr)
+#[cfg(feature = "tokio-rt")]
+impl<A: Actor> SystemActor for WeakAddr<A> {
+    fn stop(&self, graceful: bool) {
+        let state = if graceful {
+            ActorState::StopGraceful
+        } else {
+            ActorState::Stop
+        };
+
+        if let Some(addr) = self.upgrade() {
+            let (tx, _) = oneshot();
+            let _ = addr.deref().do_send_control(ActorMessage::State(state, tx));
+        }
     }
 
-    fn send_weak<M, F>(&self, f: F) -> BoxedMessageRequest<A::Runtime, M::Result>
-    where
-        A: Handler<M>,
-        M: Message + Send,
-        F: FnOnce(OneshotSender<M::Result>) -> ActorMessage<A>,
-    {
-        send(f, |msg| Box::pin(self._send_weak(msg)) as _)
+    fn watch(&self) -> Watch {
+        self.1.watch()
     }
 
-    async fn _send_weak(&self, msg: ActorMessage<A>) -> Result<(), ActixAsyncError> {
-        self.upgrade()
-            .ok_or(ActixAsyncError::Closed)?
-            .deref()
-            .send(msg)
-            .await
+    fn is_stopped(&self) -> bool {
+        self.1.is_stopped()
     }
 }
 
-/// trait to bind a given `Addr<A>` or `WeakAddr<A>` to `Message` trait type.
-pub trait AddrHandler<RT, M>
+/// `futures_sink::Sink` of actor's concurrent messages. Obtained from `Addr::into_sink`.
+#[cfg(feature = "futures-sink")]
+pub struct AddrSink<A: Actor, M> {
+    sink: SenderSink<ActorMessage<A>>,
+    _msg: PhantomData<fn(M)>,
+}
+
+#[cfg(feature = "futures-sink")]
+impl<A, M> futures_sink::Sink<M> for AddrSink<A, M>
 where
-    RT: RuntimeService,
+    A: Actor + Handler<M>,
     M: Message + Send,
-    Self: Send + Sync + 'static,
 {
-    fn send(&self, msg: M) -> BoxedMessageRequest<RT, M::Result>;
+    type Error = ActixAsyncError;
 
-    fn wait(&self, msg: M) -> BoxedMessageRequest<RT, M::Result>;
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().sink.poll_flush(cx)
+    }
 
-    fn do_send(&self, msg: M);
+    fn start_send(self: Pin<&mut Self>, item: M) -> Result<(), Self::Error> {
+        self.get_mut()
+            .sink
+            .start_send(ActorMessage::new_ref(item, None));
+        Ok(())
+    }
 
-    fn do_wait(&self, msg: M);
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().sink.poll_flush(cx)
+    }
+
+    fn poll_close(
+        self: Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().sink.poll_flush(cx)
+    }
 }
 
-impl<A, M> AddrHandler<A::Runtime, M> for Addr<A>
-where
-    A: Actor + Handler<M>,
-    M: Message + Send,
-{
-    #[inline]
-    fn send(&self, msg: M) -> BoxedMessageRequest<A::Runtime, M::Result> {
-        self._send_box(|tx| ActorMessage::new_ref(msg, Some(tx)))
+/// the reason a message can not be delivered to actor. See `DeadLetter`.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum DeadLetterReason {
+    /// dropped by `Actor::overflow_policy` or rejected by the full channel of
+    /// `Recipient::from_sender`.
+    Overflow,
+    /// sent by `Addr::do_send` or `Addr::do_wait` after actor's channel is closed.
+    Closed,
+    /// remained in actor's channel when actor stopped.
+    Stopped,
+}
+
+/// Message can not be delivered to actor. See `Actor::on_dead_letter`.
+pub struct DeadLetter<A> {
+    msg: ActorMessage<A>,
+    reason: DeadLetterReason,
+}
+
+impl<A: Actor> DeadLetter<A> {
+    pub(crate) fn new(msg: ActorMessage<A>, reason: DeadLetterReason) -> Self {
+        Self { msg, reason }
     }
 
-    #[inline]
-    fn wait(&self, msg: M) -> BoxedMessageRequest<A::Runtime, M::Result> {
-        self._send_box(|tx| ActorMessage::new_mut(msg, Some(tx)))
+    /// type name of the dropped message.
+    pub fn message_type(&self) -> &'static str {
+        self.msg.type_name()
     }
 
-    #[inline]
-    fn do_send(&self, msg: M) {
-        Addr::do_send(self, msg);
+    /// the reason message is not delivered.
+    pub fn reason(&self) -> DeadLetterReason {
+        self.reason
     }
 
-    #[inline]
-    fn do_wait(&self, msg: M) {
-        Addr::do_wait(self, msg);
+    /// take back the message when it's the given type. Can be used to re-route the message.
+    pub fn downcast<M: Message + Send>(self) -> Result<M, Self> {
+        let reason = self.reason;
+        self.msg.downcast().map_err(|msg| Self::new(msg, reason))
+    }
+
+    /// erase the actor type. The message is boxed as `Any`.
+    pub fn into_any(self) -> AnyDeadLetter {
+        let message_type = self.msg.type_name();
+        let msg = self.msg.into_any();
+
+        AnyDeadLetter {
+            actor_type: core::any::type_name::<A>(),
+            message_type,
+            reason: self.reason,
+            msg,
+        }
     }
 }
 
-impl<A, M> AddrHandler<A::Runtime, M> for WeakAddr<A>
-where
-    A: Actor + Handler<M>,
-    M: Message + Send,
-{
-    #[inline]
-    fn send(&self, msg: M) -> BoxedMessageRequest<A::Runtime, M::Result> {
-        self.send_weak(|tx| ActorMessage::new_ref(msg, Some(tx)))
+/// type erased `DeadLetter`. Received by hook set with `set_dead_letter_hook`.
+pub struct AnyDeadLetter {
+    actor_type: &'static str,
+    message_type: &'static str,
+    reason: DeadLetterReason,
+    msg: Option<Box<dyn Any + Send>>,
+}
+
+impl AnyDeadLetter {
+    /// type name of the actor message is sent to. For `Recipient::from_sender` it's the type
+    /// name of channel sender.
+    pub fn actor_type(&self) -> &'static str {
+        self.actor_type
     }
 
-    #[inline]
-    fn wait(&self, msg: M) -> BoxedMessageRequest<A::Runtime, M::Result> {
-        self.send_weak(|tx| ActorMessage::new_mut(msg, Some(tx)))
+    /// type name of the dropped message.
+    pub fn message_type(&self) -> &'static str {
+        self.message_type
     }
 
-    /// `AddrHandler::do_send` will panic if the `Addr` for `RecipientWeak` is gone.
-    #[inline]
-    fn do_send(&self, msg: M) {
-        let addr = &self.upgrade().unwrap();
-        Addr::do_send(addr, msg);
+    /// the reason message is not delivered.
+    pub fn reason(&self) -> DeadLetterReason {
+        self.reason
     }
 
-    /// `AddrHandler::do_wait` will panic if the `Addr` for `RecipientWeak` is gone.
-    #[inline]
-    fn do_wait(&self, msg: M) {
-        let addr = &self.upgrade().unwrap();
-        Addr::do_wait(addr, msg);
+    /// take the boxed message. Can be downcast to it's original type.
+    pub fn into_message(self) -> Option<Box<dyn Any + Send>> {
+        self.msg
     }
 }
 
-/// A trait object of `Addr<Actor>` that bind to given `Message` type
-pub struct Recipient<RT, M: Message + Send>(Box<dyn AddrHandler<RT, M>>);
+static DEAD_LETTER_HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
 
-impl<RT, M: Message + Send> Deref for Recipient<RT, M> {
-    type Target = dyn AddrHandler<RT, M>;
+/// set the global hook receiving dead letters of all actors that do not override
+/// `Actor::on_dead_letter`. Replace the previous hook.
+///
+/// *. The hook is called on the thread where message is dropped. It can be the sender's thread
+/// or the actor's thread.
+///
+/// # example:
+/// ```rust
+/// use actix_async::address::{set_dead_letter_hook, AnyDeadLetter};
+///
+/// fn log(letter: AnyDeadLetter) {
+///     eprintln!(
+///         "{} dropped by {}: {:?}",
+///         letter.message_type(),
+///         letter.actor_type(),
+///         letter.reason()
+///     );
+/// }
+///
+/// set_dead_letter_hook(log);
+/// ```
+pub fn set_dead_letter_hook(hook: fn(AnyDeadLetter)) {
+    DEAD_LETTER_HOOK.store(hook as *mut (), Ordering::Release);
+}
 
-    fn deref(&self) -> &Self::Target {
-        &*self.0
+// pass dead letter to global hook. See `set_dead_letter_hook`.
+pub(crate) fn dead_letter_hook(letter: AnyDeadLetter) {
+    let hook = DEAD_LETTER_HOOK.load(Ordering::Acquire);
+    if !hook.is_null() {
+        // SAFETY:
+        //
+        // Only fn(AnyDeadLetter) pointer is stored in DEAD_LETTER_HOOK.
+        let hook = unsafe { core::mem::transmute::<*mut (), fn(AnyDeadLetter)>(hook) };
+        hook(letter);
     }
 }
 
-/// A trait object of `WeakAddr<Actor>` that bind to given `Message` type
-pub struct RecipientWeak<RT, M: Message + Send>(Box<dyn AddrHandler<RT, M>>);
+/// type erased `Addr`. Addresses of different actor types can be held in one collection.
+///
+/// Can be downcast back to `Addr` with it's actor type.
+///
+/// # example:
+/// ```rust
+/// use actix_async::prelude::*;
+/// use actix_async::address::AnyAddr;
+///
+/// struct ActorA;
+/// actor!(ActorA);
+///
+/// struct ActorB;
+/// actor!(ActorB);
+///
+/// #[actix_async::main]
+/// async fn main() {
+///     let addrs: Vec<AnyAddr> = vec![ActorA.start().into_any(), ActorB.start().into_any()];
+///
+///     assert!(addrs[0].downcast_ref::<ActorA>().is_some());
+///     assert!(addrs[1].downcast_ref::<ActorA>().is_none());
+///
+///     // stop all actors.
+///     for addr in addrs.iter() {
+///         let _ = addr.stop(true).await;
+///     }
+///
+///     assert!(addrs.iter().all(|addr| !addr.is_connected()));
+/// }
+/// ```
+pub struct AnyAddr(Box<dyn AnyAddrHandler>);
 
-impl<RT, M: Message + Send> Deref for RecipientWeak<RT, M> {
-    type Target = dyn AddrHandler<RT, M>;
+impl Clone for AnyAddr {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_handler())
+    }
+}
 
-    fn deref(&self) -> &Self::Target {
-        &*self.0
+impl fmt::Debug for AnyAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyAddr")
+            .field("actor", &self.0.actor_type())
+            .field("id", &self.0.id())
+            .finish()
+    }
+}
+
+impl AnyAddr {
+    /// type name of the actor.
+    #[inline]
+    pub fn actor_type(&self) -> &'static str {
+        self.0.actor_type()
+    }
+
+    /// id of the erased `Addr`. See `Addr::id`.
+    #[inline]
+    pub fn id(&self) -> usize {
+        self.0.id()
+    }
+
+    /// unique id of actor. See `Addr::actor_id`.
+    #[inline]
+    pub fn actor_id(&self) -> ActorId {
+        self.0.actor_id()
+    }
+
+    /// stop actor. See `Addr::stop`.
+    #[inline]
+    pub fn stop(&self, graceful: bool) -> BoxFuture<'_, Result<(), ActixAsyncError>> {
+        self.0.stop(graceful)
+    }
+
+    /// watch for actor's termination. See `Addr::watch`.
+    #[inline]
+    pub fn watch(&self) -> Watch {
+        self.0.watch()
+    }
+
+    /// check if actor's channel is still open. See `Addr::is_connected`.
+    #[inline]
+    pub fn is_connected(&self) -> bool {
+        self.0.is_connected()
+    }
+
+    /// reference of `Addr` when it's the given actor type.
+    pub fn downcast_ref<A: Actor>(&self) -> Option<&Addr<A>> {
+        self.0.as_any().downcast_ref()
+    }
+
+    /// take back `Addr` when it's the given actor type.
+    pub fn downcast<A: Actor>(self) -> Result<Addr<A>, Self> {
+        if self.0.as_any().is::<Addr<A>>() {
+            Ok(*self.0.into_any().downcast().unwrap())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<A: Actor> From<Addr<A>> for AnyAddr {
+    fn from(addr: Addr<A>) -> Self {
+        Self(Box::new(addr))
+    }
+}
+
+// object safe part of Addr used by AnyAddr.
+trait AnyAddrHandler: Send + Sync + 'static {
+    fn actor_type(&self) -> &'static str;
+
+    fn id(&self) -> usize;
+
+    fn actor_id(&self) -> ActorId;
+
+    fn stop(&self, graceful: bool) -> BoxFuture<'_, Result<(), ActixAsyncError>>;
+
+    fn watch(&self) -> Watch;
+
+    fn is_connected(&self) -> bool;
+
+    fn clone_handler(&self) -> Box<dyn AnyAddrHandler>;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<A: Actor> AnyAddrHandler for Addr<A> {
+    fn actor_type(&self) -> &'static str {
+        core::any::type_name::<A>()
+    }
+
+    fn id(&self) -> usize {
+        Addr::id(self)
+    }
+
+    fn actor_id(&self) -> ActorId {
+        Addr::actor_id(self)
+    }
+
+    fn stop(&self, graceful: bool) -> BoxFuture<'_, Result<(), ActixAsyncError>> {
+        Box::pin(Addr::stop(self, graceful))
+    }
+
+    fn watch(&self) -> Watch {
+        Addr::watch(self)
+    }
+
+    fn is_connected(&self) -> bool {
+        Addr::is_connected(self)
+    }
+
+    fn clone_handler(&self) -> Box<dyn AnyAddrHandler> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+/// weak version `Addr`. Can upgrade to `Addr` when at least one instance of `Addr` is still in
+/// scope.
+///
+/// Compared and hashed by the identity of actor's channel like `Addr`.
+pub struct WeakAddr<A>(WeakSender<ActorMessage<A>>, RefCounter<Lifecycle>);
+
+impl<A> Clone for WeakAddr<A> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), self.1.clone())
+    }
+}
+
+impl<A> PartialEq for WeakAddr<A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.channel_id() == other.0.channel_id()
+    }
+}
+
+impl<A> Eq for WeakAddr<A> {}
+
+impl<A> Hash for WeakAddr<A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.channel_id().hash(state)
+    }
+}
+
+impl<A> fmt::Debug for WeakAddr<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakAddr")
+            .field("actor", &core::any::type_name::<A>())
+            .field("state", &self.1.state())
+            .finish()
+    }
+}
+
+impl<A: Actor> WeakAddr<A> {
+    /// Try to upgrade to a `Addr`
+    ///
+    /// The upgrade would fail if no `Addr` is alive anywhere.
+    #[inline]
+    pub fn upgrade(&self) -> Option<Addr<A>> {
+        self.0.upgrade().map(|tx| Addr::new(tx, self.1.clone()))
+    }
+
+    /// watch the termination of actor. See `Addr::watch` for detail.
+    #[inline]
+    pub fn watch(&self) -> Watch {
+        self.1.watch()
+    }
+
+    /// unique id of actor. See `Addr::actor_id`.
+    #[inline]
+    pub fn actor_id(&self) -> ActorId {
+        self.1.actor_id()
+    }
+
+    fn send_weak<M, F>(&self, f: F) -> BoxedMessageRequest<A::Runtime, M::Result>
+    where
+        A: Handler<M>,
+        M: Message + Send,
+        F: FnOnce(OneshotSender<M::Result>) -> ActorMessage<A>,
+    {
+        send(f, |msg| Box::pin(self._send_weak(msg)) as _)
+    }
+
+    fn _do_send_weak<M, F>(&self, f: F) -> Result<(), ActixAsyncError>
+    where
+        A: Handler<M>,
+        M: Message + Send,
+        F: FnOnce() -> ActorMessage<A> + 'static,
+    {
+        match self.upgrade() {
+            Some(addr) => addr._do_send(f),
+            None => {
+                dead_letter(f(), DeadLetterReason::Closed);
+                Err(ActixAsyncError::Closed)
+            }
+        }
+    }
+
+    async fn _send_weak(&self, msg: ActorMessage<A>) -> Result<(), ActixAsyncError> {
+        self.upgrade()
+            .ok_or(ActixAsyncError::Closed)?
+            .deref()
+            .send(msg)
+            .await
+    }
+}
+
+/// Address of actor started with `ActorBuilder::start_local`.
+///
+/// Messages sent by `LocalAddr::send`, `LocalAddr::wait`, `LocalAddr::do_send` and
+/// `LocalAddr::do_wait` go through a single thread channel of actor without synchronization.
+/// The message types are not required to be `Send` bound.
+///
+/// `LocalAddr` is `!Send` and can only be used on the thread actor runs on. It dereferences to
+/// the `Addr` of actor for the rest of the APIs.
+pub struct LocalAddr<A> {
+    addr: Addr<A>,
+    tx: LocalSender<ActorMessage<A>>,
+}
+
+impl<A> Clone for LocalAddr<A> {
+    fn clone(&self) -> Self {
+        Self {
+            addr: self.addr.clone(),
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<A> Deref for LocalAddr<A> {
+    type Target = Addr<A>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.addr
+    }
+}
+
+impl<A: Actor> LocalAddr<A> {
+    pub(crate) fn new(addr: Addr<A>, tx: LocalSender<ActorMessage<A>>) -> Self {
+        Self { addr, tx }
+    }
+
+    /// send a concurrent message to actor through the local channel. See `Addr::send`.
+    #[inline]
+    pub fn send<M>(&self, msg: M) -> LocalMessageRequest<'_, A, M::Result>
+    where
+        M: Message,
+        A: Handler<M>,
+    {
+        self._send(|tx| ActorMessage::new_ref_local(msg, Some(tx)))
+    }
+
+    /// send an exclusive message to actor through the local channel. See `Addr::wait`.
+    #[inline]
+    pub fn wait<M>(&self, msg: M) -> LocalMessageRequest<'_, A, M::Result>
+    where
+        M: Message,
+        A: Handler<M>,
+    {
+        self._send(|tx| ActorMessage::new_mut_local(msg, Some(tx)))
+    }
+
+    /// send a concurrent message to actor through the local channel and ignore the result.
+    /// See `Addr::do_send`.
+    #[inline]
+    pub fn do_send<M>(&self, msg: M)
+    where
+        M: Message,
+        A: Handler<M>,
+    {
+        self._do_send(ActorMessage::new_ref_local(msg, None))
+    }
+
+    /// send an exclusive message to actor through the local channel and ignore the result.
+    /// See `Addr::do_wait`.
+    #[inline]
+    pub fn do_wait<M>(&self, msg: M)
+    where
+        M: Message,
+        A: Handler<M>,
+    {
+        self._do_send(ActorMessage::new_mut_local(msg, None))
+    }
+
+    /// a clone of `Addr` of actor that can be sent to other threads.
+    #[inline]
+    pub fn addr(&self) -> Addr<A> {
+        self.addr.clone()
+    }
+
+    fn _send<M, F>(&self, f: F) -> LocalMessageRequest<'_, A, M::Result>
+    where
+        A: Handler<M>,
+        M: Message,
+        F: FnOnce(OneshotSender<M::Result>) -> ActorMessage<A>,
+    {
+        let (tx, rx) = oneshot();
+        let msg = f(tx).with_sender(self.addr.2);
+        _MessageRequest::new(self.tx.send(msg), rx)
+    }
+
+    fn _do_send(&self, msg: ActorMessage<A>) {
+        // message can be !Send and is dropped on current thread instead of passed to
+        // Actor::on_dead_letter.
+        let _ = self.tx.do_send(msg.with_sender(self.addr.2));
+    }
+}
+
+/// trait to bind a given `Addr<A>` or `WeakAddr<A>` to `Message` trait type.
+pub trait AddrHandler<RT, M>
+where
+    RT: RuntimeService,
+    M: Message + Send,
+    Self: Send + Sync + 'static,
+{
+    fn send(&self, msg: M) -> BoxedMessageRequest<RT, M::Result>;
+
+    fn wait(&self, msg: M) -> BoxedMessageRequest<RT, M::Result>;
+
+    fn do_send(&self, msg: M);
+
+    fn do_wait(&self, msg: M);
+
+    /// fallible `AddrHandler::do_send`. return error when message can not be queued to actor's
+    /// mailbox.
+    fn try_do_send(&self, msg: M) -> Result<(), ActixAsyncError>;
+
+    /// fallible `AddrHandler::do_wait`. See `AddrHandler::try_do_send`.
+    fn try_do_wait(&self, msg: M) -> Result<(), ActixAsyncError>;
+
+    /// check if messages can still be sent to the handler.
+    ///
+    /// Default to true.
+    fn is_connected(&self) -> bool {
+        true
+    }
+
+    /// identity of the channel messages are sent to. Used by `PartialEq` and `Hash` of
+    /// `Recipient` and `RecipientWeak`.
+    ///
+    /// Default to the address of handler itself.
+    #[doc(hidden)]
+    fn channel_id(&self) -> usize {
+        self as *const Self as *const () as usize
+    }
+}
+
+impl<A, M> AddrHandler<A::Runtime, M> for Addr<A>
+where
+    A: Actor + Handler<M>,
+    M: Message + Send,
+{
+    #[inline]
+    fn send(&self, msg: M) -> BoxedMessageRequest<A::Runtime, M::Result> {
+        self._send_box(|tx| ActorMessage::new_ref(msg, Some(tx)))
+    }
+
+    #[inline]
+    fn wait(&self, msg: M) -> BoxedMessageRequest<A::Runtime, M::Result> {
+        self._send_box(|tx| ActorMessage::new_mut(msg, Some(tx)))
+    }
+
+    #[inline]
+    fn do_send(&self, msg: M) {
+        Addr::do_send(self, msg);
+    }
+
+    #[inline]
+    fn do_wait(&self, msg: M) {
+        Addr::do_wait(self, msg);
+    }
+
+    #[inline]
+    fn try_do_send(&self, msg: M) -> Result<(), ActixAsyncError> {
+        self._do_send(|| ActorMessage::new_ref(msg, None))
+    }
+
+    #[inline]
+    fn try_do_wait(&self, msg: M) -> Result<(), ActixAsyncError> {
+        self._do_send(|| ActorMessage::new_mut(msg, None))
+    }
+
+    #[inline]
+    fn is_connected(&self) -> bool {
+        Addr::is_connected(self)
+    }
+
+    #[inline]
+    fn channel_id(&self) -> usize {
+        self.0.channel_id()
+    }
+}
+
+impl<A, M> AddrHandler<A::Runtime, M> for WeakAddr<A>
+where
+    A: Actor + Handler<M>,
+    M: Message + Send,
+{
+    #[inline]
+    fn send(&self, msg: M) -> BoxedMessageRequest<A::Runtime, M::Result> {
+        self.send_weak(|tx| ActorMessage::new_ref(msg, Some(tx)))
+    }
+
+    #[inline]
+    fn wait(&self, msg: M) -> BoxedMessageRequest<A::Runtime, M::Result> {
+        self.send_weak(|tx| ActorMessage::new_mut(msg, Some(tx)))
+    }
+
+    /// message is passed to `Actor::on_dead_letter` if the `Addr` for `RecipientWeak` is gone.
+    #[inline]
+    fn do_send(&self, msg: M) {
+        let _ = AddrHandler::<A::Runtime, M>::try_do_send(self, msg);
+    }
+
+    /// message is passed to `Actor::on_dead_letter` if the `Addr` for `RecipientWeak` is gone.
+    #[inline]
+    fn do_wait(&self, msg: M) {
+        let _ = AddrHandler::<A::Runtime, M>::try_do_wait(self, msg);
+    }
+
+    #[inline]
+    fn try_do_send(&self, msg: M) -> Result<(), ActixAsyncError> {
+        self._do_send_weak(|| ActorMessage::new_ref(msg, None))
+    }
+
+    #[inline]
+    fn try_do_wait(&self, msg: M) -> Result<(), ActixAsyncError> {
+        self._do_send_weak(|| ActorMessage::new_mut(msg, None))
+    }
+
+    #[inline]
+    fn is_connected(&self) -> bool {
+        self.upgrade().is_some_and(|addr| addr.is_connected())
+    }
+
+    #[inline]
+    fn channel_id(&self) -> usize {
+        self.0.channel_id()
+    }
+}
+
+// `Addr` or `WeakAddr` that convert message type `M` to `M2` before sending it to actor.
+struct ConvertAddr<T, M2> {
+    addr: T,
+    _msg: PhantomData<fn() -> M2>,
+}
+
+impl<T, M2> ConvertAddr<T, M2> {
+    fn new(addr: T) -> Self {
+        Self {
+            addr,
+            _msg: PhantomData,
+        }
+    }
+}
+
+impl<RT, T, M, M2> AddrHandler<RT, M> for ConvertAddr<T, M2>
+where
+    RT: RuntimeService,
+    T: AddrHandler<RT, ConvertMessage<M2, M::Result>>,
+    M: Message + Send + Into<M2>,
+    M2: Message + Send,
+{
+    #[inline]
+    fn send(&self, msg: M) -> BoxedMessageRequest<RT, M::Result> {
+        self.addr.send(ConvertMessage::new(msg.into()))
+    }
+
+    #[inline]
+    fn wait(&self, msg: M) -> BoxedMessageRequest<RT, M::Result> {
+        self.addr.wait(ConvertMessage::new(msg.into()))
+    }
+
+    #[inline]
+    fn do_send(&self, msg: M) {
+        self.addr.do_send(ConvertMessage::new(msg.into()))
+    }
+
+    #[inline]
+    fn do_wait(&self, msg: M) {
+        self.addr.do_wait(ConvertMessage::new(msg.into()))
+    }
+
+    #[inline]
+    fn try_do_send(&self, msg: M) -> Result<(), ActixAsyncError> {
+        self.addr.try_do_send(ConvertMessage::new(msg.into()))
+    }
+
+    #[inline]
+    fn try_do_wait(&self, msg: M) -> Result<(), ActixAsyncError> {
+        self.addr.try_do_wait(ConvertMessage::new(msg.into()))
+    }
+
+    #[inline]
+    fn is_connected(&self) -> bool {
+        self.addr.is_connected()
+    }
+
+    #[inline]
+    fn channel_id(&self) -> usize {
+        self.addr.channel_id()
+    }
+}
+
+/// A trait object of `Addr<Actor>` that bind to given `Message` type
+///
+/// Recipients are compared and hashed by identity. Recipients of the same actor are equal.
+pub struct Recipient<RT, M: Message + Send>(Box<dyn AddrHandler<RT, M>>);
+
+impl<RT, M: Message + Send> Deref for Recipient<RT, M> {
+    type Target = dyn AddrHandler<RT, M>;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl<RT: RuntimeService + 'static, M: Message + Send> PartialEq for Recipient<RT, M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.channel_id() == other.0.channel_id()
+    }
+}
+
+impl<RT: RuntimeService + 'static, M: Message + Send> Eq for Recipient<RT, M> {}
+
+impl<RT: RuntimeService + 'static, M: Message + Send> Hash for Recipient<RT, M> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.channel_id().hash(state)
+    }
+}
+
+/// convert `Addr` into `Recipient`. Same as `Addr::recipient` without cloning the `Addr`.
+///
+/// # example:
+/// ```rust
+/// use actix_async::address::Recipient;
+/// use actix_async::prelude::*;
+///
+/// struct TestActor;
+/// actor!(TestActor);
+///
+/// struct TestMessage;
+/// message!(TestMessage, u32);
+///
+/// #[actix_async::handler]
+/// impl Handler<TestMessage> for TestActor {
+///     async fn handle(&self, _: TestMessage, _: Context<'_, Self>) -> u32 {
+///         996
+///     }
+/// }
+///
+/// // accept any type can be converted to recipient.
+/// async fn notify(re: impl Into<Recipient<TokioRuntime, TestMessage>>) -> u32 {
+///     re.into().send(TestMessage).await.unwrap()
+/// }
+///
+/// #[actix_async::main]
+/// async fn main() {
+///     let addr = TestActor.start();
+///     assert_eq!(notify(&addr).await, 996);
+///     assert_eq!(notify(addr).await, 996);
+/// }
+/// ```
+impl<A, M> From<Addr<A>> for Recipient<A::Runtime, M>
+where
+    A: Actor + Handler<M>,
+    M: Message + Send,
+{
+    #[inline]
+    fn from(addr: Addr<A>) -> Self {
+        Recipient(Box::new(addr))
+    }
+}
+
+impl<A, M> From<&Addr<A>> for Recipient<A::Runtime, M>
+where
+    A: Actor + Handler<M>,
+    M: Message + Send,
+{
+    #[inline]
+    fn from(addr: &Addr<A>) -> Self {
+        addr.recipient()
+    }
+}
+
+impl<RT, M> Recipient<RT, M>
+where
+    RT: RuntimeService + 'static,
+    M: Message + Send,
+{
+    /// send a concurrent message and block current thread until the result is received.
+    /// See `Addr::blocking_send`.
+    #[cfg(feature = "std")]
+    pub fn blocking_send(&self, msg: M) -> Result<M::Result, ActixAsyncError> {
+        block_on(self.send(msg))
+    }
+
+    /// Recipient bound to message type `N` that convert it to `M` with given closure before
+    /// sending it to actor.
+    ///
+    /// *. Unlike `Addr::recipient_convert` the result type must stay the same.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::address::Recipient;
+    /// use actix_async::prelude::*;
+    ///
+    /// struct Subscriber;
+    /// actor!(Subscriber);
+    ///
+    /// struct Event(u32);
+    /// message!(Event, u32);
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Event> for Subscriber {
+    ///     async fn handle(&self, msg: Event, _: Context<'_, Self>) -> u32 {
+    ///         msg.0
+    ///     }
+    /// }
+    ///
+    /// // message emitted by publisher.
+    /// struct Published(u16);
+    /// message!(Published, u32);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = Subscriber.start();
+    ///
+    ///     let recipient: Recipient<_, Published> = addr
+    ///         .recipient::<Event>()
+    ///         .with(|msg: Published| Event(msg.0 as u32));
+    ///
+    ///     assert_eq!(recipient.send(Published(996)).await.unwrap(), 996);
+    /// }
+    /// ```
+    pub fn with<N, F>(self, f: F) -> Recipient<RT, N>
+    where
+        N: Message<Result = M::Result> + Send,
+        F: Fn(N) -> M + Send + Sync + 'static,
+    {
+        Recipient(Box::new(MapRecipient::new(self.0, f)))
+    }
+}
+
+#[cfg(feature = "tokio-rt")]
+impl<RT, M> Recipient<RT, M>
+where
+    RT: RuntimeService,
+    M: Message<Result = ()> + Send,
+{
+    /// Construct a Recipient from the sender of tokio's bounded channel.
+    ///
+    /// The request resolves when message is accepted by the channel and `ActixAsyncError::Closed`
+    /// is returned when the receiver of channel is gone.
+    ///
+    /// *. Message sent by `Recipient::do_send` that is rejected by a full or closed channel is
+    /// passed to the global hook set by `set_dead_letter_hook`.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    /// use actix_async::address::Recipient;
+    ///
+    /// struct TestMessage(u32);
+    /// message!(TestMessage, ());
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+    ///
+    ///     let recipient = Recipient::<TokioRuntime, _>::from_sender(tx);
+    ///
+    ///     recipient.send(TestMessage(996)).await.unwrap();
+    ///     assert_eq!(996, rx.recv().await.unwrap().0);
+    /// }
+    /// ```
+    pub fn from_sender(tx: tokio::sync::mpsc::Sender<M>) -> Self {
+        Recipient(Box::new(ChannelSender(tx)))
+    }
+}
+
+// tokio's channel sender that can be used as `Recipient`.
+#[cfg(feature = "tokio-rt")]
+struct ChannelSender<M>(tokio::sync::mpsc::Sender<M>);
+
+#[cfg(feature = "tokio-rt")]
+impl<RT, M> AddrHandler<RT, M> for ChannelSender<M>
+where
+    RT: RuntimeService,
+    M: Message<Result = ()> + Send,
+{
+    fn send(&self, msg: M) -> BoxedMessageRequest<RT, M::Result> {
+        let (tx, rx) = oneshot();
+        let fut = Box::pin(async move {
+            self.0
+                .send(msg)
+                .await
+                .map_err(|_| ActixAsyncError::Closed)?;
+            let _ = tx.send(());
+            Ok(())
+        });
+
+        _MessageRequest::new(fut, rx)
+    }
+
+    #[inline]
+    fn wait(&self, msg: M) -> BoxedMessageRequest<RT, M::Result> {
+        AddrHandler::<RT, M>::send(self, msg)
+    }
+
+    fn do_send(&self, msg: M) {
+        use tokio::sync::mpsc::error::TrySendError;
+
+        if let Err(e) = self.0.try_send(msg) {
+            let (msg, reason) = match e {
+                TrySendError::Full(msg) => (msg, DeadLetterReason::Overflow),
+                TrySendError::Closed(msg) => (msg, DeadLetterReason::Closed),
+            };
+            dead_letter_hook(AnyDeadLetter {
+                actor_type: core::any::type_name::<tokio::sync::mpsc::Sender<M>>(),
+                message_type: core::any::type_name::<M>(),
+                reason,
+                msg: Some(Box::new(msg)),
+            });
+        }
+    }
+
+    #[inline]
+    fn do_wait(&self, msg: M) {
+        AddrHandler::<RT, M>::do_send(self, msg)
+    }
+
+    fn try_do_send(&self, msg: M) -> Result<(), ActixAsyncError> {
+        self.0.try_send(msg).map_err(|e| match e {
+            tokio::sync::mpsc::error::TrySendError::Full(_) => ActixAsyncError::MailboxFull,
+            tokio::sync::mpsc::error::TrySendError::Closed(_) => ActixAsyncError::Closed,
+        })
+    }
+
+    #[inline]
+    fn try_do_wait(&self, msg: M) -> Result<(), ActixAsyncError> {
+        AddrHandler::<RT, M>::try_do_send(self, msg)
+    }
+
+    #[inline]
+    fn is_connected(&self) -> bool {
+        !self.0.is_closed()
+    }
+}
+
+// `Recipient` or `RecipientWeak` that convert message type `N` to `M` with a closure before
+// sending it. See `Recipient::with`.
+struct MapRecipient<RT, M: Message + Send, F> {
+    recipient: Box<dyn AddrHandler<RT, M>>,
+    f: F,
+}
+
+impl<RT, M: Message + Send, F> MapRecipient<RT, M, F> {
+    fn new(recipient: Box<dyn AddrHandler<RT, M>>, f: F) -> Self {
+        Self { recipient, f }
+    }
+}
+
+impl<RT, M, N, F> AddrHandler<RT, N> for MapRecipient<RT, M, F>
+where
+    RT: RuntimeService + 'static,
+    M: Message + Send,
+    N: Message<Result = M::Result> + Send,
+    F: Fn(N) -> M + Send + Sync + 'static,
+{
+    #[inline]
+    fn send(&self, msg: N) -> BoxedMessageRequest<RT, N::Result> {
+        self.recipient.send((self.f)(msg))
+    }
+
+    #[inline]
+    fn wait(&self, msg: N) -> BoxedMessageRequest<RT, N::Result> {
+        self.recipient.wait((self.f)(msg))
+    }
+
+    #[inline]
+    fn do_send(&self, msg: N) {
+        self.recipient.do_send((self.f)(msg))
+    }
+
+    #[inline]
+    fn do_wait(&self, msg: N) {
+        self.recipient.do_wait((self.f)(msg))
+    }
+
+    #[inline]
+    fn try_do_send(&self, msg: N) -> Result<(), ActixAsyncError> {
+        self.recipient.try_do_send((self.f)(msg))
+    }
+
+    #[inline]
+    fn try_do_wait(&self, msg: N) -> Result<(), ActixAsyncError> {
+        self.recipient.try_do_wait((self.f)(msg))
+    }
+
+    #[inline]
+    fn is_connected(&self) -> bool {
+        self.recipient.is_connected()
+    }
+
+    #[inline]
+    fn channel_id(&self) -> usize {
+        self.recipient.channel_id()
+    }
+}
+
+/// A trait object of `WeakAddr<Actor>` that bind to given `Message` type
+///
+/// Compared and hashed by identity like `Recipient`.
+pub struct RecipientWeak<RT, M: Message + Send>(Box<dyn AddrHandler<RT, M>>);
+
+impl<RT, M: Message + Send> Deref for RecipientWeak<RT, M> {
+    type Target = dyn AddrHandler<RT, M>;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl<RT: RuntimeService + 'static, M: Message + Send> PartialEq for RecipientWeak<RT, M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.channel_id() == other.0.channel_id()
+    }
+}
+
+impl<RT: RuntimeService + 'static, M: Message + Send> Eq for RecipientWeak<RT, M> {}
+
+impl<RT: RuntimeService + 'static, M: Message + Send> Hash for RecipientWeak<RT, M> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.channel_id().hash(state)
+    }
+}
+
+impl<RT, M> RecipientWeak<RT, M>
+where
+    RT: RuntimeService + 'static,
+    M: Message + Send,
+{
+    /// weak version of `Recipient::with`.
+    pub fn with<N, F>(self, f: F) -> RecipientWeak<RT, N>
+    where
+        N: Message<Result = M::Result> + Send,
+        F: Fn(N) -> M + Send + Sync + 'static,
+    {
+        RecipientWeak(Box::new(MapRecipient::new(self.0, f)))
+    }
+}
+
+/// convert `WeakAddr` into `RecipientWeak`. See `From<Addr<A>>` impl of `Recipient`.
+impl<A, M> From<WeakAddr<A>> for RecipientWeak<A::Runtime, M>
+where
+    A: Actor + Handler<M>,
+    M: Message + Send,
+{
+    #[inline]
+    fn from(addr: WeakAddr<A>) -> Self {
+        RecipientWeak(Box::new(addr))
+    }
+}
+
+impl<A, M> From<&Addr<A>> for RecipientWeak<A::Runtime, M>
+where
+    A: Actor + Handler<M>,
+    M: Message + Send,
+{
+    #[inline]
+    fn from(addr: &Addr<A>) -> Self {
+        addr.recipient_weak()
+    }
+}
+
+/// A set of `RecipientWeak` for broadcasting messages to multiple actors.
+///
+/// Recipients are deduplicated by identity and the ones whose actor is gone are pruned
+/// automatically when inserting and broadcasting.
+///
+/// # example:
+/// ```rust
+/// use actix_async::address::RecipientSet;
+/// use actix_async::prelude::*;
+///
+/// struct Subscriber;
+/// actor!(Subscriber);
+///
+/// #[derive(Clone)]
+/// struct Event(u32);
+/// message!(Event, u32);
+///
+/// #[actix_async::handler]
+/// impl Handler<Event> for Subscriber {
+///     async fn handle(&self, msg: Event, _: Context<'_, Self>) -> u32 {
+///         msg.0
+///     }
+/// }
+///
+/// #[actix_async::main]
+/// async fn main() {
+///     let sub1 = Subscriber.start();
+///     let sub2 = Subscriber.start();
+///
+///     let mut set = RecipientSet::<TokioRuntime, Event>::new();
+///     set.insert(&sub1);
+///     set.insert(&sub2);
+///
+///     // do_send to all recipients.
+///     set.broadcast(Event(1));
+///
+///     // send to all recipients and gather the results.
+///     let res = set.send_all(Event(2)).await;
+///     assert_eq!(res, vec![Ok(2), Ok(2)]);
+///
+///     // recipient is pruned when it's actor is gone.
+///     drop(sub1);
+///     let res = set.send_all(Event(3)).await;
+///     assert_eq!(res, vec![Ok(3)]);
+///     assert_eq!(set.len(), 1);
+/// }
+/// ```
+pub struct RecipientSet<RT, M: Message + Send> {
+    recipients: Vec<RecipientWeak<RT, M>>,
+}
+
+impl<RT, M> Default for RecipientSet<RT, M>
+where
+    RT: RuntimeService + 'static,
+    M: Message + Send,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<RT, M> RecipientSet<RT, M>
+where
+    RT: RuntimeService + 'static,
+    M: Message + Send,
+{
+    /// Construct an empty set.
+    pub fn new() -> Self {
+        Self {
+            recipients: Vec::new(),
+        }
+    }
+
+    /// insert a recipient to set. Return false when it's already in the set.
+    pub fn insert(&mut self, recipient: impl Into<RecipientWeak<RT, M>>) -> bool {
+        self.prune();
+        let recipient = recipient.into();
+        if self.recipients.contains(&recipient) {
+            false
+        } else {
+            self.recipients.push(recipient);
+            true
+        }
+    }
+
+    /// remove a recipient from set. Return false when it's not in the set.
+    pub fn remove(&mut self, recipient: &RecipientWeak<RT, M>) -> bool {
+        let len = self.recipients.len();
+        self.recipients.retain(|re| re != recipient);
+        self.recipients.len() != len
+    }
+
+    /// count of recipients in set. Recipients whose actor is gone are counted until they are
+    /// pruned.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.recipients.len()
+    }
+
+    /// check if set is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.recipients.is_empty()
+    }
+
+    /// remove recipients whose actor is gone.
+    pub fn prune(&mut self) {
+        self.recipients.retain(|re| re.is_connected());
+    }
+
+    /// send a clone of message to all recipients with `AddrHandler::do_send` and ignore the
+    /// results.
+    pub fn broadcast(&mut self, msg: M)
+    where
+        M: Clone,
+    {
+        self.recipients
+            .retain(|re| !matches!(re.try_do_send(msg.clone()), Err(ActixAsyncError::Closed)));
+    }
+
+    /// send a clone of message to all recipients concurrently and gather the results in the
+    /// order of recipients.
+    pub async fn send_all(&mut self, msg: M) -> Vec<Result<M::Result, ActixAsyncError>>
+    where
+        M: Clone,
+    {
+        self.prune();
+
+        let mut reqs = self
+            .recipients
+            .iter()
+            .map(|re| Box::pin(re.send(msg.clone())))
+            .collect::<Vec<_>>();
+
+        join_requests(&mut reqs, core::future::pending())
+            .await
+            .into_iter()
+            .map(Option::unwrap)
+            .collect()
     }
 }
@@ -1,19 +1,44 @@
-use core::time::Duration;
+use core::{
+    any::Any,
+    future::{ready, Future},
+    marker::PhantomData,
+    ops::ControlFlow,
+    pin::Pin,
+    sync::atomic::{AtomicUsize, Ordering},
+    task::{Context as StdContext, Poll, Waker},
+    time::Duration,
+};
 
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec::Vec};
 
 use crate::context_future::ContextInner;
 
-use super::actor::{Actor, ActorState};
-use super::address::Addr;
+pub use crate::context_future::ContextFuture;
+pub use crate::message::{Envelope, ScheduledKind, ScheduledTask};
+
+use super::actor::{Actor, ActorState, StopReason};
+use super::address::{ActorId, Addr};
+#[cfg(feature = "framed")]
+use super::framed::{Frame, FrameStream};
 use super::handler::Handler;
+#[cfg(feature = "futures-sink")]
+use super::io::{SinkDriver, SinkHandle, WriteHandler};
+#[cfg(feature = "cron")]
+use super::message::CronMessage;
 use super::message::{
-    ActorMessage, ActorMessageClone, FunctionMessage, FunctionMutMessage, FutureMessage,
-    IntervalMessage, Message, StreamContainer, StreamMessage,
+    wait_task, ActorMessage, ActorMessageClone, FunctionMessage, FunctionMutMessage, FutureMessage,
+    FutureStream, InFlightStream, IntervalFn, IntervalMessage, IntervalWaitFn, MapStream, Message,
+    SpawnedFuture, StreamContainer, StreamMessage,
 };
+use super::request::{ResponseHandle, ResponseSink};
+#[cfg(feature = "std")]
+use super::runtime::RuntimeService;
+#[cfg(feature = "std")]
+use super::util::{channel::ChannelBuilder, futures::block_on};
 use super::util::{
-    channel::{oneshot, OneshotReceiver, OneshotSender},
+    channel::OneshotSender,
     futures::{LocalBoxFuture, Stream},
+    smart_pointer::{Lock, RefCounter},
 };
 
 /// Context type of `Actor`. Can be accessed within `Handler::handle` and
@@ -22,19 +47,48 @@ use super::util::{
 /// Used to mutate the state of actor and add additional tasks to actor.
 pub struct Context<'a, A: Actor> {
     inner: &'a ContextInner<A>,
+    envelope: Option<Envelope>,
+    // type erased ResponseSink of message currently handled. See `Addr::send_stream`.
+    sink: Option<RefCounter<dyn Any + Send + Sync>>,
+    // type erased response channel of message currently handled. See
+    // `Context::response_handle`.
+    response: Option<RefCounter<dyn Any>>,
+}
+
+/// blocking receiver can be added to actor with `Context::add_channel`.
+///
+/// It's implemented for `std::sync::mpsc::Receiver`. Receivers from other crates like
+/// crossbeam-channel can implement it with their blocking receive method.
+#[cfg(feature = "std")]
+pub trait BlockingReceiver: Send + 'static {
+    type Item;
+
+    /// block current thread until an item is received. return `None` when the channel is
+    /// closed.
+    fn recv(&mut self) -> Option<Self::Item>;
+}
+
+#[cfg(feature = "std")]
+impl<T: Send + 'static> BlockingReceiver for std::sync::mpsc::Receiver<T> {
+    type Item = T;
+
+    #[inline]
+    fn recv(&mut self) -> Option<T> {
+        std::sync::mpsc::Receiver::recv(self).ok()
+    }
 }
 
 /// a join handle can be used to cancel a spawned async task like interval closure and stream
 /// handler
 pub struct ContextJoinHandle {
-    handle: OneshotSender<()>,
+    state: RefCounter<JoinState>,
 }
 
 impl ContextJoinHandle {
     /// Cancel the task associate to this handle.
     #[inline]
     pub fn cancel(self) {
-        let _ = self.handle.send(());
+        self.state.set(CANCEL);
     }
 
     /// Check if the task associate with this handle is terminated.
@@ -43,173 +97,1745 @@ impl ContextJoinHandle {
     /// panic.
     #[inline]
     pub fn is_terminated(&self) -> bool {
-        self.handle.is_closed()
+        self.state.get() & TERMINATE != 0
+    }
+
+    /// Pause the task associate to this handle.
+    ///
+    /// A paused stream would not be polled until `ContextJoinHandle::resume` is called. Items
+    /// not yet produced stay in the stream so it's source can apply backpressure.
+    ///
+    /// A paused interval skips it's ticks and restarts it's period on resume. A paused delayed
+    /// task is held until resume and runs right away when it's delay is already passed.
+    ///
+    /// *. Streams, spawned futures, intervals and delayed tasks can be paused.
+    #[inline]
+    pub fn pause(&self) {
+        self.state.set(PAUSE);
+    }
+
+    /// Resume the task associate to this handle if it's paused.
+    #[inline]
+    pub fn resume(&self) {
+        self.state.unset(PAUSE);
+    }
+
+    /// Check if the task associate with this handle is paused.
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.state.get() & PAUSE != 0
     }
 }
 
-impl<'c, A: Actor> Context<'c, A> {
-    pub(crate) fn new(inner: &'c ContextInner<A>) -> Self {
-        Context { inner }
+const CANCEL: usize = 0b001;
+const PAUSE: usize = 0b010;
+const TERMINATE: usize = 0b100;
+
+// state shared by ContextJoinHandle and the task it associate with.
+struct JoinState {
+    state: AtomicUsize,
+    // waker of task. woken when handle state changed.
+    waker: Lock<Option<Waker>>,
+    // waker of TaskGroupJoin. woken when task is terminated.
+    join_waker: Lock<Option<Waker>>,
+}
+
+impl JoinState {
+    fn get(&self) -> usize {
+        self.state.load(Ordering::Acquire)
     }
 
-    /// run interval concurrent closure on context. `Handler::handle` will be called.
-    pub fn run_interval<F>(&self, dur: Duration, f: F) -> ContextJoinHandle
+    fn set(&self, flag: usize) {
+        self.state.fetch_or(flag, Ordering::AcqRel);
+        self.wake();
+    }
+
+    fn unset(&self, flag: usize) {
+        self.state.fetch_and(!flag, Ordering::AcqRel);
+        self.wake();
+    }
+
+    fn wake(&self) {
+        if let Some(waker) = self.waker.lock().take() {
+            waker.wake();
+        }
+    }
+
+    fn register_join(&self, waker: &Waker) {
+        let mut join_waker = self.join_waker.lock();
+        match *join_waker {
+            Some(ref w) if w.will_wake(waker) => {}
+            _ => *join_waker = Some(waker.clone()),
+        }
+    }
+}
+
+// task side of ContextJoinHandle.
+pub(crate) struct TaskHandle {
+    state: RefCounter<JoinState>,
+}
+
+impl TaskHandle {
+    /// poll the cancel state of task. register the waker so task would be woken up when it's
+    /// handle state changed.
+    pub(crate) fn poll_cancel(&self, cx: &mut StdContext<'_>) -> Poll<()> {
+        {
+            let mut waker = self.state.waker.lock();
+            match *waker {
+                Some(ref w) if w.will_wake(cx.waker()) => {}
+                _ => *waker = Some(cx.waker().clone()),
+            }
+        }
+
+        if self.state.get() & CANCEL != 0 {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.state.get() & PAUSE != 0
+    }
+
+    pub(crate) fn cancel_handle(&self) -> TaskCancel {
+        TaskCancel {
+            state: self.state.clone(),
+        }
+    }
+}
+
+// cancel side of TaskHandle that can be shared with the task itself.
+#[derive(Clone)]
+pub(crate) struct TaskCancel {
+    state: RefCounter<JoinState>,
+}
+
+impl TaskCancel {
+    pub(crate) fn cancel(&self) {
+        self.state.set(CANCEL);
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.state.get() & PAUSE != 0
+    }
+}
+
+impl Drop for TaskHandle {
+    fn drop(&mut self) {
+        self.state.state.fetch_or(TERMINATE, Ordering::AcqRel);
+        if let Some(waker) = self.state.join_waker.lock().take() {
+            waker.wake();
+        }
+    }
+}
+
+/// output of interval closure. See `Context::run_interval` and `Context::run_interval_mut`.
+///
+/// Interval keeps running when closure returns `()` or `ControlFlow::Continue` and is canceled
+/// when it returns `ControlFlow::Break`.
+pub trait IntervalFlow: 'static {
+    /// check if the interval should be canceled.
+    fn is_break(&self) -> bool;
+}
+
+impl IntervalFlow for () {
+    #[inline]
+    fn is_break(&self) -> bool {
+        false
+    }
+}
+
+impl IntervalFlow for ControlFlow<()> {
+    #[inline]
+    fn is_break(&self) -> bool {
+        matches!(self, ControlFlow::Break(_))
+    }
+}
+
+pub(crate) fn join_handle() -> (ContextJoinHandle, TaskHandle) {
+    let state = RefCounter::new(JoinState {
+        state: AtomicUsize::new(0),
+        waker: Lock::new(None),
+        join_waker: Lock::new(None),
+    });
+
+    let handle = ContextJoinHandle {
+        state: state.clone(),
+    };
+
+    (handle, TaskHandle { state })
+}
+
+/// a group of tasks added to context. Obtained from `Context::task_group`.
+///
+/// Tasks added through the group share one `TaskGroupHandle` that can cancel, pause and resume
+/// them all at once and can be awaited for their collective completion.
+///
+/// # example:
+/// ```rust
+/// use std::time::Duration;
+///
+/// use actix_async::prelude::*;
+/// use actix_async::context::TaskGroupHandle;
+///
+/// struct GroupActor {
+///     group: Option<TaskGroupHandle>,
+/// }
+/// actor!(GroupActor);
+///
+/// #[actix_async::main]
+/// async fn main() {
+///     let address = GroupActor::create(|ctx| {
+///         let group = ctx.task_group();
+///
+///         group.run_interval(Duration::from_millis(100), |_, _| Box::pin(async {}));
+///         group.run_later(Duration::from_secs(1), |_, _| Box::pin(async {}));
+///
+///         GroupActor {
+///             group: Some(group.handle()),
+///         }
+///     });
+///
+///     let handle = address
+///         .run_wait(|act, _| Box::pin(async move { act.group.take() }))
+///         .await
+///         .unwrap()
+///         .unwrap();
+///
+///     // cancel all tasks in group and wait for them to finish.
+///     handle.cancel();
+///     handle.join().await;
+///     assert!(handle.is_terminated());
+/// }
+/// ```
+pub struct TaskGroup<'c, A: Actor> {
+    ctx: Context<'c, A>,
+    handle: TaskGroupHandle,
+}
+
+impl<A: Actor> TaskGroup<'_, A> {
+    /// get the handle of group.
+    #[inline]
+    pub fn handle(&self) -> TaskGroupHandle {
+        self.handle.clone()
+    }
+
+    /// run interval concurrent closure on context in group. See `Context::run_interval`.
+    pub fn run_interval<F, R>(&self, dur: Duration, f: F) -> ContextJoinHandle
     where
-        F: for<'a> FnOnce(&'a A, Context<'a, A>) -> LocalBoxFuture<'a, ()> + Clone + 'static,
+        F: for<'a> FnOnce(&'a A, Context<'a, A>) -> LocalBoxFuture<'a, R> + Clone + 'static,
+        R: IntervalFlow,
     {
-        self.interval(|rx| {
-            let msg = FunctionMessage::new(f);
-            IntervalMessage::new(dur, rx, ActorMessageClone::Ref(Box::new(msg)))
-        })
+        self.handle.register(self.ctx.run_interval(dur, f))
     }
 
-    /// run interval exclusive closure on context. `Handler::handle_wait` will be called.
-    /// If `Handler::handle_wait` is not override `Handler::handle` will be called as fallback.
-    pub fn run_wait_interval<F>(&self, dur: Duration, f: F) -> ContextJoinHandle
+    /// run interval exclusive closure on context in group. See `Context::run_wait_interval`.
+    pub fn run_wait_interval<F, R>(&self, dur: Duration, f: F) -> ContextJoinHandle
     where
-        F: for<'a> FnOnce(&'a mut A, Context<'a, A>) -> LocalBoxFuture<'a, ()> + Clone + 'static,
+        F: for<'a> FnOnce(&'a mut A, Context<'a, A>) -> LocalBoxFuture<'a, R> + Clone + 'static,
+        R: IntervalFlow,
     {
-        self.interval(|rx| {
-            let msg = FunctionMutMessage::new(f);
-            IntervalMessage::new(dur, rx, ActorMessageClone::Mut(Box::new(msg)))
-        })
+        self.handle.register(self.ctx.run_wait_interval(dur, f))
     }
 
-    fn interval<F>(&self, f: F) -> ContextJoinHandle
+    /// run interval concurrent FnMut closure on context in group. See
+    /// `Context::run_interval_mut`.
+    pub fn run_interval_mut<F, R>(&self, dur: Duration, f: F) -> ContextJoinHandle
     where
-        F: FnOnce(OneshotReceiver<()>) -> IntervalMessage<A>,
+        F: for<'a> FnMut(&'a A, Context<'a, A>) -> LocalBoxFuture<'a, R> + 'static,
+        R: IntervalFlow,
     {
-        let (handle, rx) = oneshot();
-
-        let msg = f(rx);
-        let msg = StreamMessage::new_interval(msg);
-
-        self.inner.stream_cache.borrow_mut().push(msg);
+        self.handle.register(self.ctx.run_interval_mut(dur, f))
+    }
 
-        ContextJoinHandle { handle }
+    /// run interval exclusive FnMut closure on context in group. See
+    /// `Context::run_wait_interval_mut`.
+    pub fn run_wait_interval_mut<F, R>(&self, dur: Duration, f: F) -> ContextJoinHandle
+    where
+        F: for<'a> FnMut(&'a mut A, Context<'a, A>) -> LocalBoxFuture<'a, R> + 'static,
+        R: IntervalFlow,
+    {
+        self.handle.register(self.ctx.run_wait_interval_mut(dur, f))
     }
 
-    /// run concurrent closure on context after given duration. `Handler::handle` will be called.
+    /// run concurrent closure on context in group after given duration. See `Context::run_later`.
     pub fn run_later<F>(&self, dur: Duration, f: F) -> ContextJoinHandle
     where
         F: for<'a> FnOnce(&'a A, Context<'a, A>) -> LocalBoxFuture<'a, ()> + 'static,
     {
-        self.later(|rx| {
-            let msg = FunctionMessage::<_, ()>::new(f);
-            let msg = ActorMessage::new_ref(msg, None);
-            FutureMessage::new(dur, rx, msg)
-        })
+        self.handle.register(self.ctx.run_later(dur, f))
     }
 
-    /// run exclusive closure on context after given duration. `Handler::handle_wait` will be
-    /// called.
-    /// If `Handler::handle_wait` is not override `Handler::handle` will be called as fallback.
+    /// run exclusive closure on context in group after given duration. See
+    /// `Context::run_wait_later`.
     pub fn run_wait_later<F>(&self, dur: Duration, f: F) -> ContextJoinHandle
     where
         F: for<'a> FnOnce(&'a mut A, Context<'a, A>) -> LocalBoxFuture<'a, ()> + 'static,
     {
-        self.later(|rx| {
-            let msg = FunctionMutMessage::<_, ()>::new(f);
-            let msg = ActorMessage::new_mut(msg, None);
-            FutureMessage::new(dur, rx, msg)
-        })
+        self.handle.register(self.ctx.run_wait_later(dur, f))
     }
 
-    fn later<F>(&self, f: F) -> ContextJoinHandle
+    /// spawn a future on context in group. See `Context::spawn`.
+    pub fn spawn<F>(&self, fut: F) -> ContextJoinHandle
     where
-        F: FnOnce(OneshotReceiver<()>) -> FutureMessage<A>,
+        F: Future<Output = ()> + 'static,
     {
-        let (handle, rx) = oneshot();
-        self.inner.future_cache.borrow_mut().push(f(rx));
-        ContextJoinHandle { handle }
+        self.handle.register(self.ctx.spawn(fut))
     }
 
-    /// stop the context. It would end the actor gracefully by close the channel draining all
-    /// remaining messages.
-    pub fn stop(&self) {
-        self.inner.rx.borrow().close();
-        self.inner.state.set(ActorState::StopGraceful);
+    /// add a stream to context in group. See `Context::add_stream`.
+    pub fn add_stream<S>(&self, stream: S) -> ContextJoinHandle
+    where
+        S: Stream + 'static,
+        S::Item: Message + 'static,
+        A: Handler<S::Item>,
+    {
+        self.handle.register(self.ctx.add_stream(stream))
     }
 
-    /// get the address of actor from context.
+    /// add a stream to context in group. See `Context::add_wait_stream`.
+    pub fn add_wait_stream<S>(&self, stream: S) -> ContextJoinHandle
+    where
+        S: Stream + 'static,
+        S::Item: Message + 'static,
+        A: Handler<S::Item>,
+    {
+        self.handle.register(self.ctx.add_wait_stream(stream))
+    }
+}
+
+/// shared handle of a `TaskGroup`. Can be stored in actor's state and add more tasks to the
+/// group later with `Context::with_task_group`.
+#[derive(Clone)]
+pub struct TaskGroupHandle {
+    tasks: RefCounter<Lock<Vec<RefCounter<JoinState>>>>,
+}
+
+impl Default for TaskGroupHandle {
+    fn default() -> Self {
+        Self {
+            tasks: RefCounter::new(Lock::new(Vec::new())),
+        }
+    }
+}
+
+impl TaskGroupHandle {
+    fn register(&self, handle: ContextJoinHandle) -> ContextJoinHandle {
+        let mut tasks = self.tasks.lock();
+        // remove terminated tasks so the group does not grow forever.
+        tasks.retain(|state| state.get() & TERMINATE == 0);
+        tasks.push(handle.state.clone());
+        handle
+    }
+
+    fn for_each<F>(&self, f: F)
+    where
+        F: Fn(&JoinState),
+    {
+        self.tasks.lock().iter().for_each(|state| f(state));
+    }
+
+    /// Cancel all tasks in group.
+    pub fn cancel(&self) {
+        self.for_each(|state| state.set(CANCEL));
+    }
+
+    /// Pause all tasks in group. See `ContextJoinHandle::pause`.
+    pub fn pause(&self) {
+        self.for_each(|state| state.set(PAUSE));
+    }
+
+    /// Resume all paused tasks in group.
+    pub fn resume(&self) {
+        self.for_each(|state| state.unset(PAUSE));
+    }
+
+    /// Check if all tasks in group are terminated.
+    pub fn is_terminated(&self) -> bool {
+        self.tasks
+            .lock()
+            .iter()
+            .all(|state| state.get() & TERMINATE != 0)
+    }
+
+    /// Wait for all tasks in group to terminate.
+    ///
+    /// *. Tasks added to group after the returned future is polled are also waited.
     #[inline]
-    pub fn address(&self) -> Option<Addr<A>> {
-        Addr::from_recv(&*self.inner.rx.borrow()).ok()
+    pub fn join(&self) -> TaskGroupJoin<'_> {
+        TaskGroupJoin { handle: self }
+    }
+}
+
+/// Future returned by `TaskGroupHandle::join`.
+pub struct TaskGroupJoin<'a> {
+    handle: &'a TaskGroupHandle,
+}
+
+impl Future for TaskGroupJoin<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Self::Output> {
+        let mut tasks = self.handle.tasks.lock();
+
+        // register waker before check the state so a task terminated in between would not be
+        // missed.
+        tasks.retain(|state| {
+            state.register_join(cx.waker());
+            state.get() & TERMINATE == 0
+        });
+
+        if tasks.is_empty() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// message sent to actor when it's child actor is stopped. See `Context::spawn_child`.
+pub struct ChildStopped<C> {
+    reason: StopReason,
+    _child: PhantomData<fn() -> C>,
+}
+
+impl<C> ChildStopped<C> {
+    /// the reason child actor stopped.
+    #[inline]
+    pub fn reason(&self) -> StopReason {
+        self.reason.clone()
     }
+}
 
-    /// add a stream to context. multiple stream can be added to one context.
+impl<C: 'static> Message for ChildStopped<C> {
+    type Result = ();
+}
+
+impl<'c, A: Actor> Context<'c, A> {
+    pub(crate) fn new(inner: &'c ContextInner<A>) -> Self {
+        Context {
+            inner,
+            envelope: None,
+            sink: None,
+            response: None,
+        }
+    }
+
+    // copy of context for handling multiple messages in one task.
+    pub(crate) fn reborrow(&self) -> Context<'c, A> {
+        Context {
+            inner: self.inner,
+            envelope: self.envelope,
+            sink: self.sink.clone(),
+            response: self.response.clone(),
+        }
+    }
+
+    // attach the envelope of message currently handled.
+    pub(crate) fn with_envelope(mut self, envelope: Envelope) -> Self {
+        self.envelope = Some(envelope);
+        self
+    }
+
+    // attach the response sink of message currently handled.
+    pub(crate) fn with_response_sink<R: Send + 'static>(mut self, sink: ResponseSink<R>) -> Self {
+        self.sink = Some(RefCounter::new(sink));
+        self
+    }
+
+    /// sink of streaming response for the message currently handled. See `Addr::send_stream`.
     ///
-    /// stream item will be treated as concurrent message and `Handler::handle` will be called.
-    /// If `Handler::handle_wait` is not override `Handler::handle` will be called as fallback.
+    /// Return `None` when message is not sent by `Addr::send_stream` or the item type is not
+    /// the one requested by sender.
     ///
-    /// *. Stream would force closed when the actor is stopped. Either by dropping all `Addr` or
-    /// calling `Addr::stop`
+    /// *. The response stream is ended when all clones of sink are dropped. By default it's
+    /// when the handler is finished.
+    #[inline]
+    pub fn response_sink<R: Send + 'static>(&self) -> Option<ResponseSink<R>> {
+        self.sink.as_ref()?.downcast_ref().cloned()
+    }
+
+    // attach the response channel of message currently handled.
+    pub(crate) fn with_response<R: 'static>(mut self, tx: Option<&OneshotSender<R>>) -> Self {
+        self.response = tx.map(OneshotSender::as_any);
+        self
+    }
+
+    /// take over the response of message currently handled. The result returned by handler is
+    /// ignored and the caller receives the value sent through the handle.
+    ///
+    /// Return `None` when the message is not the given type, it has no caller waiting for the
+    /// result or the handle is already taken.
+    ///
+    /// *. Caller receives `ActixAsyncError::Closed` when the handle is dropped without sending.
     ///
     /// # example:
     /// ```rust
     /// use actix_async::prelude::*;
-    /// use futures_util::stream::once;
     ///
-    /// struct StreamActor;
-    /// actor!(StreamActor);
+    /// struct TestActor;
+    /// actor!(TestActor);
     ///
-    /// struct StreamMessage;
-    /// message!(StreamMessage, ());
+    /// struct Query;
+    /// message!(Query, usize);
     ///
     /// #[actix_async::handler]
-    /// impl Handler<StreamMessage> for StreamActor {
-    ///     async fn handle(&self, _: StreamMessage, _: Context<'_, Self>) {
-    ///     /*
-    ///         The stream is owned by Context so there is no default way to return anything
-    ///         from the handler.
-    ///         A suggest way to return anything here is to use a channel sender or another
-    ///         actor's Addr to StreamActor as it's state.
-    ///     */
+    /// impl Handler<Query> for TestActor {
+    ///     async fn handle(&self, _: Query, ctx: Context<'_, Self>) -> usize {
+    ///         let handle = ctx.response_handle::<Query>().unwrap();
+    ///
+    ///         // send the response from another task.
+    ///         tokio::spawn(async move {
+    ///             let _ = handle.send(996);
+    ///         });
+    ///
+    ///         // the result is ignored.
+    ///         0
     ///     }
     /// }
     ///
     /// #[actix_async::main]
     /// async fn main() {
-    ///     let address = StreamActor::create(|ctx| {
-    ///         ctx.add_stream(once(async { StreamMessage }));
-    ///         StreamActor
-    ///     });
+    ///     let addr = TestActor.start();
+    ///     assert_eq!(addr.send(Query).await.unwrap(), 996);
     /// }
     /// ```
-    #[inline]
-    pub fn add_stream<S>(&self, stream: S) -> ContextJoinHandle
-    where
-        S: Stream + 'static,
-        S::Item: Message + 'static,
-        A: Handler<S::Item>,
-    {
-        self.stream(stream, |item| ActorMessage::new_ref(item, None))
+    pub fn response_handle<M: Message>(&self) -> Option<ResponseHandle<M::Result>> {
+        let tx = OneshotSender::defer(self.response.as_ref()?)?;
+        Some(ResponseHandle::new(tx))
     }
 
-    /// add a stream to context. multiple stream can be added to one context.
+    /// metadata of the message currently handled.
     ///
-    /// stream item will be treated as exclusive message and `Handler::handle_wait` will be called.
+    /// Return `None` when context is not from a message handler. (`Actor::on_start`,
+    /// `Context::run_interval` and etc.)
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct EnvelopeActor;
+    /// actor!(EnvelopeActor);
+    ///
+    /// struct Traced;
+    /// message!(Traced, Option<usize>);
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Traced> for EnvelopeActor {
+    ///     async fn handle(&self, _: Traced, ctx: Context<'_, Self>) -> Option<usize> {
+    ///         ctx.current_envelope().unwrap().sender()
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = EnvelopeActor.start();
+    ///     let res = addr.send(Traced).await.unwrap();
+    ///     assert_eq!(res, Some(addr.id()));
+    /// }
+    /// ```
     #[inline]
-    pub fn add_wait_stream<S>(&self, stream: S) -> ContextJoinHandle
-    where
-        S: Stream + 'static,
-        S::Item: Message + 'static,
-        A: Handler<S::Item>,
-    {
-        self.stream(stream, |item| ActorMessage::new_mut(item, None))
+    pub fn current_envelope(&self) -> Option<Envelope> {
+        self.envelope
     }
 
-    fn stream<S, F>(&self, stream: S, f: F) -> ContextJoinHandle
+    /// replace actor with a new instance constructed by given closure.
+    pub(crate) async fn restart<F, Fut>(&self, act: &mut A, f: F)
     where
-        S: Stream + 'static,
-        S::Item: Message + 'static,
-        A: Handler<S::Item>,
-        F: FnOnce(S::Item) -> ActorMessage<A> + Copy + 'static,
+        F: for<'a> FnOnce(Context<'a, A>) -> Fut,
+        Fut: Future<Output = A>,
     {
-        let (handle, rx) = oneshot();
-        let stream = StreamContainer::new(stream, rx, f);
-        let msg = StreamMessage::new_boxed(stream);
+        self.inner.lifecycle.stopping();
+        act.on_stop(StopReason::Restart, Context::new(self.inner))
+            .await;
+
+        *act = f(Context::new(self.inner)).await;
+
+        if act
+            .on_start_fallible(Context::new(self.inner))
+            .await
+            .is_err()
+        {
+            self.inner.set_stop_reason(StopReason::StartFailed);
+            self.inner.state.set(ActorState::Stop);
+        } else {
+            self.inner.lifecycle.started();
+        }
+    }
+
+    /// create a new task group on context. See `TaskGroup` for detail.
+    #[inline]
+    pub fn task_group(&self) -> TaskGroup<'c, A> {
+        self.with_task_group(&TaskGroupHandle::default())
+    }
+
+    /// add tasks to an existing group with it's handle.
+    #[inline]
+    pub fn with_task_group(&self, handle: &TaskGroupHandle) -> TaskGroup<'c, A> {
+        TaskGroup {
+            ctx: Context::new(self.inner),
+            handle: handle.clone(),
+        }
+    }
+
+    /// run interval concurrent closure on context. `Handler::handle` will be called.
+    ///
+    /// The closure is cloned on every tick and can return `ControlFlow::Break` to cancel the
+    /// interval from within. See `IntervalFlow` for detail.
+    pub fn run_interval<F, R>(&self, dur: Duration, f: F) -> ContextJoinHandle
+    where
+        F: for<'a> FnOnce(&'a A, Context<'a, A>) -> LocalBoxFuture<'a, R> + Clone + 'static,
+        R: IntervalFlow,
+    {
+        self.run_interval_mut(dur, move |act, ctx| (f.clone())(act, ctx))
+    }
+
+    /// run interval exclusive closure on context. `Handler::handle_wait` will be called.
+    /// If `Handler::handle_wait` is not override `Handler::handle` will be called as fallback.
+    ///
+    /// Like `Context::run_interval` the closure can return `ControlFlow::Break` to cancel the
+    /// interval.
+    pub fn run_wait_interval<F, R>(&self, dur: Duration, f: F) -> ContextJoinHandle
+    where
+        F: for<'a> FnOnce(&'a mut A, Context<'a, A>) -> LocalBoxFuture<'a, R> + Clone + 'static,
+        R: IntervalFlow,
+    {
+        self.run_wait_interval_mut(dur, move |act, ctx| (f.clone())(act, ctx))
+    }
+
+    /// run interval concurrent closure on context. Unlike `Context::run_interval` the closure is
+    /// not cloned on every tick and can keep mutable state across ticks.
+    ///
+    /// The closure can return `ControlFlow::Break` to cancel the interval from within. See
+    /// `IntervalFlow` for detail.
+    ///
+    /// *. The closure is called at the start of every tick. The returned future must not borrow
+    /// the closure itself.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::{ops::ControlFlow, time::Duration};
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     addr.run(|_, ctx| {
+    ///         Box::pin(async move {
+    ///             let mut ticks = 0usize;
+    ///             ctx.run_interval_mut(Duration::from_millis(100), move |_, _| {
+    ///                 ticks += 1;
+    ///                 let ticks = ticks;
+    ///                 Box::pin(async move {
+    ///                     println!("tick {}", ticks);
+    ///                     // stop the interval after 3 ticks.
+    ///                     if ticks == 3 {
+    ///                         ControlFlow::Break(())
+    ///                     } else {
+    ///                         ControlFlow::Continue(())
+    ///                     }
+    ///                 })
+    ///             });
+    ///         })
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// }
+    /// ```
+    pub fn run_interval_mut<F, R>(&self, dur: Duration, f: F) -> ContextJoinHandle
+    where
+        F: for<'a> FnMut(&'a A, Context<'a, A>) -> LocalBoxFuture<'a, R> + 'static,
+        R: IntervalFlow,
+    {
+        self.interval(|rx| {
+            let msg = IntervalFn::new(f, rx.cancel_handle());
+            IntervalMessage::new(dur, rx, ActorMessageClone::Ref(Box::new(msg)))
+        })
+    }
+
+    /// run interval exclusive closure on context. See `Context::run_interval_mut`.
+    pub fn run_wait_interval_mut<F, R>(&self, dur: Duration, f: F) -> ContextJoinHandle
+    where
+        F: for<'a> FnMut(&'a mut A, Context<'a, A>) -> LocalBoxFuture<'a, R> + 'static,
+        R: IntervalFlow,
+    {
+        self.interval(|rx| {
+            let msg = IntervalWaitFn::new(f, rx.cancel_handle());
+            IntervalMessage::new(dur, rx, ActorMessageClone::Mut(Box::new(msg)))
+        })
+    }
+
+    /// run concurrent closure on context on every tick of a cron schedule. `Handler::handle`
+    /// will be called.
+    ///
+    /// The expression is parsed by `cron` crate and it's ticks are evaluated in UTC. Like
+    /// `Context::run_interval_mut` the closure can keep state across ticks and return
+    /// `ControlFlow::Break` to cancel the schedule.
+    ///
+    /// Return error when the expression is invalid.
+    ///
+    /// *. Only available with `cron` feature.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     addr.run(|_, ctx| {
+    ///         Box::pin(async move {
+    ///             // run on every 5 minutes.
+    ///             ctx.run_cron("0 */5 * * * *", |_, _| {
+    ///                 Box::pin(async { println!("report") })
+    ///             })
+    ///             .unwrap();
+    ///         })
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// }
+    /// ```
+    #[cfg(feature = "cron")]
+    pub fn run_cron<F, R>(&self, expr: &str, f: F) -> Result<ContextJoinHandle, cron::error::Error>
+    where
+        F: for<'a> FnMut(&'a A, Context<'a, A>) -> LocalBoxFuture<'a, R> + 'static,
+        R: IntervalFlow,
+    {
+        let schedule = expr.parse::<cron::Schedule>()?;
+        let (handle, task) = join_handle();
+        let cancel = task.cancel_handle();
+        let msg = IntervalFn::new(f, cancel.clone());
+        let msg = CronMessage::new(schedule, task, ActorMessageClone::Ref(Box::new(msg)));
+        let msg = StreamMessage::new_timer(msg, cancel);
+        self.inner.stream_cache.borrow_mut().push(msg);
+        Ok(handle)
+    }
+
+    fn interval<F>(&self, f: F) -> ContextJoinHandle
+    where
+        F: FnOnce(TaskHandle) -> IntervalMessage<A>,
+    {
+        let (handle, task) = join_handle();
+        self.add_interval(f(task));
+        handle
+    }
+
+    // add interval message with it's task handle constructed outside of context.
+    pub(crate) fn add_interval(&self, msg: IntervalMessage<A>) {
+        let msg = StreamMessage::new_interval(msg);
+        self.inner.stream_cache.borrow_mut().push(msg);
+    }
+
+    /// run concurrent closure on context after given duration. `Handler::handle` will be called.
+    pub fn run_later<F>(&self, dur: Duration, f: F) -> ContextJoinHandle
+    where
+        F: for<'a> FnOnce(&'a A, Context<'a, A>) -> LocalBoxFuture<'a, ()> + 'static,
+    {
+        self.later(|rx| {
+            let msg = FunctionMessage::<_, ()>::new(f);
+            let msg = ActorMessage::new_ref_local(msg, None);
+            FutureMessage::new(dur, rx, msg)
+        })
+    }
+
+    /// run exclusive closure on context after given duration. `Handler::handle_wait` will be
+    /// called.
+    /// If `Handler::handle_wait` is not override `Handler::handle` will be called as fallback.
+    pub fn run_wait_later<F>(&self, dur: Duration, f: F) -> ContextJoinHandle
+    where
+        F: for<'a> FnOnce(&'a mut A, Context<'a, A>) -> LocalBoxFuture<'a, ()> + 'static,
+    {
+        self.later(|rx| {
+            let msg = FunctionMutMessage::<_, ()>::new(f);
+            let msg = ActorMessage::new_mut_local(msg, None);
+            FutureMessage::new(dur, rx, msg)
+        })
+    }
+
+    /// run concurrent closure on context at given deadline. `Handler::handle` will be called.
+    ///
+    /// Unlike `Context::run_later` the deadline does not drift when it's computed ahead of
+    /// time. Closure runs immediately when the deadline is already passed.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let deadline = Instant::now() + Duration::from_secs(1);
+    ///
+    ///     addr.run(move |_, ctx| {
+    ///         Box::pin(async move {
+    ///             ctx.run_at(deadline, |_, _| Box::pin(async { println!("deadline reached") }));
+    ///         })
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn run_at<F>(&self, deadline: std::time::Instant, f: F) -> ContextJoinHandle
+    where
+        F: for<'a> FnOnce(&'a A, Context<'a, A>) -> LocalBoxFuture<'a, ()> + 'static,
+    {
+        self.later(|rx| {
+            let msg = FunctionMessage::<_, ()>::new(f);
+            let msg = ActorMessage::new_ref_local(msg, None);
+            FutureMessage::new_at(deadline, rx, msg)
+        })
+    }
+
+    /// run exclusive closure on context at given deadline. `Handler::handle_wait` will be
+    /// called. See `Context::run_at` for detail.
+    #[cfg(feature = "std")]
+    pub fn run_wait_at<F>(&self, deadline: std::time::Instant, f: F) -> ContextJoinHandle
+    where
+        F: for<'a> FnOnce(&'a mut A, Context<'a, A>) -> LocalBoxFuture<'a, ()> + 'static,
+    {
+        self.later(|rx| {
+            let msg = FunctionMutMessage::<_, ()>::new(f);
+            let msg = ActorMessage::new_mut_local(msg, None);
+            FutureMessage::new_at(deadline, rx, msg)
+        })
+    }
+
+    /// send a concurrent message to actor itself. `Handler::handle` will be called.
+    ///
+    /// The message is added to context directly and bypass actor's mailbox. A full mailbox
+    /// would not block or reject it.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Ping(usize);
+    /// message!(Ping, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Ping> for TestActor {
+    ///     async fn handle(&self, msg: Ping, ctx: Context<'_, Self>) {
+    ///         // keep notify self until counter is zero.
+    ///         if msg.0 > 0 {
+    ///             ctx.notify(Ping(msg.0 - 1));
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///     addr.send(Ping(3)).await.unwrap();
+    /// }
+    /// ```
+    pub fn notify<M>(&self, msg: M)
+    where
+        M: Message,
+        A: Handler<M>,
+    {
+        self.notify_now(ActorMessage::new_ref_local(msg, None));
+    }
+
+    /// send an exclusive message to actor itself. `Handler::handle_wait` will be called.
+    /// If `Handler::handle_wait` is not override `Handler::handle` will be called as fallback.
+    ///
+    /// *. See `Context::notify` for detail.
+    pub fn notify_wait<M>(&self, msg: M)
+    where
+        M: Message,
+        A: Handler<M>,
+    {
+        self.notify_now(ActorMessage::new_mut_local(msg, None));
+    }
+
+    /// send a concurrent message to actor itself after given duration. `Handler::handle` will
+    /// be called.
+    ///
+    /// *. See `Context::notify` for detail.
+    pub fn notify_later<M>(&self, msg: M, dur: Duration) -> ContextJoinHandle
+    where
+        M: Message,
+        A: Handler<M>,
+    {
+        self.later(|rx| FutureMessage::new(dur, rx, ActorMessage::new_ref_local(msg, None)))
+    }
+
+    /// send an exclusive message to actor itself after given duration. `Handler::handle_wait`
+    /// will be called.
+    /// If `Handler::handle_wait` is not override `Handler::handle` will be called as fallback.
+    ///
+    /// *. See `Context::notify` for detail.
+    pub fn notify_wait_later<M>(&self, msg: M, dur: Duration) -> ContextJoinHandle
+    where
+        M: Message,
+        A: Handler<M>,
+    {
+        self.later(|rx| FutureMessage::new(dur, rx, ActorMessage::new_mut_local(msg, None)))
+    }
+
+    fn notify_now(&self, msg: ActorMessage<A>) {
+        let msg = StreamMessage::new_boxed(FutureStream::new(ready(msg)));
+        self.inner.stream_cache.borrow_mut().push(msg);
+    }
+
+    fn later<F>(&self, f: F) -> ContextJoinHandle
+    where
+        F: FnOnce(TaskHandle) -> FutureMessage<A>,
+    {
+        let (handle, task) = join_handle();
+        self.add_later(f(task));
+        handle
+    }
+
+    // add delayed message with it's task handle constructed outside of context.
+    pub(crate) fn add_later(&self, msg: FutureMessage<A>) {
+        self.inner.future_cache.borrow_mut().push(msg);
+    }
+
+    /// cancel all delayed tasks, intervals and cron schedules on context. Including the ones
+    /// scheduled from `Addr`.
+    ///
+    /// Their `ContextJoinHandle` are terminated and pending messages are dropped.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let count = addr
+    ///         .run(|_, ctx| {
+    ///             Box::pin(async move {
+    ///                 ctx.run_later(Duration::from_secs(1), |_, _| Box::pin(async {}));
+    ///                 ctx.run_interval(Duration::from_secs(1), |_, _| Box::pin(async {}));
+    ///                 assert_eq!(ctx.timer_count(), 2);
+    ///
+    ///                 ctx.cancel_all_timers();
+    ///                 ctx.timer_count()
+    ///             })
+    ///         })
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     assert_eq!(count, 0);
+    /// }
+    /// ```
+    pub fn cancel_all_timers(&self) {
+        self.inner.future_cache.borrow_mut().clear();
+        self.inner
+            .stream_cache
+            .borrow_mut()
+            .retain(|stream| !stream.is_timer());
+    }
+
+    /// cancel all streams added to context. See `Context::cancel_all_timers`.
+    ///
+    /// *. Futures spawned by `Context::spawn` are not canceled.
+    pub fn cancel_all_streams(&self) {
+        self.inner
+            .stream_cache
+            .borrow_mut()
+            .retain(|stream| !stream.is_stream());
+    }
+
+    /// count of delayed tasks, intervals and cron schedules on context.
+    ///
+    /// *. Tasks canceled by `ContextJoinHandle` are counted until actor polls and removes them.
+    pub fn timer_count(&self) -> usize {
+        let streams = self.inner.stream_cache.borrow();
+        self.inner.future_cache.borrow().len() + streams.iter().filter(|s| s.is_timer()).count()
+    }
+
+    /// count of streams added to context. See `Context::timer_count`.
+    pub fn stream_count(&self) -> usize {
+        let streams = self.inner.stream_cache.borrow();
+        streams.iter().filter(|s| s.is_stream()).count()
+    }
+
+    /// snapshot of delayed tasks, intervals, timers and streams scheduled on context.
+    ///
+    /// *. Futures spawned by `Context::spawn` and streams used internally by context are not
+    /// listed.
+    ///
+    /// *. Tasks canceled by `ContextJoinHandle` are listed until actor polls and removes them.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use actix_async::context::ScheduledKind;
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let tasks = addr
+    ///         .run(|_, ctx| {
+    ///             ctx.run_later(Duration::from_secs(3), |_, _| Box::pin(async {}));
+    ///             ctx.run_interval(Duration::from_secs(1), |_, _| Box::pin(async {}));
+    ///             let tasks = ctx.scheduled_tasks();
+    ///             Box::pin(async move { tasks })
+    ///         })
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     assert_eq!(tasks.len(), 2);
+    ///
+    ///     let later = tasks.iter().find(|t| t.kind() == ScheduledKind::Later).unwrap();
+    ///     assert!(later.remaining().unwrap() > Duration::from_secs(2));
+    /// }
+    /// ```
+    pub fn scheduled_tasks(&self) -> Vec<ScheduledTask> {
+        let futures = self.inner.future_cache.borrow();
+        let streams = self.inner.stream_cache.borrow();
+
+        futures
+            .iter()
+            .map(FutureMessage::describe)
+            .chain(streams.iter().filter_map(StreamMessage::describe))
+            .collect()
+    }
+
+    /// pause receiving new message from actor's mailbox.
+    ///
+    /// Running tasks, intervals, delayed and stream tasks on context are not affected. Messages
+    /// sent to actor stay in mailbox until `Context::resume` is called.
+    ///
+    /// *. A paused actor does not observe all of it's addresses dropped until it's resumed.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let _ = addr
+    ///         .run(|_, ctx| {
+    ///             Box::pin(async move {
+    ///                 ctx.pause();
+    ///                 // resume after 1 second. i.e waiting for a downstream to recover.
+    ///                 ctx.run_later(Duration::from_secs(1), |_, ctx| {
+    ///                     Box::pin(async move { ctx.resume() })
+    ///                 });
+    ///             })
+    ///         })
+    ///         .await;
+    ///
+    ///     // message is handled after context resumed.
+    ///     let res = addr.run(|_, _| Box::pin(async { 996 })).await;
+    ///     assert_eq!(res.unwrap(), 996);
+    /// }
+    /// ```
+    #[inline]
+    pub fn pause(&self) {
+        self.inner.pause();
+    }
+
+    /// resume receiving new message from actor's mailbox. See `Context::pause`.
+    #[inline]
+    pub fn resume(&self) {
+        self.inner.resume();
+    }
+
+    /// check if context is paused from receiving new message.
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.inner.is_paused()
+    }
+
+    /// capacity of actor's mailbox.
+    #[inline]
+    pub fn mailbox_capacity(&self) -> usize {
+        self.inner.rx.borrow().capacity()
+    }
+
+    /// change the capacity of actor's mailbox while it's running. i.e shed load by shrinking it
+    /// when actor is overloaded.
+    ///
+    /// Messages already in mailbox are kept when the capacity shrinks below their count. Senders
+    /// would wait(or act by `Actor::overflow_policy`) until the mailbox drains below the new
+    /// capacity.
+    ///
+    /// *. When `Actor::size_hint_bounds` is set the capacity keeps adapting from the new value
+    /// within the bounds.
+    ///
+    /// # Panics
+    /// panic when capacity is zero.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let res = addr
+    ///         .run(|_, ctx| {
+    ///             Box::pin(async move {
+    ///                 ctx.set_mailbox_capacity(16);
+    ///                 ctx.mailbox_capacity()
+    ///             })
+    ///         })
+    ///         .await;
+    ///
+    ///     assert_eq!(res.unwrap(), 16);
+    /// }
+    /// ```
+    #[inline]
+    pub fn set_mailbox_capacity(&self, cap: usize) {
+        self.inner.rx.borrow().set_capacity(cap);
+    }
+
+    /// stop the context. It would end the actor gracefully by close the channel draining all
+    /// remaining messages.
+    pub fn stop(&self) {
+        self.inner.rx.borrow().close();
+        self.inner.state.set(ActorState::StopGraceful);
+    }
+
+    /// stop the context gracefully like `Context::stop` with the given reason.
+    ///
+    /// The reason is passed to `Actor::on_stop`, resolved by `Addr::watch` and observed by
+    /// `Supervisor` through `SupervisedState::reason`.
+    ///
+    /// *. When actor is already stopping with a reason the first one is kept.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// // application error observed by watchers of actor.
+    /// #[derive(Debug, PartialEq)]
+    /// enum JobError {
+    ///     Timeout,
+    /// }
+    ///
+    /// impl From<JobError> for StopReason {
+    ///     fn from(e: JobError) -> Self {
+    ///         StopReason::custom(e)
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///     let watch = addr.watch();
+    ///
+    ///     let _ = addr
+    ///         .run(|_, ctx| {
+    ///             ctx.stop_with(JobError::Timeout);
+    ///             Box::pin(async {})
+    ///         })
+    ///         .await;
+    ///
+    ///     match watch.await {
+    ///         StopReason::Custom(reason) => {
+    ///             assert_eq!(reason.downcast_ref(), Some(&JobError::Timeout))
+    ///         }
+    ///         _ => unreachable!(),
+    ///     }
+    /// }
+    /// ```
+    pub fn stop_with(&self, reason: impl Into<StopReason>) {
+        self.inner.set_stop_reason(reason.into());
+        self.stop();
+    }
+
+    /// start a child actor and return it's address.
+    ///
+    /// The child is owned by the actor and stays alive as long as the actor is running. When the
+    /// actor stops, all it's children are stopped gracefully. When a child stops, the actor
+    /// receives a `ChildStopped` message as concurrent message.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    /// use actix_async::context::ChildStopped;
+    ///
+    /// struct Parent;
+    /// actor!(Parent);
+    ///
+    /// struct Child;
+    /// actor!(Child);
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<ChildStopped<Child>> for Parent {
+    ///     async fn handle(&self, msg: ChildStopped<Child>, _: Context<'_, Self>) {
+    ///         // child is stopped.
+    ///         let _reason = msg.reason();
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let parent = Parent.start();
+    ///
+    ///     let child = parent
+    ///         .run(|_, ctx| Box::pin(async move { ctx.spawn_child(Child) }))
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     // stop parent would stop child.
+    ///     let _ = parent.stop(true).await;
+    ///     assert_eq!(child.watch().await, StopReason::Graceful);
+    /// }
+    /// ```
+    pub fn spawn_child<C>(&self, child: C) -> Addr<C>
+    where
+        C: Actor,
+        A: Handler<ChildStopped<C>>,
+    {
+        let addr = child.start();
+
+        let watch = addr.watch();
+        let fut = Box::pin(async move {
+            let msg = ChildStopped {
+                reason: watch.await,
+                _child: PhantomData,
+            };
+            ActorMessage::new_ref(msg, None)
+        });
+        let msg = StreamMessage::new_boxed(FutureStream::new(fut));
+        self.inner.stream_cache.borrow_mut().push(msg);
+
+        self.inner.add_child(Box::new(addr.clone()));
+
+        addr
+    }
+
+    /// unique id of actor. See `Addr::actor_id`.
+    #[inline]
+    pub fn actor_id(&self) -> ActorId {
+        self.inner.lifecycle.actor_id()
+    }
+
+    /// broadcast event to all subscribers of it's type. See `Addr::subscribe`.
+    ///
+    /// Return the count of subscribers received the event. Event is dropped when there is no
+    /// subscriber.
+    #[inline]
+    pub fn emit<E>(&self, event: E) -> usize
+    where
+        E: Clone + Send + 'static,
+    {
+        self.inner.lifecycle.publish(event)
+    }
+
+    /// get the address of actor from context.
+    #[inline]
+    pub fn address(&self) -> Option<Addr<A>> {
+        Addr::from_recv(&*self.inner.rx.borrow(), &self.inner.lifecycle).ok()
+    }
+
+    /// spawn a future on context. The future is driven by actor's `ContextFuture` together with
+    /// messages and is dropped when the actor is stopped.
+    ///
+    /// *. Like streams added to context the future is not polled when actor is handling
+    /// exclusive message.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///
+    ///     let handle = addr
+    ///         .run(|_, ctx| {
+    ///             Box::pin(async move {
+    ///                 ctx.spawn(async {
+    ///                     tokio::time::sleep(Duration::from_secs(1)).await;
+    ///                     println!("spawned future finished");
+    ///                 })
+    ///             })
+    ///         })
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     // spawned future can be canceled with it's handle.
+    ///     handle.cancel();
+    /// }
+    /// ```
+    pub fn spawn<F>(&self, fut: F) -> ContextJoinHandle
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        let (handle, task) = join_handle();
+        let msg = StreamMessage::new_boxed(SpawnedFuture::new(fut, task));
+        self.inner.stream_cache.borrow_mut().push(msg);
+        handle
+    }
+
+    /// run a future on context exclusively. No new message is taken from actor's mailbox until
+    /// the future is resolved.
+    ///
+    /// The future is handled like an exclusive message. It starts after all running concurrent
+    /// messages are finished and futures from multiple calls run one after another in order.
+    ///
+    /// *. The future is dropped without being polled when the actor is stopped before it starts.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct Flush;
+    /// message!(Flush, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Flush> for TestActor {
+    ///     async fn handle(&self, _: Flush, ctx: Context<'_, Self>) {
+    ///         // messages sent after Flush are handled when the flush is done.
+    ///         ctx.wait(async {
+    ///             tokio::time::sleep(Duration::from_millis(100)).await;
+    ///         });
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor.start();
+    ///     addr.send(Flush).await.unwrap();
+    /// }
+    /// ```
+    pub fn wait<F>(&self, fut: F)
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        self.inner.add_wait(wait_task(fut));
+    }
+
+    /// add a stream to context. multiple stream can be added to one context.
+    ///
+    /// stream item will be treated as concurrent message and `Handler::handle` will be called.
+    /// If `Handler::handle_wait` is not override `Handler::handle` will be called as fallback.
+    ///
+    /// *. Stream would force closed when the actor is stopped. Either by dropping all `Addr` or
+    /// calling `Addr::stop`
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    /// use futures_util::stream::once;
+    ///
+    /// struct StreamActor;
+    /// actor!(StreamActor);
+    ///
+    /// struct StreamMessage;
+    /// message!(StreamMessage, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<StreamMessage> for StreamActor {
+    ///     async fn handle(&self, _: StreamMessage, _: Context<'_, Self>) {
+    ///     /*
+    ///         The stream is owned by Context so there is no default way to return anything
+    ///         from the handler.
+    ///         A suggest way to return anything here is to use a channel sender or another
+    ///         actor's Addr to StreamActor as it's state.
+    ///     */
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let address = StreamActor::create(|ctx| {
+    ///         ctx.add_stream(once(async { StreamMessage }));
+    ///         StreamActor
+    ///     });
+    /// }
+    /// ```
+    #[inline]
+    pub fn add_stream<S>(&self, stream: S) -> ContextJoinHandle
+    where
+        S: Stream + 'static,
+        S::Item: Message + 'static,
+        A: Handler<S::Item>,
+    {
+        self.stream(stream, |item| ActorMessage::new_ref_local(item, None))
+    }
+
+    /// add a stream to context with a limit of it's items handled concurrently. See
+    /// `Context::add_stream`.
+    ///
+    /// Stream is not polled when `max_in_flight` items are still being handled so a fast stream
+    /// can not queue unbounded tasks on actor. `max_in_flight` of 0 is treated as 1.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    /// use futures_util::stream::repeat_with;
+    ///
+    /// struct StreamActor;
+    /// actor!(StreamActor);
+    ///
+    /// struct Tick;
+    /// message!(Tick, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Tick> for StreamActor {
+    ///     async fn handle(&self, _: Tick, _: Context<'_, Self>) {
+    ///         tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let address = StreamActor::create(|ctx| {
+    ///         // at most 8 ticks are handled at the same time.
+    ///         ctx.add_stream_limited(repeat_with(|| Tick), 8);
+    ///         StreamActor
+    ///     });
+    /// }
+    /// ```
+    #[inline]
+    pub fn add_stream_limited<S>(&self, stream: S, max_in_flight: usize) -> ContextJoinHandle
+    where
+        S: Stream + 'static,
+        S::Item: Message + 'static,
+        A: Handler<S::Item>,
+    {
+        self.add_stream::<InFlightStream<S>>(InFlightStream::new(stream, max_in_flight))
+    }
+
+    /// add a stream to context and map it's items to message with given closure. See
+    /// `Context::add_stream`.
+    ///
+    /// It can be used to attach stream with items that are not `Message` type.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    /// use futures_util::stream::iter;
+    ///
+    /// struct StreamActor;
+    /// actor!(StreamActor);
+    ///
+    /// struct Frame(Vec<u8>);
+    /// message!(Frame, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Frame> for StreamActor {
+    ///     async fn handle(&self, msg: Frame, _: Context<'_, Self>) {
+    ///         println!("received {} bytes", msg.0.len());
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let address = StreamActor::create(|ctx| {
+    ///         ctx.add_stream_map(iter(vec![vec![0u8; 8], vec![0u8; 16]]), Frame);
+    ///         StreamActor
+    ///     });
+    /// }
+    /// ```
+    #[inline]
+    pub fn add_stream_map<S, F, M>(&self, stream: S, f: F) -> ContextJoinHandle
+    where
+        S: Stream + 'static,
+        F: FnMut(S::Item) -> M + 'static,
+        M: Message + 'static,
+        A: Handler<M>,
+    {
+        self.add_stream(MapStream::new(stream, f))
+    }
+
+    /// add a stream to context. multiple stream can be added to one context.
+    ///
+    /// stream item will be treated as exclusive message and `Handler::handle_wait` will be called.
+    #[inline]
+    pub fn add_wait_stream<S>(&self, stream: S) -> ContextJoinHandle
+    where
+        S: Stream + 'static,
+        S::Item: Message + 'static,
+        A: Handler<S::Item>,
+    {
+        self.stream(stream, |item| ActorMessage::new_mut_local(item, None))
+    }
+
+    /// add a sink to context and return the handle to write to it. Counterpart of
+    /// `Context::add_stream` for outbound IO.
+    ///
+    /// Writes are buffered in handle with capacity of 16 and sent to sink by actor's context.
+    /// `WriteHandler` hooks are called when sink returns error or is closed.
+    ///
+    /// *. Like streams the sink is not driven when actor is handling exclusive message.
+    ///
+    /// *. Only available with `futures-sink` feature.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    /// use actix_async::io::{SinkHandle, WriteHandler};
+    /// use futures_util::sink::drain;
+    ///
+    /// struct Writer {
+    ///     sink: SinkHandle<&'static str>,
+    /// }
+    /// actor!(Writer);
+    ///
+    /// impl WriteHandler<core::convert::Infallible> for Writer {}
+    ///
+    /// struct Write(&'static str);
+    /// message!(Write, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Write> for Writer {
+    ///     async fn handle(&self, msg: Write, _: Context<'_, Self>) {
+    ///         // wait for buffer to have space.
+    ///         let _ = self.sink.write(msg.0).await;
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = Writer::create(|ctx| Writer {
+    ///         sink: ctx.add_sink(drain()),
+    ///     });
+    ///
+    ///     addr.send(Write("hello")).await.unwrap();
+    /// }
+    /// ```
+    #[cfg(feature = "futures-sink")]
+    #[inline]
+    pub fn add_sink<S, I>(&self, sink: S) -> SinkHandle<I>
+    where
+        S: futures_sink::Sink<I> + 'static,
+        S::Error: 'static,
+        I: 'static,
+        A: WriteHandler<S::Error>,
+    {
+        self.add_sink_with_capacity(sink, 16)
+    }
+
+    /// add a sink to context with given buffer capacity. See `Context::add_sink`.
+    #[cfg(feature = "futures-sink")]
+    pub fn add_sink_with_capacity<S, I>(&self, sink: S, capacity: usize) -> SinkHandle<I>
+    where
+        S: futures_sink::Sink<I> + 'static,
+        S::Error: 'static,
+        I: 'static,
+        A: WriteHandler<S::Error>,
+    {
+        let (driver, handle) = SinkDriver::new(sink, capacity);
+        let msg = StreamMessage::new_boxed(driver);
+        self.inner.stream_cache.borrow_mut().push(msg);
+        handle
+    }
+
+    /// add a transport with codec to context. Counterpart of actix's framed actor.
+    ///
+    /// Transport is split into read and write half. Frames decoded from read half are treated as
+    /// concurrent `Frame` messages like `Context::add_stream`. Frames written to the returned
+    /// handle are encoded to write half like `Context::add_sink`.
+    ///
+    /// *. Only available with `framed` feature. See `framed::serve_tcp` for example.
+    #[cfg(feature = "framed")]
+    pub fn add_framed<T, C, I>(&self, io: T, codec: C) -> SinkHandle<I>
+    where
+        T: tokio::io::AsyncRead + tokio::io::AsyncWrite + 'static,
+        C: tokio_util::codec::Decoder + tokio_util::codec::Encoder<I> + Clone + 'static,
+        I: 'static,
+        A: Handler<Frame<C::Item, <C as tokio_util::codec::Decoder>::Error>>
+            + WriteHandler<<C as tokio_util::codec::Encoder<I>>::Error>,
+    {
+        let (read, write) = tokio::io::split(io);
+        let read = tokio_util::codec::FramedRead::new(read, codec.clone());
+        self.add_stream(FrameStream::new(read));
+        self.add_sink(tokio_util::codec::FramedWrite::new(write, codec))
+    }
+
+    /// add a blocking receiver to context. items received are treated as concurrent messages
+    /// like `Context::add_stream`.
+    ///
+    /// The receiver is polled on a thread spawned with `RuntimeService::spawn_blocking` and items
+    /// are forwarded to actor through a bounded channel with the capacity of
+    /// `Actor::size_hint`. The blocking thread waits when the channel is full.
+    ///
+    /// *. The blocking thread exits when the receiver returns `None` or when the actor is
+    /// stopped(or the returned handle is canceled) and the next item is received. A receiver
+    /// that never returns would keep the thread forever. (tokio runtime waits for it on shutdown)
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::sync::mpsc;
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct ChannelActor;
+    /// actor!(ChannelActor);
+    ///
+    /// struct ChannelMessage(usize);
+    /// message!(ChannelMessage, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<ChannelMessage> for ChannelActor {
+    ///     async fn handle(&self, msg: ChannelMessage, _: Context<'_, Self>) {
+    ///         println!("received {}", msg.0);
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let (tx, rx) = mpsc::channel();
+    ///
+    ///     let _addr = ChannelActor::create(|ctx| {
+    ///         ctx.add_channel(rx);
+    ///         ChannelActor
+    ///     });
+    ///
+    ///     // legacy producer on a std thread.
+    ///     std::thread::spawn(move || {
+    ///         for i in 0..8 {
+    ///             tx.send(ChannelMessage(i)).unwrap();
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn add_channel<R>(&self, rx: R) -> ContextJoinHandle
+    where
+        R: BlockingReceiver,
+        R::Item: Message + Send,
+        A: Handler<R::Item>,
+    {
+        let (tx, stream) = ChannelBuilder::new(A::size_hint()).build();
+
+        <A::Runtime as RuntimeService>::spawn_blocking(move || {
+            let mut rx = rx;
+            while let Some(item) = rx.recv() {
+                // channel is closed when actor is stopped or stream is canceled.
+                if block_on(tx.send(item)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        self.add_stream(stream)
+    }
+
+    /// run a blocking closure with `RuntimeService::spawn_blocking` and send it's output to
+    /// actor as a concurrent message. `Handler::handle` will be called.
+    ///
+    /// Blocking calls made in handler stall all tasks of actor. Offload them with this method
+    /// and the actor keeps handling other messages until the output is delivered.
+    ///
+    /// *. The output is dropped when the returned handle is canceled or actor is stopped before
+    /// the closure returns. The closure itself can not be interrupted.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct TestActor;
+    /// actor!(TestActor);
+    ///
+    /// struct FileRead(std::io::Result<String>);
+    /// message!(FileRead, ());
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<FileRead> for TestActor {
+    ///     async fn handle(&self, msg: FileRead, _: Context<'_, Self>) {
+    ///         println!("read file: {:?}", msg.0);
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let addr = TestActor::create(|ctx| {
+    ///         ctx.run_blocking(|| FileRead(std::fs::read_to_string("Cargo.toml")));
+    ///         TestActor
+    ///     });
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn run_blocking<F, M>(&self, f: F) -> ContextJoinHandle
+    where
+        F: FnOnce() -> M + Send + 'static,
+        M: Message + Send,
+        A: Handler<M>,
+    {
+        let (tx, rx) = ChannelBuilder::new(1).build();
+
+        <A::Runtime as RuntimeService>::spawn_blocking(move || {
+            // channel is closed when actor is stopped or the task is canceled.
+            let _ = block_on(tx.send(f()));
+        });
+
+        let (handle, task) = join_handle();
+        let stream = StreamContainer::new(rx, task, |item| ActorMessage::new_ref(item, None));
+        let msg = StreamMessage::new_boxed(stream);
+        self.inner.stream_cache.borrow_mut().push(msg);
+        handle
+    }
+
+    fn stream<S, F>(&self, stream: S, f: F) -> ContextJoinHandle
+    where
+        S: Stream + 'static,
+        S::Item: Message + 'static,
+        A: Handler<S::Item>,
+        F: FnOnce(S::Item) -> ActorMessage<A> + Copy + 'static,
+    {
+        let (handle, task) = join_handle();
+        let cancel = task.cancel_handle();
+        let stream = StreamContainer::new(stream, task, f);
+        let msg = StreamMessage::new_stream(stream, cancel);
         self.inner.stream_cache.borrow_mut().push(msg);
-        ContextJoinHandle { handle }
+        handle
     }
 }
@@ -1,21 +1,33 @@
 use core::{
     cell::{Cell, RefCell},
     future::Future,
-    pin::Pin,
-    task::{Context as StdContext, Poll},
+    pin::{pin, Pin},
+    task::{Context as StdContext, Poll, Waker},
 };
 
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
 use pin_project_lite::pin_project;
 use slab::Slab;
 
-use super::actor::{Actor, ActorState};
+use super::actor::{Actor, ActorState, StopReason};
+#[cfg(feature = "std")]
+use super::actor::{PanicAction, PanicInfo};
+use super::address::ChildAddr;
 use super::context::Context;
+use super::error::StartError;
 use super::handler::MessageHandler;
-use super::message::{ActorMessage, FutureMessage, StreamMessage};
+use super::lifecycle::Lifecycle;
+use super::message::{
+    mailbox_full_task, ActorMessage, FutureMessage, IdleTimer, Order, RateLimiter, SenderOrder,
+    StreamMessage,
+};
+#[cfg(feature = "std")]
+use super::observer::MailboxObserver;
+#[cfg(feature = "std")]
+use super::util::futures::CatchUnwind;
 use super::util::{
-    channel::Receiver,
-    futures::{poll_fn, yield_now, LocalBoxFuture, Stream},
+    channel::{LocalReceiver, OneshotSender, Receiver},
+    futures::{poll_fn, ready, yield_now, LocalBoxFuture, Stream},
     smart_pointer::RefCounter,
 };
 use super::waker::{ActorWaker, WakeQueue};
@@ -24,25 +36,312 @@ pub(crate) struct ContextInner<A: Actor> {
     pub(crate) state: Cell<ActorState>,
     pub(crate) future_cache: RefCell<Vec<FutureMessage<A>>>,
     pub(crate) stream_cache: RefCell<Vec<StreamMessage<A>>>,
+    wait_cache: RefCell<VecDeque<Box<dyn MessageHandler<A> + Send>>>,
+    // waker of actor task. woken when new task is added to wait_cache.
+    wait_waker: RefCell<Option<Waker>>,
     pub(crate) rx: RefCell<Receiver<ActorMessage<A>>>,
+    pub(crate) lifecycle: RefCounter<Lifecycle>,
+    children: RefCell<Vec<Box<dyn ChildAddr>>>,
+    stop_reason: RefCell<Option<StopReason>>,
+    paused: Cell<bool>,
+    resume_waker: RefCell<Option<Waker>>,
+    rate_limiter: RefCell<Option<RateLimiter<A>>>,
+    sender_order: RefCell<Option<SenderOrder>>,
+    local: Option<LocalReceiver<ActorMessage<A>>>,
+    #[cfg(feature = "std")]
+    observer: Option<RefCounter<dyn MailboxObserver>>,
 }
 
 impl<A: Actor> ContextInner<A> {
-    pub(crate) fn new(rx: Receiver<ActorMessage<A>>) -> Self {
+    pub(crate) fn new(rx: Receiver<ActorMessage<A>>, lifecycle: RefCounter<Lifecycle>) -> Self {
+        lifecycle.start();
         Self {
             state: Cell::new(ActorState::Stop),
             future_cache: RefCell::new(Vec::with_capacity(8)),
             stream_cache: RefCell::new(Vec::with_capacity(8)),
+            wait_cache: RefCell::new(VecDeque::new()),
+            wait_waker: RefCell::new(None),
             rx: RefCell::new(rx),
+            lifecycle,
+            children: RefCell::new(Vec::new()),
+            stop_reason: RefCell::new(None),
+            paused: Cell::new(false),
+            resume_waker: RefCell::new(None),
+            rate_limiter: RefCell::new(None),
+            sender_order: RefCell::new(None),
+            local: None,
+            #[cfg(feature = "std")]
+            observer: None,
+        }
+    }
+
+    /// limit the rate of messages taken from actor's channel.
+    pub(crate) fn rate_limit(mut self, limiter: Option<RateLimiter<A>>) -> Self {
+        self.rate_limiter = RefCell::new(limiter);
+        self
+    }
+
+    /// receive messages from local channel together with actor's channel.
+    pub(crate) fn local(mut self, rx: LocalReceiver<ActorMessage<A>>) -> Self {
+        self.local = Some(rx);
+        self
+    }
+
+    /// count of messages in actor's channel and local channel.
+    pub(crate) fn mailbox_len(&self) -> usize {
+        let len = self.rx.borrow().len();
+        match self.local.as_ref() {
+            Some(local) => len + local.len(),
+            None => len,
+        }
+    }
+
+    /// complete concurrent messages from the same `Addr` in the order they are received.
+    pub(crate) fn ordered(mut self, ordered: bool) -> Self {
+        if ordered {
+            self.sender_order = RefCell::new(Some(SenderOrder::new(A::size_hint())));
+        }
+        self
+    }
+
+    /// install observer of actor's mailbox. See `ActorBuilder::observer`.
+    #[cfg(feature = "std")]
+    pub(crate) fn observer(mut self, observer: Option<RefCounter<dyn MailboxObserver>>) -> Self {
+        self.observer = observer;
+        self
+    }
+
+    // notify observer a message is taken from actor's mailbox.
+    fn observe_dequeue(&self, msg: &ActorMessage<A>) {
+        #[cfg(feature = "std")]
+        if let (Some(observer), Some(_envelope)) = (self.observer.as_ref(), msg.envelope()) {
+            #[cfg(feature = "envelope")]
+            let waited = _envelope.elapsed();
+            #[cfg(not(feature = "envelope"))]
+            let waited = core::time::Duration::ZERO;
+            observer.on_dequeue(msg.type_name(), waited);
+        }
+
+        #[cfg(not(feature = "std"))]
+        let _ = msg;
+    }
+
+    // wrap the handling task of a message taken from actor's mailbox with observer hooks.
+    fn observe_handle<'a>(
+        &'a self,
+        type_name: &'static str,
+        task: LocalBoxFuture<'a, ()>,
+    ) -> LocalBoxFuture<'a, ()> {
+        #[cfg(feature = "std")]
+        if let Some(observer) = self.observer.as_ref() {
+            return Box::pin(async move {
+                observer.on_handle_start(type_name);
+                let now = std::time::Instant::now();
+                task.await;
+                observer.on_handle_end(type_name, now.elapsed());
+            });
+        }
+
+        #[cfg(not(feature = "std"))]
+        let _ = type_name;
+
+        task
+    }
+
+    // order of concurrent message from given sender. None when actor is not ordered or message
+    // is not sent from an `Addr`.
+    fn order(&self, sender: usize) -> Option<Order> {
+        match self.sender_order.borrow_mut().as_mut() {
+            Some(order) if sender != 0 => Some(order.next(sender)),
+            _ => None,
+        }
+    }
+
+    /// shrink future and stream cache to their initial capacity.
+    pub(crate) fn shrink_cache(&self) {
+        fn shrink<T>(cache: &mut Vec<T>) {
+            let cap = cache.len().max(8);
+            if cache.capacity() > cap {
+                let mut new = Vec::with_capacity(cap);
+                new.append(cache);
+                *cache = new;
+            }
+        }
+
+        shrink(&mut self.future_cache.borrow_mut());
+        shrink(&mut self.stream_cache.borrow_mut());
+    }
+
+    /// add an exclusive task that run before any new message is taken from actor's channel.
+    pub(crate) fn add_wait(&self, msg: Box<dyn MessageHandler<A> + Send>) {
+        self.wait_cache.borrow_mut().push_back(msg);
+        // task can be added from spawned future or stream and actor must be woken to start it.
+        if let Some(waker) = self.wait_waker.borrow().as_ref() {
+            waker.wake_by_ref();
+        }
+    }
+
+    /// run future until it's resolved or actor is forced to stop. See `Addr::stop_with_timeout`.
+    async fn or_force_stop<F: Future>(&self, fut: F) -> Option<F::Output> {
+        let mut fut = pin!(fut);
+        let res = poll_fn(|cx| {
+            if let Poll::Ready(res) = fut.as_mut().poll(cx) {
+                return Poll::Ready(Some(res));
+            }
+            self.lifecycle.poll_force_stop(cx).map(|_| None)
+        })
+        .await;
+
+        if res.is_none() {
+            self.set_stop_reason(StopReason::Forced);
+            self.state.set(ActorState::Stop);
+        }
+
+        res
+    }
+
+    fn has_wait(&self) -> bool {
+        !self.wait_cache.borrow().is_empty()
+    }
+
+    fn register_wait_waker(&self, waker: &Waker) {
+        let mut wait_waker = self.wait_waker.borrow_mut();
+        match *wait_waker {
+            Some(ref w) if w.will_wake(waker) => {}
+            _ => *wait_waker = Some(waker.clone()),
         }
     }
 
+    /// stop receiving message from actor's channel.
+    pub(crate) fn pause(&self) {
+        self.paused.set(true);
+    }
+
+    /// resume receiving message and wake up the actor if it's waiting for the channel.
+    pub(crate) fn resume(&self) {
+        self.paused.set(false);
+        if let Some(waker) = self.resume_waker.borrow_mut().take() {
+            waker.wake();
+        }
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused.get()
+    }
+
+    // poll actor's channel for new message. only control messages are received when accept is
+    // false, context is paused or rate limited.
+    fn poll_mailbox(&self, cx: &mut StdContext<'_>, accept: bool) -> Poll<Option<ActorMessage<A>>> {
+        // control messages are never blocked.
+        if let Poll::Ready(msg) = self.rx.borrow_mut().poll_control(cx) {
+            return Poll::Ready(Some(msg));
+        }
+
+        // futures from Context::wait run before any new message.
+        if !accept || self.has_wait() {
+            return Poll::Pending;
+        }
+
+        if self.paused.get() {
+            *self.resume_waker.borrow_mut() = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        let mut limiter = self.rate_limiter.borrow_mut();
+
+        if let Some(limiter) = limiter.as_mut() {
+            ready!(limiter.poll_ready(cx));
+        }
+
+        let res = match self.local.as_ref() {
+            // actor's channel is checked first without waiting so control messages are not
+            // delayed by local messages.
+            Some(local) => {
+                let msg = self.rx.borrow().try_recv();
+                match msg {
+                    Ok(msg) => Poll::Ready(Some(msg)),
+                    Err(_) => match local.poll_recv(cx) {
+                        Poll::Ready(msg) => Poll::Ready(Some(msg)),
+                        Poll::Pending => Pin::new(&mut *self.rx.borrow_mut()).poll_next(cx),
+                    },
+                }
+            }
+            None => Pin::new(&mut *self.rx.borrow_mut()).poll_next(cx),
+        };
+
+        // control messages arrived after the check above are received here without token.
+        if let (Poll::Ready(Some(msg)), Some(limiter)) = (&res, limiter.as_mut()) {
+            if !matches!(msg, ActorMessage::State(..)) {
+                limiter.acquire();
+            }
+        }
+
+        res
+    }
+
+    /// add child actor. stopped children are removed.
+    pub(crate) fn add_child(&self, child: Box<dyn ChildAddr>) {
+        let mut children = self.children.borrow_mut();
+        children.retain(|child| !child.is_stopped());
+        children.push(child);
+    }
+
+    /// set the reason actor is stopping. only the first reason is kept.
+    pub(crate) fn set_stop_reason(&self, reason: StopReason) {
+        self.stop_reason.borrow_mut().get_or_insert(reason);
+    }
+
+    /// close actor's channel and drop all pending messages in it.
+    pub(crate) fn close_mailbox(&self) {
+        let rx = self.rx.borrow_mut();
+        rx.close();
+        while rx.try_recv().is_ok() {}
+
+        if let Some(local) = self.local.as_ref() {
+            local.close();
+            while local.try_recv().is_some() {}
+        }
+    }
+
+    /// the reason actor is stopping. fallback to actor state when no reason is set.
+    pub(crate) fn stop_reason(&self) -> StopReason {
+        self.stop_reason
+            .borrow()
+            .clone()
+            .unwrap_or_else(|| match self.state.get() {
+                ActorState::StopGraceful => StopReason::Graceful,
+                _ => StopReason::Forced,
+            })
+    }
+
     #[inline]
     pub(crate) fn as_ref(&self) -> Context<'_, A> {
         Context::new(self)
     }
 }
 
+impl<A: Actor> Drop for ContextInner<A> {
+    fn drop(&mut self) {
+        #[cfg(feature = "std")]
+        {
+            // context is dropped when unwinding from an untrapped panic.
+            if std::thread::panicking() {
+                self.set_stop_reason(StopReason::Panic);
+            }
+        }
+
+        // children are stopped with their parent.
+        for child in self.children.get_mut().drain(..) {
+            child.stop();
+        }
+
+        self.lifecycle.stop(self.stop_reason());
+    }
+}
+
+/// The future driving an actor's message handling.
+///
+/// Obtained from `Actor::create_context` and can be driven by `ContextFuture::run`.
 pub struct ContextFuture<A: Actor> {
     act: A,
     ctx: ContextInner<A>,
@@ -63,6 +362,27 @@ impl<A: Actor> ContextFuture<A> {
             queue: WakeQueue::new(),
         }
     }
+
+    /// construct actor with fallible async closure. context is dropped and actor's channel
+    /// is closed when closure failed.
+    pub(crate) async fn try_start<F, Fut, E>(f: F, ctx: ContextInner<A>) -> Result<Self, E>
+    where
+        F: for<'c> FnOnce(Context<'c, A>) -> Fut + 'static,
+        Fut: Future<Output = Result<A, E>>,
+    {
+        match f(ctx.as_ref()).await {
+            Ok(act) => Ok(Self {
+                act,
+                ctx,
+                queue: WakeQueue::new(),
+            }),
+            Err(e) => {
+                ctx.set_stop_reason(StopReason::StartFailed);
+                ctx.close_mailbox();
+                Err(e)
+            }
+        }
+    }
 }
 
 async fn poll_stream<A: Actor>(stream_cache: &RefCell<Vec<StreamMessage<A>>>) -> ActorMessage<A> {
@@ -84,7 +404,10 @@ async fn poll_stream<A: Actor>(stream_cache: &RefCell<Vec<StreamMessage<A>>>) ->
     .await
 }
 
-async fn poll_future<A: Actor>(future_cache: &RefCell<Vec<FutureMessage<A>>>) -> ActorMessage<A> {
+async fn poll_future<A: Actor>(
+    future_cache: &RefCell<Vec<FutureMessage<A>>>,
+    mut idle: Option<&mut IdleTimer<A>>,
+) -> ActorMessage<A> {
     poll_fn(|cx| {
         let mut cache = future_cache.borrow_mut();
         let mut i = 0;
@@ -94,6 +417,9 @@ async fn poll_future<A: Actor>(future_cache: &RefCell<Vec<FutureMessage<A>>>) ->
                     cache.swap_remove(i);
 
                     if let Some(msg) = msg {
+                        if let Some(idle) = idle.as_mut() {
+                            idle.active();
+                        }
                         return Poll::Ready(msg);
                     }
 
@@ -103,18 +429,21 @@ async fn poll_future<A: Actor>(future_cache: &RefCell<Vec<FutureMessage<A>>>) ->
             }
         }
 
-        Poll::Pending
+        match idle.as_mut() {
+            Some(idle) => idle.poll_idle(cx),
+            None => Poll::Pending,
+        }
     })
     .await
 }
 
-struct TaskRef<'a> {
+pub(crate) struct TaskRef<'a> {
     task: Slab<(LocalBoxFuture<'a, ()>, Option<RefCounter<ActorWaker>>)>,
     queue: &'a WakeQueue,
 }
 
 impl<'a> TaskRef<'a> {
-    fn new<A: Actor>(queue: &'a WakeQueue) -> Self {
+    pub(crate) fn new<A: Actor>(queue: &'a WakeQueue) -> Self {
         Self {
             task: Slab::with_capacity(A::size_hint()),
             queue,
@@ -129,7 +458,7 @@ impl<'a> TaskRef<'a> {
         self.task.len()
     }
 
-    fn add_task(&mut self, task: LocalBoxFuture<'a, ()>) {
+    pub(crate) fn add_task(&mut self, task: LocalBoxFuture<'a, ()>) {
         let idx = self.task.insert((task, None));
         self.queue.enqueue(idx);
     }
@@ -180,7 +509,27 @@ impl<'a> TaskRef<'a> {
         .await
     }
 
-    async fn graceful_resolve(&mut self) {
+    /// shrink task slab and wake queue back to actor's size hint when there is no task.
+    pub(crate) fn shrink<A: Actor>(&mut self) {
+        if !self.task.is_empty() {
+            return;
+        }
+
+        if self.task.capacity() > A::size_hint() {
+            self.task = Slab::with_capacity(A::size_hint());
+        }
+
+        // wakers of resolved tasks can still enqueue. lock is only held shortly by them.
+        self.queue.lock().shrink_to(A::size_hint());
+    }
+
+    // capacity of task slab and wake queue.
+    #[cfg(test)]
+    pub(crate) fn capacity(&self) -> (usize, usize) {
+        (self.task.capacity(), self.queue.lock().capacity())
+    }
+
+    pub(crate) async fn graceful_resolve(&mut self) {
         while !self.is_empty() {
             self.poll_task().await;
             yield_now().await;
@@ -188,7 +537,8 @@ impl<'a> TaskRef<'a> {
     }
 }
 
-struct TaskMut<A: Actor>(Option<Box<dyn MessageHandler<A> + Send>>);
+// exclusive task and if it's a message taken from actor's mailbox.
+struct TaskMut<A: Actor>(Option<(Box<dyn MessageHandler<A> + Send>, bool)>);
 
 impl<A: Actor> TaskMut<A> {
     fn new() -> Self {
@@ -196,14 +546,18 @@ impl<A: Actor> TaskMut<A> {
     }
 
     fn add_task(&mut self, msg: Box<dyn MessageHandler<A> + Send>) {
-        self.0 = Some(msg);
+        self.0 = Some((msg, false));
+    }
+
+    fn add_mailbox_task(&mut self, msg: Box<dyn MessageHandler<A> + Send>) {
+        self.0 = Some((msg, true));
     }
 
     fn is_empty(&self) -> bool {
         self.0.is_none()
     }
 
-    fn take(&mut self) -> Option<Box<dyn MessageHandler<A> + Send>> {
+    fn take(&mut self) -> Option<(Box<dyn MessageHandler<A> + Send>, bool)> {
         self.0.take()
     }
 }
@@ -220,7 +574,12 @@ impl<A: Actor> ContextFuture<A> {
     /// Run context future on current thread.
     ///
     /// It would block current async task.
-    pub async fn run(mut self) {
+    pub async fn run(self) {
+        self.run_with(None).await
+    }
+
+    /// run context future and notify the result of `Actor::on_start_fallible` with given sender.
+    pub(crate) async fn run_with(mut self, started: Option<OneshotSender<Result<(), StartError>>>) {
         let ContextFuture {
             ctx,
             queue,
@@ -228,39 +587,135 @@ impl<A: Actor> ContextFuture<A> {
             ..
         } = self;
 
-        act.on_start(ctx.as_ref()).await;
+        let res = act.on_start_fallible(ctx.as_ref()).await;
+        let failed = res.is_err();
+
+        if let Some(tx) = started {
+            let _ = tx.send(res);
+        }
+
+        if failed {
+            ctx.set_stop_reason(StopReason::StartFailed);
+            ctx.close_mailbox();
+            return;
+        }
+
         ctx.state.set(ActorState::Running);
+        ctx.lifecycle.started();
 
         let mut notify = None;
 
         let task_mut = &mut TaskMut::new();
 
+        let panics = &PanicQueue::new();
+
+        let idle = &mut A::idle_timeout().map(IdleTimer::<A>::new);
+
+        let mut idle_polls = 0;
+
+        // `Actor::on_mailbox_full` is called and the mailbox has not dropped below threshold.
+        let mut mailbox_full = false;
+
+        // graceful stop message is received from control queue. count of messages queued before
+        // it that are still to be handled.
+        let mut graceful = None;
+
         'task: loop {
+            #[cfg(feature = "std")]
+            {
+                if let Some(info) = panics.pop() {
+                    let Some(action) = ctx.or_force_stop(act.on_panic(&info, ctx.as_ref())).await
+                    else {
+                        break 'task;
+                    };
+                    match action {
+                        PanicAction::Continue => {}
+                        PanicAction::Stop => {
+                            ctx.set_stop_reason(StopReason::Panic);
+                            ctx.as_ref().stop();
+                        }
+                        PanicAction::Restart => {
+                            ctx.set_stop_reason(StopReason::Restart);
+                            ctx.lifecycle.stopping();
+                            act.on_stop(StopReason::Restart, ctx.as_ref()).await;
+                            std::panic::resume_unwind(info.into_payload())
+                        }
+                    }
+                    continue 'task;
+                }
+            }
+
             match task_mut.take() {
-                Some(mut msg) => msg.handle_wait(act, ctx.as_ref()).await,
+                Some((mut msg, mailbox)) => {
+                    ctx.lifecycle.set_in_flight(1);
+                    let type_name = msg.type_name();
+                    let mut task = msg.handle_wait(act, ctx.as_ref());
+                    if mailbox {
+                        task = ctx.observe_handle(type_name, task);
+                    }
+                    if ctx.or_force_stop(panics.trap::<A>(task)).await.is_none() {
+                        break 'task;
+                    }
+                }
                 None => {
                     let task_ref = &mut TaskRef::new::<A>(&queue);
 
                     loop {
                         match ctx.state.get() {
                             ActorState::StopGraceful => {
-                                task_ref.graceful_resolve().await;
+                                ctx.or_force_stop(task_ref.graceful_resolve()).await;
                                 break 'task;
                             }
                             ActorState::Stop => break 'task,
-                            ActorState::Running if !task_mut.is_empty() && task_ref.is_empty() => {
+                            ActorState::Running
+                                if (!task_mut.is_empty() || !panics.is_empty())
+                                    && task_ref.is_empty() =>
+                            {
                                 continue 'task
                             }
                             _ => {}
                         }
 
-                        let fut1 = if task_mut.is_empty() && task_ref.len() < A::size_hint() {
-                            Some(poll_fn(|cx| {
-                                Pin::new(&mut *ctx.rx.borrow_mut()).poll_next(cx)
-                            }))
-                        } else {
-                            None
-                        };
+                        if let Some(polls) = A::shrink_policy() {
+                            if task_ref.is_empty() {
+                                idle_polls += 1;
+                                if idle_polls >= polls {
+                                    idle_polls = 0;
+                                    task_ref.shrink::<A>();
+                                    ctx.shrink_cache();
+                                }
+                            } else {
+                                idle_polls = 0;
+                            }
+                        }
+
+                        if let Some(threshold) = A::mailbox_full_threshold() {
+                            if ctx.mailbox_len() < threshold {
+                                mailbox_full = false;
+                            } else if !mailbox_full && task_mut.is_empty() {
+                                mailbox_full = true;
+                                task_mut.add_task(mailbox_full_task::<A>());
+                                continue;
+                            }
+                        }
+
+                        // futures from Context::wait run in order as exclusive tasks.
+                        if task_mut.is_empty() {
+                            if let Some(msg) = ctx.wait_cache.borrow_mut().pop_front() {
+                                task_mut.add_task(msg);
+                                continue;
+                            }
+                        }
+
+                        // stop accepting new message when there is pending exclusive task or
+                        // trapped panic.
+                        let blocked = !task_mut.is_empty() || !panics.is_empty();
+
+                        ctx.lifecycle
+                            .set_in_flight(task_ref.len() + !task_mut.is_empty() as usize);
+
+                        let accept = !blocked && task_ref.len() < A::size_hint();
+                        let fut1 = Some(poll_fn(|cx| ctx.poll_mailbox(cx, accept)));
 
                         let fut2 = if !task_ref.is_empty() {
                             Some(task_ref.poll_task())
@@ -268,38 +723,110 @@ impl<A: Actor> ContextFuture<A> {
                             None
                         };
 
-                        let (fut3, fut4) = if task_mut.is_empty() {
+                        let (fut3, fut4) = if !blocked {
                             (
                                 Some(poll_stream(&ctx.stream_cache)),
-                                Some(poll_future(&ctx.future_cache)),
+                                Some(poll_future(&ctx.future_cache, idle.as_mut())),
                             )
                         } else {
                             (None, None)
                         };
 
-                        let select = ConditionSelect4 {
-                            fut1,
-                            fut2,
-                            fut3,
-                            fut4,
+                        // break select when future from Context::wait is added by the tasks
+                        // polled in it.
+                        let output = {
+                            let mut select = pin!(ConditionSelect4 {
+                                fut1,
+                                fut2,
+                                fut3,
+                                fut4,
+                            });
+
+                            ctx.or_force_stop(poll_fn(|cx| {
+                                if task_mut.is_empty() && ctx.has_wait() {
+                                    return Poll::Ready(None);
+                                }
+                                ctx.register_wait_waker(cx.waker());
+                                select.as_mut().poll(cx).map(Some)
+                            }))
+                            .await
+                        };
+
+                        let output = match output {
+                            Some(Some(output)) => output,
+                            Some(None) => continue,
+                            None => break 'task,
+                        };
+
+                        // message is taken from actor's mailbox.
+                        let mut mailbox = false;
+
+                        let msg = match output {
+                            ConditionSelect4Output::B(_) => None,
+                            ConditionSelect4Output::A(None) => {
+                                ctx.set_stop_reason(StopReason::AddrDropped);
+                                ctx.state.set(ActorState::Stop);
+                                continue;
+                            }
+                            ConditionSelect4Output::A(Some(msg)) => {
+                                if let Some(idle) = idle.as_mut() {
+                                    idle.active();
+                                }
+
+                                if let Some((queued, _)) = graceful.as_mut() {
+                                    if !matches!(msg, ActorMessage::State(..)) {
+                                        *queued -= 1;
+                                        if *queued == 0 {
+                                            let (_, tx) = graceful.take().unwrap();
+                                            ctx.state.set(ActorState::StopGraceful);
+                                            notify = Some(tx);
+                                        }
+                                    }
+                                }
+
+                                ctx.observe_dequeue(&msg);
+                                mailbox = true;
+
+                                Some(msg)
+                            }
+                            ConditionSelect4Output::C(msg) => {
+                                if let Some(idle) = idle.as_mut() {
+                                    idle.active();
+                                }
+                                Some(msg)
+                            }
+                            ConditionSelect4Output::D(msg) => Some(msg),
                         };
 
-                        match select.await {
-                            ConditionSelect4Output::B(_) => yield_now().await,
-                            ConditionSelect4Output::A(Some(msg))
-                            | ConditionSelect4Output::C(msg)
-                            | ConditionSelect4Output::D(msg) => match msg {
-                                ActorMessage::Ref(mut msg) => {
-                                    let task = msg.handle(act, ctx.as_ref());
-                                    task_ref.add_task(task);
+                        match msg {
+                            Some(ActorMessage::Ref(mut msg)) => {
+                                let type_name = msg.type_name();
+                                let mut task = match ctx.order(msg.envelope().sender) {
+                                    Some(order) => msg.handle_ordered(act, ctx.as_ref(), order),
+                                    None => msg.handle(act, ctx.as_ref()),
+                                };
+                                if mailbox {
+                                    task = ctx.observe_handle(type_name, task);
                                 }
-                                ActorMessage::Mut(msg) => task_mut.add_task(msg),
-                                ActorMessage::State(state, tx) => {
+                                task_ref.add_task(panics.trap::<A>(task));
+                            }
+                            Some(ActorMessage::Mut(msg)) if mailbox => {
+                                task_mut.add_mailbox_task(msg)
+                            }
+                            Some(ActorMessage::Mut(msg)) => task_mut.add_task(msg),
+                            Some(ActorMessage::State(state, tx)) => {
+                                let queued = ctx.mailbox_len();
+                                if state == ActorState::StopGraceful && queued > 0 {
+                                    // handle messages queued before stop message. paused
+                                    // context is resumed to drain them.
+                                    ctx.resume();
+                                    graceful = Some((queued, tx));
+                                } else {
                                     ctx.state.set(state);
                                     notify = Some(tx);
                                 }
-                            },
-                            ConditionSelect4Output::A(None) => ctx.state.set(ActorState::Stop),
+                            }
+                            None => yield_now().await,
                         }
                     }
                 }
@@ -307,12 +834,18 @@ impl<A: Actor> ContextFuture<A> {
         }
 
         if ctx.state.get() == ActorState::StopGraceful {
-            if let Some(mut msg) = task_mut.take() {
-                msg.handle_wait(act, ctx.as_ref()).await
+            if let Some((mut msg, mailbox)) = task_mut.take() {
+                let type_name = msg.type_name();
+                let mut task = msg.handle_wait(act, ctx.as_ref());
+                if mailbox {
+                    task = ctx.observe_handle(type_name, task);
+                }
+                ctx.or_force_stop(panics.trap::<A>(task)).await;
             }
         }
 
-        act.on_stop(ctx.as_ref()).await;
+        ctx.lifecycle.stopping();
+        act.on_stop(ctx.stop_reason(), ctx.as_ref()).await;
 
         if let Some(notify) = notify {
             let _ = notify.send(());
@@ -320,6 +853,59 @@ impl<A: Actor> ContextFuture<A> {
     }
 }
 
+// panics trapped from actor tasks and waiting to be handled by Actor::on_panic.
+#[cfg(feature = "std")]
+struct PanicQueue(RefCell<Vec<PanicInfo>>);
+
+#[cfg(feature = "std")]
+impl PanicQueue {
+    fn new() -> Self {
+        Self(RefCell::new(Vec::new()))
+    }
+
+    // wrap task with catch_unwind when Actor::trap_panic is enabled. panicked task is dropped
+    // and it's panic is queued.
+    fn trap<'a, A: Actor>(&'a self, task: LocalBoxFuture<'a, ()>) -> LocalBoxFuture<'a, ()> {
+        if A::trap_panic() {
+            Box::pin(async move {
+                if let Err(e) = CatchUnwind::new(task).await {
+                    self.0.borrow_mut().push(PanicInfo::new(e));
+                }
+            })
+        } else {
+            task
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    fn pop(&self) -> Option<PanicInfo> {
+        self.0.borrow_mut().pop()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+struct PanicQueue;
+
+#[cfg(not(feature = "std"))]
+impl PanicQueue {
+    fn new() -> Self {
+        Self
+    }
+
+    #[inline(always)]
+    fn trap<'a, A: Actor>(&'a self, task: LocalBoxFuture<'a, ()>) -> LocalBoxFuture<'a, ()> {
+        task
+    }
+
+    #[inline(always)]
+    fn is_empty(&self) -> bool {
+        true
+    }
+}
+
 pin_project! {
     struct ConditionSelect4<Fut1, Fut2, Fut3, Fut4> {
         #[pin]
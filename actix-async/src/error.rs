@@ -1,5 +1,7 @@
 use core::fmt::{Debug, Display, Formatter, Result as FmtResult};
 
+use alloc::boxed::Box;
+
 #[derive(PartialEq)]
 pub enum ActixAsyncError {
     /// actor's channel is closed. happens when actor is shutdown.
@@ -11,6 +13,17 @@ pub enum ActixAsyncError {
     /// failed to receive result from actor in time.
     ReceiveTimeout,
 
+    /// actor's channel is full. happens when sending with `MessageRequest::fail_fast`.
+    MailboxFull,
+
+    /// message is discarded by actor as it's not handled before the time to live set by
+    /// `MessageRequest::ttl`.
+    Expired,
+
+    /// actor aborted the handling of message as it's not finished within
+    /// `Actor::handler_timeout`.
+    HandlerTimeout,
+
     /// fail to receive result for given message. happens when actor is blocked or the
     /// thread it runs on panicked.
     Receiver,
@@ -42,6 +55,24 @@ impl Debug for ActixAsyncError {
                     &"MessageRequest is timed out. (Failed to receive result from actor in time.)",
                 )
                 .finish(),
+            ActixAsyncError::MailboxFull => fmt
+                .field("cause", &"MailboxFull")
+                .field("description", &"Actor's channel is full")
+                .finish(),
+            ActixAsyncError::Expired => fmt
+                .field("cause", &"Expired")
+                .field(
+                    "description",
+                    &"Message is expired before it's handled by actor",
+                )
+                .finish(),
+            ActixAsyncError::HandlerTimeout => fmt
+                .field("cause", &"HandlerTimeout")
+                .field(
+                    "description",
+                    &"Handling of message is aborted as it's not finished in time",
+                )
+                .finish(),
             ActixAsyncError::Receiver => fmt
                 .field("cause", &"Receive")
                 .field("description", &"Fail to receive result for given message.")
@@ -65,3 +96,106 @@ impl Display for ActixAsyncError {
 
 #[cfg(feature = "std")]
 impl std::error::Error for ActixAsyncError {}
+
+/// error returned by `Addr::try_send`. The message is given back to caller.
+pub enum TrySendError<M> {
+    /// actor's channel is full.
+    Full(M),
+
+    /// actor's channel is closed. happens when actor is shutdown.
+    Closed(M),
+}
+
+impl<M> TrySendError<M> {
+    /// take the message failed to send.
+    pub fn into_inner(self) -> M {
+        match self {
+            Self::Full(msg) | Self::Closed(msg) => msg,
+        }
+    }
+
+    /// return true when actor's channel is full.
+    pub fn is_full(&self) -> bool {
+        matches!(self, Self::Full(_))
+    }
+
+    /// return true when actor's channel is closed.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, Self::Closed(_))
+    }
+
+    pub(crate) fn map<F, T>(self, f: F) -> TrySendError<T>
+    where
+        F: FnOnce(M) -> T,
+    {
+        match self {
+            Self::Full(msg) => TrySendError::Full(f(msg)),
+            Self::Closed(msg) => TrySendError::Closed(f(msg)),
+        }
+    }
+}
+
+impl<M> From<TrySendError<M>> for ActixAsyncError {
+    fn from(e: TrySendError<M>) -> Self {
+        match e {
+            TrySendError::Full(_) => ActixAsyncError::MailboxFull,
+            TrySendError::Closed(_) => ActixAsyncError::Closed,
+        }
+    }
+}
+
+impl<M> Debug for TrySendError<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mut fmt = f.debug_struct("TrySendError");
+
+        match self {
+            TrySendError::Full(_) => fmt
+                .field("cause", &"Full")
+                .field("description", &"Actor's channel is full")
+                .finish(),
+            TrySendError::Closed(_) => fmt
+                .field("cause", &"Closed")
+                .field("description", &"Actor is already closed")
+                .finish(),
+        }
+    }
+}
+
+impl<M> Display for TrySendError<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M> std::error::Error for TrySendError<M> {}
+
+/// error returned by `Actor::on_start_fallible` to abort the startup of actor.
+pub struct StartError(Box<dyn Display + Send>);
+
+impl StartError {
+    /// construct a start error from any error type that can be displayed.
+    pub fn new<E>(err: E) -> Self
+    where
+        E: Display + Send + 'static,
+    {
+        Self(Box::new(err))
+    }
+}
+
+impl Debug for StartError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("StartError")
+            .field("cause", &format_args!("{}", self.0))
+            .finish()
+    }
+}
+
+impl Display for StartError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Actor failed to start: {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StartError {}
@@ -0,0 +1,180 @@
+//! Network protocol actor with `tokio_util::codec` transport. See `Context::add_framed`.
+
+use core::{
+    pin::Pin,
+    task::{Context as StdContext, Poll},
+};
+
+use std::{io, net::SocketAddr};
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::codec::{Decoder, FramedRead};
+
+use super::actor::Actor;
+use super::address::Addr;
+use super::context::Context;
+use super::message::Message;
+use super::util::futures::Stream;
+
+/// message of frames read from transport added with `Context::add_framed`.
+pub enum Frame<T, E> {
+    /// a decoded frame.
+    Item(T),
+    /// transport or codec returned error.
+    Error(E),
+    /// transport reached EOF. No frame would be received after it.
+    Closed,
+}
+
+impl<T: 'static, E: 'static> Message for Frame<T, E> {
+    type Result = ();
+}
+
+pin_project_lite::pin_project! {
+    // stream of frames with a final Frame::Closed.
+    pub(crate) struct FrameStream<T, C> {
+        #[pin]
+        framed: FramedRead<T, C>,
+        done: bool,
+    }
+}
+
+impl<T, C> FrameStream<T, C> {
+    pub(crate) fn new(framed: FramedRead<T, C>) -> Self {
+        Self {
+            framed,
+            done: false,
+        }
+    }
+}
+
+impl<T, C> Stream for FrameStream<T, C>
+where
+    T: tokio::io::AsyncRead,
+    C: Decoder,
+{
+    type Item = Frame<C::Item, C::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        match futures_core::ready!(this.framed.poll_next(cx)) {
+            Some(Ok(item)) => Poll::Ready(Some(Frame::Item(item))),
+            Some(Err(e)) => Poll::Ready(Some(Frame::Error(e))),
+            None => {
+                *this.done = true;
+                Poll::Ready(Some(Frame::Closed))
+            }
+        }
+    }
+}
+
+/// accept tcp connections from listener and start an actor for every connection.
+///
+/// Actors are kept alive until they are stopped. i.e by calling `Context::stop` when
+/// `Frame::Closed` is received. They are not stopped when this future returns or is dropped.
+///
+/// Errors of a single connection(aborted, reset, etc) are skipped. Return error when listener
+/// fails to accept connection for other reasons.
+///
+/// # example:
+/// ```rust,no_run
+/// use actix_async::prelude::*;
+/// use actix_async::framed::{serve_tcp, Frame};
+/// use actix_async::io::{SinkHandle, WriteHandler};
+/// use tokio::net::TcpListener;
+/// use tokio_util::codec::LinesCodec;
+///
+/// struct Session {
+///     sink: SinkHandle<String>,
+/// }
+/// actor!(Session);
+///
+/// impl WriteHandler<tokio_util::codec::LinesCodecError> for Session {}
+///
+/// #[actix_async::handler]
+/// impl Handler<Frame<String, tokio_util::codec::LinesCodecError>> for Session {
+///     async fn handle(&self, msg: Frame<String, tokio_util::codec::LinesCodecError>, ctx: Context<'_, Self>) {
+///         match msg {
+///             // echo the line back.
+///             Frame::Item(line) => {
+///                 let _ = self.sink.write(line).await;
+///             }
+///             Frame::Error(_) | Frame::Closed => ctx.stop(),
+///         }
+///     }
+/// }
+///
+/// #[actix_async::main]
+/// async fn main() -> std::io::Result<()> {
+///     let listener = TcpListener::bind("127.0.0.1:8080").await?;
+///     serve_tcp(listener, |stream, _, ctx: Context<'_, Session>| Session {
+///         sink: ctx.add_framed(stream, LinesCodec::new()),
+///     })
+///     .await
+/// }
+/// ```
+pub async fn serve_tcp<A, F>(listener: TcpListener, f: F) -> io::Result<()>
+where
+    A: Actor,
+    F: for<'c> FnOnce(TcpStream, SocketAddr, Context<'c, A>) -> A + Clone + 'static,
+{
+    loop {
+        let (stream, addr) = match listener.accept().await {
+            Ok(res) => res,
+            Err(ref e) if is_connection_error(e) => continue,
+            Err(e) => return Err(e),
+        };
+        let f = f.clone();
+        keep_alive(A::create(move |ctx| f(stream, addr, ctx)));
+    }
+}
+
+/// accept unix stream connections from listener and start an actor for every connection. See
+/// `serve_tcp` for detail.
+#[cfg(unix)]
+pub async fn serve_unix<A, F>(listener: tokio::net::UnixListener, f: F) -> io::Result<()>
+where
+    A: Actor,
+    F: for<'c> FnOnce(tokio::net::UnixStream, Context<'c, A>) -> A + Clone + 'static,
+{
+    loop {
+        let (stream, _) = match listener.accept().await {
+            Ok(res) => res,
+            Err(ref e) if is_connection_error(e) => continue,
+            Err(e) => return Err(e),
+        };
+        let f = f.clone();
+        keep_alive(A::create(move |ctx| f(stream, ctx)));
+    }
+}
+
+// error of a single connection. listener can keep accepting after it.
+fn is_connection_error(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::TimedOut
+    )
+}
+
+// session actor holds it's own address so it's only stopped by itself. The address is dropped
+// with context when actor is stopped.
+fn keep_alive<A: Actor>(addr: Addr<A>) {
+    let this = addr.clone();
+    addr.schedule(move |_, ctx| {
+        ctx.spawn(async move {
+            let _addr = this;
+            core::future::pending::<()>().await
+        });
+        Box::pin(async {})
+    });
+}
@@ -1,17 +1,29 @@
-use core::future::Future;
+use core::{any::Any, future::Future};
 
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec::Vec};
 
 use super::actor::Actor;
 use super::context::Context;
-use super::message::{FunctionMessage, FunctionMutMessage, Message, MessageContainer};
-use super::util::{channel::OneshotSender, futures::LocalBoxFuture};
+use super::message::{
+    BatchMessage, ConvertMessage, Envelope, FunctionMessage, FunctionMutMessage, InFlightMessage,
+    Message, MessageContainer, Order, StreamRequest,
+};
+use super::runtime::RuntimeService;
+use super::util::{
+    channel::OneshotSender,
+    futures::{LocalBoxFuture, Timeout},
+};
 
 /// Trait define how actor handle a message.
+///
+/// Handler methods are async functions returning unboxed futures. The future is stored in
+/// actor's task directly without extra allocation.
+///
 /// # example:
 /// ```rust:
+/// use std::future::Future;
+///
 /// use actix_async::prelude::*;
-/// use actix_async::handler;
 ///
 /// struct TestActor;
 /// actor!(TestActor);
@@ -22,26 +34,51 @@ use super::util::{channel::OneshotSender, futures::LocalBoxFuture};
 /// struct TestMessage2;
 /// message!(TestMessage2, ());
 ///
-/// // use async method directly with the help of handler macro.
-/// #[handler]
+/// struct TestMessage3;
+/// message!(TestMessage3, ());
+///
+/// // use async method directly.
 /// impl Handler<TestMessage> for TestActor {
-///    async fn handle(&self, _: TestMessage,ctx: Context<'_, Self>) {
+///     async fn handle(&self, _: TestMessage, ctx: Context<'_, Self>) {
 ///         let _this = self;
 ///         let _ctx = ctx;
 ///         println!("hello from TestMessage");
 ///     }
 /// }
 ///
-/// // impl boxed future manually without handler macro.
+/// // return future manually.
 /// impl Handler<TestMessage2> for TestActor {
-///     fn handle<'a: 'r,'c: 'r, 'r>(&'a self, _: TestMessage2, ctx: Context<'c, Self>) -> LocalBoxFuture<'r, ()> {
-///         Box::pin(async move {
+///     fn handle(&self, _: TestMessage2, ctx: Context<'_, Self>) -> impl Future<Output = ()> {
+///         async move {
 ///             let _this = self;
 ///             let _ctx = ctx;
 ///             println!("hello from TestMessage2");
+///         }
+///     }
+/// }
+///
+/// // boxed future is still accepted.
+/// impl Handler<TestMessage3> for TestActor {
+///     fn handle<'a>(&'a self, _: TestMessage3, ctx: Context<'a, Self>) -> LocalBoxFuture<'a, ()> {
+///         Box::pin(async move {
+///             let _this = self;
+///             let _ctx = ctx;
+///             println!("hello from TestMessage3");
 ///         })
 ///     }
 /// }
+///
+/// struct TestMessage4;
+/// message!(TestMessage4, u32);
+///
+/// // generate Handler impl from async method with handler macro.
+/// #[actix_async::handler]
+/// impl TestActor {
+///     #[handle]
+///     async fn on_message4(&self, _: TestMessage4, _: Context<'_, Self>) -> u32 {
+///         996
+///     }
+/// }
 /// ```
 pub trait Handler<M>
 where
@@ -50,6 +87,68 @@ where
 {
     /// concurrent handler. `Actor` and `Context` are borrowed immutably so it's safe to handle
     /// multiple messages at the same time.
+    fn handle<'a>(&'a self, msg: M, ctx: Context<'a, Self>)
+        -> impl Future<Output = M::Result> + 'a;
+
+    /// exclusive handler. `Actor` and `Context` are borrowed mutably so only one message can be
+    /// handle at any given time. `Actor` would block on this method until it's finished.
+    fn handle_wait<'a>(
+        &'a mut self,
+        msg: M,
+        ctx: Context<'a, Self>,
+    ) -> impl Future<Output = M::Result> + 'a {
+        // fall back to handle by default
+        self.handle(msg, ctx)
+    }
+}
+
+/// compat trait of 0.1 `Handler` returning boxed future with separate lifetimes for actor,
+/// `Context` and the returned future.
+///
+/// Existing impls can be kept by renaming the trait and forward `Handler` to them with
+/// `handler_boxed!` macro.
+///
+/// # example:
+/// ```rust
+/// use actix_async::prelude::*;
+///
+/// struct TestActor;
+/// actor!(TestActor);
+///
+/// struct TestMessage;
+/// message!(TestMessage, u32);
+///
+/// impl HandlerBoxed<TestMessage> for TestActor {
+///     fn handle<'act, 'ctx, 'res>(
+///         &'act self,
+///         _: TestMessage,
+///         ctx: Context<'ctx, Self>,
+///     ) -> LocalBoxFuture<'res, u32>
+///     where
+///         'act: 'res,
+///         'ctx: 'res,
+///     {
+///         Box::pin(async move {
+///             let _ctx = ctx;
+///             996
+///         })
+///     }
+/// }
+///
+/// handler_boxed!(TestActor, TestMessage);
+///
+/// #[actix_async::main]
+/// async fn main() {
+///     let addr = TestActor.start();
+///     assert_eq!(addr.send(TestMessage).await.unwrap(), 996);
+/// }
+/// ```
+pub trait HandlerBoxed<M>
+where
+    M: Message,
+    Self: Actor,
+{
+    /// concurrent handler. See `Handler::handle`.
     fn handle<'act, 'ctx, 'res>(
         &'act self,
         msg: M,
@@ -59,8 +158,7 @@ where
         'act: 'res,
         'ctx: 'res;
 
-    /// exclusive handler. `Actor` and `Context` are borrowed mutably so only one message can be
-    /// handle at any given time. `Actor` would block on this method until it's finished.
+    /// exclusive handler. See `Handler::handle_wait`.
     fn handle_wait<'act, 'ctx, 'res>(
         &'act mut self,
         msg: M,
@@ -71,7 +169,7 @@ where
         'ctx: 'res,
     {
         // fall back to handle by default
-        self.handle(msg, ctx)
+        HandlerBoxed::handle(self, msg, ctx)
     }
 }
 
@@ -81,15 +179,11 @@ where
     F: for<'a> FnOnce(&'a A, Context<'a, A>) -> LocalBoxFuture<'a, R> + 'static,
     R: Send + 'static,
 {
-    fn handle<'act, 'ctx, 'res>(
-        &'act self,
+    fn handle<'a>(
+        &'a self,
         msg: FunctionMessage<F, R>,
-        ctx: Context<'ctx, Self>,
-    ) -> LocalBoxFuture<'res, R>
-    where
-        'act: 'res,
-        'ctx: 'res,
-    {
+        ctx: Context<'a, Self>,
+    ) -> impl Future<Output = R> + 'a {
         (msg.func)(self, ctx)
     }
 }
@@ -100,37 +194,137 @@ where
     F: for<'a> FnOnce(&'a mut A, Context<'a, A>) -> LocalBoxFuture<'a, R> + 'static,
     R: Send + 'static,
 {
-    fn handle<'act, 'ctx, 'res>(
-        &'act self,
-        _: FunctionMutMessage<F, R>,
-        _: Context<'ctx, Self>,
-    ) -> LocalBoxFuture<'res, R>
-    where
-        'act: 'res,
-        'ctx: 'res,
-    {
-        unreachable!("Handler::handle can not be called on FunctionMutMessage");
+    async fn handle(&self, _: FunctionMutMessage<F, R>, _: Context<'_, Self>) -> R {
+        unreachable!("Handler::handle can not be called on FunctionMutMessage")
     }
 
-    fn handle_wait<'act, 'ctx, 'res>(
-        &'act mut self,
+    fn handle_wait<'a>(
+        &'a mut self,
         msg: FunctionMutMessage<F, R>,
-        ctx: Context<'ctx, Self>,
-    ) -> LocalBoxFuture<'res, R>
-    where
-        'act: 'res,
-        'ctx: 'res,
-    {
+        ctx: Context<'a, Self>,
+    ) -> impl Future<Output = R> + 'a {
         (msg.func)(self, ctx)
     }
 }
 
+impl<A, M, R> Handler<ConvertMessage<M, R>> for A
+where
+    A: Actor + Handler<M>,
+    M: Message,
+    R: From<M::Result> + Send + 'static,
+{
+    async fn handle(&self, msg: ConvertMessage<M, R>, ctx: Context<'_, Self>) -> R {
+        R::from(self.handle(msg.msg, ctx).await)
+    }
+
+    async fn handle_wait(&mut self, msg: ConvertMessage<M, R>, ctx: Context<'_, Self>) -> R {
+        R::from(self.handle_wait(msg.msg, ctx).await)
+    }
+}
+
+impl<A, M, R> Handler<StreamRequest<M, R>> for A
+where
+    A: Actor + Handler<M>,
+    M: Message,
+    R: Send + 'static,
+{
+    fn handle<'a>(
+        &'a self,
+        msg: StreamRequest<M, R>,
+        ctx: Context<'a, Self>,
+    ) -> impl Future<Output = M::Result> + 'a {
+        self.handle(msg.msg, ctx.with_response_sink(msg.sink))
+    }
+
+    fn handle_wait<'a>(
+        &'a mut self,
+        msg: StreamRequest<M, R>,
+        ctx: Context<'a, Self>,
+    ) -> impl Future<Output = M::Result> + 'a {
+        self.handle_wait(msg.msg, ctx.with_response_sink(msg.sink))
+    }
+}
+
+impl<A, M> Handler<InFlightMessage<M>> for A
+where
+    A: Actor + Handler<M>,
+    M: Message,
+{
+    async fn handle(&self, msg: InFlightMessage<M>, ctx: Context<'_, Self>) -> M::Result {
+        let InFlightMessage { msg, permit } = msg;
+        let res = self.handle(msg, ctx).await;
+        drop(permit);
+        res
+    }
+
+    async fn handle_wait(&mut self, msg: InFlightMessage<M>, ctx: Context<'_, Self>) -> M::Result {
+        let InFlightMessage { msg, permit } = msg;
+        let res = self.handle_wait(msg, ctx).await;
+        drop(permit);
+        res
+    }
+}
+
+impl<A, M> Handler<BatchMessage<M>> for A
+where
+    A: Actor + Handler<M>,
+    M: Message,
+{
+    async fn handle(&self, msg: BatchMessage<M>, ctx: Context<'_, Self>) -> Vec<M::Result> {
+        let mut res = Vec::with_capacity(msg.0.len());
+        for msg in msg.0 {
+            res.push(self.handle(msg, ctx.reborrow()).await);
+        }
+        res
+    }
+
+    async fn handle_wait(
+        &mut self,
+        msg: BatchMessage<M>,
+        ctx: Context<'_, Self>,
+    ) -> Vec<M::Result> {
+        let mut res = Vec::with_capacity(msg.0.len());
+        for msg in msg.0 {
+            res.push(self.handle_wait(msg, ctx.reborrow()).await);
+        }
+        res
+    }
+}
+
 pub trait MessageHandler<A: Actor> {
     fn handle<'f>(&mut self, act: &'f A, ctx: Context<'f, A>) -> LocalBoxFuture<'f, ()>;
 
     fn handle_wait<'f>(&mut self, act: &'f mut A, ctx: Context<'f, A>) -> LocalBoxFuture<'f, ()> {
         self.handle(act, ctx)
     }
+
+    /// handle concurrent message and complete it after the previous message from the same
+    /// sender.
+    // MessageHandler lives in private module and can not be named outside of crate.
+    #[allow(private_interfaces)]
+    fn handle_ordered<'f>(
+        &mut self,
+        act: &'f A,
+        ctx: Context<'f, A>,
+        order: Order,
+    ) -> LocalBoxFuture<'f, ()>;
+
+    /// metadata of message.
+    fn envelope(&self) -> Envelope;
+
+    fn set_sender(&mut self, sender: usize);
+
+    /// estimated bytes of message.
+    fn size(&self) -> usize;
+
+    /// type name of message.
+    fn type_name(&self) -> &'static str;
+
+    /// type erased message container. used to take back message failed to send.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// take the message boxed as Any. Return None when message is not Send.
+    fn take_any(&mut self) -> Option<Box<dyn Any + Send>>;
 }
 
 impl<A, M> MessageHandler<A> for MessageContainer<M>
@@ -140,32 +334,111 @@ where
 {
     fn handle<'f>(&mut self, act: &'f A, ctx: Context<'f, A>) -> LocalBoxFuture<'f, ()> {
         let (msg, tx) = self.take();
-        let fut = act.handle(msg, ctx);
-        handle(tx, fut)
+        let fut = act.handle(
+            msg,
+            ctx.with_envelope(self.envelope).with_response(tx.as_ref()),
+        );
+        handle::<A, _>(tx, fut)
     }
 
     fn handle_wait<'f>(&mut self, act: &'f mut A, ctx: Context<'f, A>) -> LocalBoxFuture<'f, ()> {
         let (msg, tx) = self.take();
-        let fut = act.handle_wait(msg, ctx);
-        handle(tx, fut)
+        let fut = act.handle_wait(
+            msg,
+            ctx.with_envelope(self.envelope).with_response(tx.as_ref()),
+        );
+        handle::<A, _>(tx, fut)
+    }
+
+    fn handle_ordered<'f>(
+        &mut self,
+        act: &'f A,
+        ctx: Context<'f, A>,
+        mut order: Order,
+    ) -> LocalBoxFuture<'f, ()> {
+        let (msg, tx) = self.take();
+        let fut = act.handle(
+            msg,
+            ctx.with_envelope(self.envelope).with_response(tx.as_ref()),
+        );
+        Box::pin(async move {
+            match tx {
+                Some(tx) => {
+                    if tx.start() {
+                        let res = timeout::<A, _>(fut).await;
+                        order.wait().await;
+                        match res {
+                            Some(res) => {
+                                let _ = tx.send(res);
+                            }
+                            None => tx.timeout(),
+                        }
+                    }
+                }
+                None => {
+                    let _ = timeout::<A, _>(fut).await;
+                    order.wait().await;
+                }
+            }
+        })
+    }
+
+    fn envelope(&self) -> Envelope {
+        self.envelope
+    }
+
+    fn set_sender(&mut self, sender: usize) {
+        self.envelope.sender = sender;
+    }
+
+    fn size(&self) -> usize {
+        self.msg.as_ref().map(M::size_hint_bytes).unwrap_or(0)
+    }
+
+    fn type_name(&self) -> &'static str {
+        core::any::type_name::<M>()
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn take_any(&mut self) -> Option<Box<dyn Any + Send>> {
+        let msg = self.msg.take()?;
+        self.any.map(|f| f(msg))
     }
 }
 
-fn handle<'f, Fut>(tx: Option<OneshotSender<Fut::Output>>, fut: Fut) -> LocalBoxFuture<'f, ()>
+fn handle<'f, A, Fut>(tx: Option<OneshotSender<Fut::Output>>, fut: Fut) -> LocalBoxFuture<'f, ()>
 where
+    A: Actor,
     Fut: Future + 'f,
 {
     Box::pin(async move {
         match tx {
             Some(tx) => {
-                if !tx.is_closed() {
-                    let res = fut.await;
-                    let _ = tx.send(res);
+                // message expired or request dropped before handling starts.
+                if tx.start() {
+                    match timeout::<A, _>(fut).await {
+                        Some(res) => {
+                            let _ = tx.send(res);
+                        }
+                        None => tx.timeout(),
+                    }
                 }
             }
             None => {
-                let _ = fut.await;
+                let _ = timeout::<A, _>(fut).await;
             }
         }
     })
 }
+
+// abort handler future when it's not finished within Actor::handler_timeout.
+fn timeout<A, Fut>(fut: Fut) -> Timeout<Fut, <A::Runtime as RuntimeService>::Sleep>
+where
+    A: Actor,
+    Fut: Future,
+{
+    Timeout::new(fut, A::handler_timeout().map(A::Runtime::sleep))
+}
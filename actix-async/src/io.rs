@@ -0,0 +1,321 @@
+//! Outbound IO of actor. See `Context::add_sink`.
+
+use core::{
+    cell::RefCell,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context as StdContext, Poll, Waker},
+};
+
+use alloc::{boxed::Box, collections::VecDeque, rc::Rc, vec::Vec};
+
+use futures_sink::Sink;
+
+use super::actor::Actor;
+use super::context::Context;
+use super::message::{function_task, ActorMessage};
+use super::util::futures::{poll_fn, ready, Stream};
+
+/// Trait define how actor handle the result of a sink added with `Context::add_sink`.
+///
+/// Hooks are called as concurrent messages.
+pub trait WriteHandler<E>: Actor {
+    /// async hook when sink returns error. The sink is closed and writes still in buffer are
+    /// dropped.
+    ///
+    /// Default to stop the actor gracefully.
+    fn error<'a>(&'a self, err: E, ctx: Context<'a, Self>) -> impl Future<Output = ()> + 'a {
+        drop(err);
+        async move {
+            ctx.stop();
+        }
+    }
+
+    /// async hook when sink is closed after all writes are flushed. Happens when
+    /// `SinkHandle::close` is called or all `SinkHandle` are dropped.
+    ///
+    /// Default to do nothing.
+    fn finished<'a>(&'a self, _: Context<'a, Self>) -> impl Future<Output = ()> + 'a {
+        async {}
+    }
+}
+
+/// Handle of a sink added to actor's context. Obtained from `Context::add_sink`.
+///
+/// Writes are buffered and sent to sink by actor's context. Handle is cheap to clone and the sink
+/// is closed when all handles are dropped.
+///
+/// *. Handle is bound to the thread actor runs on and can be stored in actor's state.
+///
+/// *. `write` drives the sink by itself when buffer is full so it can be awaited in exclusive
+/// tasks where actor's context does not drive the sink.
+pub struct SinkHandle<I> {
+    state: Rc<RefCell<SinkState<I>>>,
+    io: Rc<dyn SinkWrite>,
+}
+
+struct SinkState<I> {
+    buf: VecDeque<I>,
+    cap: usize,
+    // no more write is accepted.
+    closed: bool,
+    driver: Option<Waker>,
+    writers: Vec<Waker>,
+}
+
+impl<I> SinkState<I> {
+    fn wake_driver(&mut self) {
+        if let Some(waker) = self.driver.take() {
+            waker.wake();
+        }
+    }
+
+    fn wake_writers(&mut self) {
+        self.writers.drain(..).for_each(Waker::wake);
+    }
+
+    fn register_writer(&mut self, waker: &Waker) {
+        if !self.writers.iter().any(|w| w.will_wake(waker)) {
+            self.writers.push(waker.clone());
+        }
+    }
+}
+
+impl<I> Clone for SinkHandle<I> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            io: self.io.clone(),
+        }
+    }
+}
+
+impl<I> Drop for SinkHandle<I> {
+    fn drop(&mut self) {
+        // sink would be closed when the last handle is dropped.
+        self.state.borrow_mut().wake_driver();
+    }
+}
+
+impl<I> SinkHandle<I> {
+    /// queue an item to sink's buffer. Return the item when buffer is full or sink is closed.
+    pub fn try_write(&self, item: I) -> Result<(), I> {
+        let mut state = self.state.borrow_mut();
+        if state.closed || state.buf.len() >= state.cap {
+            return Err(item);
+        }
+        state.buf.push_back(item);
+        state.wake_driver();
+        Ok(())
+    }
+
+    /// queue an item to sink's buffer. Wait for buffer to have space when it's full.
+    ///
+    /// Return the item when sink is closed.
+    pub async fn write(&self, item: I) -> Result<(), I> {
+        let mut item = Some(item);
+        poll_fn(|cx| loop {
+            let mut state = self.state.borrow_mut();
+            if state.closed {
+                return Poll::Ready(Err(item.take().unwrap()));
+            }
+            if state.buf.len() < state.cap {
+                state.buf.push_back(item.take().unwrap());
+                state.wake_driver();
+                return Poll::Ready(Ok(()));
+            }
+            // driver can be blocked by exclusive task. write buffered items to sink here.
+            state.register_writer(cx.waker());
+            drop(state);
+            ready!(self.io.poll_write(cx));
+        })
+        .await
+    }
+
+    /// close the sink after all buffered writes are flushed.
+    pub fn close(&self) {
+        let mut state = self.state.borrow_mut();
+        state.closed = true;
+        state.wake_driver();
+        state.wake_writers();
+    }
+
+    /// check if sink is closed and no more write would be accepted.
+    pub fn is_closed(&self) -> bool {
+        self.state.borrow().closed
+    }
+
+    /// count of writes in buffer.
+    pub fn buffered(&self) -> usize {
+        self.state.borrow().buf.len()
+    }
+}
+
+// sink shared by SinkDriver and SinkHandle.
+struct SinkIo<S, I>
+where
+    S: Sink<I>,
+{
+    sink: Pin<Box<S>>,
+    state: Rc<RefCell<SinkState<I>>>,
+    flushing: bool,
+    // error from writing in SinkHandle. It's passed to WriteHandler by SinkDriver.
+    err: Option<S::Error>,
+}
+
+impl<S, I> SinkIo<S, I>
+where
+    S: Sink<I>,
+{
+    // send all buffered items to sink and flush it.
+    fn poll_send(&mut self, cx: &mut StdContext<'_>) -> Poll<Result<(), S::Error>> {
+        loop {
+            let has_item = !self.state.borrow().buf.is_empty();
+            if has_item {
+                ready!(self.sink.as_mut().poll_ready(cx))?;
+                let item = {
+                    let mut state = self.state.borrow_mut();
+                    state.wake_writers();
+                    state.buf.pop_front().unwrap()
+                };
+                self.sink.as_mut().start_send(item)?;
+                self.flushing = true;
+                continue;
+            }
+
+            if self.flushing {
+                ready!(self.sink.as_mut().poll_flush(cx))?;
+                self.flushing = false;
+            }
+
+            return Poll::Ready(Ok(()));
+        }
+    }
+}
+
+// type erased sink writing for SinkHandle.
+trait SinkWrite {
+    // resolve when buffered items are written or sink is errored.
+    fn poll_write(&self, cx: &mut StdContext<'_>) -> Poll<()>;
+}
+
+impl<S, I> SinkWrite for RefCell<SinkIo<S, I>>
+where
+    S: Sink<I>,
+{
+    fn poll_write(&self, cx: &mut StdContext<'_>) -> Poll<()> {
+        let mut io = self.borrow_mut();
+        if io.err.is_some() {
+            return Poll::Ready(());
+        }
+        if let Err(e) = ready!(io.poll_send(cx)) {
+            io.err = Some(e);
+            let mut state = io.state.borrow_mut();
+            state.closed = true;
+            state.wake_driver();
+        }
+        Poll::Ready(())
+    }
+}
+
+// drive buffered writes of SinkHandle into sink. It's added to context as a stream producing
+// WriteHandler hooks.
+pub(crate) struct SinkDriver<A, S, I>
+where
+    S: Sink<I>,
+{
+    io: Rc<RefCell<SinkIo<S, I>>>,
+    done: bool,
+    _act: PhantomData<fn() -> A>,
+}
+
+impl<A, S, I> SinkDriver<A, S, I>
+where
+    S: Sink<I> + 'static,
+    I: 'static,
+{
+    pub(crate) fn new(sink: S, cap: usize) -> (Self, SinkHandle<I>) {
+        let state = Rc::new(RefCell::new(SinkState {
+            buf: VecDeque::with_capacity(cap),
+            cap: core::cmp::max(cap, 1),
+            closed: false,
+            driver: None,
+            writers: Vec::new(),
+        }));
+
+        let io = Rc::new(RefCell::new(SinkIo {
+            sink: Box::pin(sink),
+            state: state.clone(),
+            flushing: false,
+            err: None,
+        }));
+
+        let driver = Self {
+            io: io.clone(),
+            done: false,
+            _act: PhantomData,
+        };
+
+        (driver, SinkHandle { state, io })
+    }
+}
+
+impl<A, S, I> Unpin for SinkDriver<A, S, I> where S: Sink<I> {}
+
+impl<A, S, I> Stream for SinkDriver<A, S, I>
+where
+    A: WriteHandler<S::Error>,
+    S: Sink<I>,
+    S::Error: 'static,
+{
+    type Item = ActorMessage<A>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        let mut io = this.io.borrow_mut();
+
+        // writer can poll sink with it's own waker. register driver's waker before polling.
+        io.state.borrow_mut().driver = Some(cx.waker().clone());
+
+        let err = match io.err.take() {
+            Some(e) => e,
+            None => match ready!(io.poll_send(cx)) {
+                Err(e) => e,
+                Ok(()) => {
+                    // all handles are dropped when driver holds the only reference of state.
+                    let closed = io.state.borrow().closed || Rc::strong_count(&io.state) == 1;
+                    if !closed {
+                        return Poll::Pending;
+                    }
+                    match ready!(io.sink.as_mut().poll_close(cx)) {
+                        Ok(()) => {
+                            this.done = true;
+                            close(&io.state);
+                            let msg = function_task(|act: &A, ctx| Box::pin(act.finished(ctx)));
+                            return Poll::Ready(Some(ActorMessage::Ref(msg)));
+                        }
+                        Err(e) => e,
+                    }
+                }
+            },
+        };
+
+        this.done = true;
+        close(&io.state);
+        io.state.borrow_mut().buf.clear();
+        let msg = function_task(move |act: &A, ctx| Box::pin(act.error(err, ctx)));
+        Poll::Ready(Some(ActorMessage::Ref(msg)))
+    }
+}
+
+fn close<I>(state: &Rc<RefCell<SinkState<I>>>) {
+    let mut state = state.borrow_mut();
+    state.closed = true;
+    state.wake_writers();
+}
@@ -57,8 +57,11 @@ extern crate alloc;
 mod actor;
 mod context_future;
 mod handler;
+mod lifecycle;
 mod macros;
 mod message;
+#[cfg(feature = "std")]
+mod observer;
 mod util;
 mod waker;
 
@@ -66,35 +69,58 @@ pub mod address;
 pub mod context;
 pub mod error;
 pub mod prelude {
-    pub use crate::actor::{Actor, ActorState};
+    pub use crate::actor::{
+        Actor, ActorBuilder, ActorState, CustomReason, OverflowPolicy, StartHandle, StopReason,
+    };
+    #[cfg(feature = "std")]
+    pub use crate::actor::{PanicAction, PanicInfo};
     pub use crate::context::Context;
     pub use crate::context::ContextJoinHandle;
     pub use crate::error::ActixAsyncError;
-    pub use crate::handler::Handler;
+    pub use crate::handler::{Handler, HandlerBoxed};
     pub use crate::message::Message;
+    #[cfg(feature = "std")]
+    pub use crate::observer::MailboxObserver;
     pub use crate::runtime::RuntimeService;
     pub use crate::util::futures::LocalBoxFuture;
+    #[cfg(feature = "macros")]
+    // derive macro of Message trait.
+    pub use actix_async_codegen::Message;
+
+    #[cfg(not(feature = "futures-core"))]
+    // crate's own Stream trait when futures-core is not used.
+    pub use crate::util::futures::Stream;
 
     // message macro
     pub use crate::message;
 
+    // macro forwarding Handler to HandlerBoxed
+    pub use crate::handler_boxed;
+
     #[cfg(feature = "tokio-rt")]
     // tokio actor macro
     pub use crate::actor;
 
     #[cfg(feature = "tokio-rt")]
-    pub use self::default_tokio_rt::TokioRuntime;
+    pub use self::default_tokio_rt::{TokioInterval, TokioRuntime};
 
     #[cfg(feature = "tokio-rt")]
     mod default_tokio_rt {
         use super::RuntimeService;
 
-        use core::{future::Future, time::Duration};
+        use core::{
+            future::Future,
+            task::{Context, Poll},
+            time::Duration,
+        };
+
+        use crate::runtime::{MissedTickBehavior, RuntimeInterval};
 
         pub struct TokioRuntime;
 
         impl RuntimeService for TokioRuntime {
             type Sleep = tokio::time::Sleep;
+            type Interval = TokioInterval;
 
             fn spawn<F: Future<Output = ()> + 'static>(f: F) {
                 tokio::task::spawn_local(f);
@@ -103,20 +129,56 @@ pub mod prelude {
             fn sleep(dur: Duration) -> Self::Sleep {
                 tokio::time::sleep(dur)
             }
+
+            fn sleep_until(deadline: std::time::Instant) -> Self::Sleep {
+                tokio::time::sleep_until(deadline.into())
+            }
+
+            fn spawn_blocking<F: FnOnce() + Send + 'static>(f: F) {
+                tokio::task::spawn_blocking(f);
+            }
+        }
+
+        /// interval of `TokioRuntime` backed by `tokio::time::Interval`.
+        pub struct TokioInterval(tokio::time::Interval);
+
+        impl RuntimeInterval for TokioInterval {
+            fn new(period: Duration, missed: MissedTickBehavior) -> Self {
+                let start = tokio::time::Instant::now() + period;
+                let mut interval = tokio::time::interval_at(start, period);
+                interval.set_missed_tick_behavior(match missed {
+                    MissedTickBehavior::Burst => tokio::time::MissedTickBehavior::Burst,
+                    MissedTickBehavior::Delay => tokio::time::MissedTickBehavior::Delay,
+                    MissedTickBehavior::Skip => tokio::time::MissedTickBehavior::Skip,
+                });
+                Self(interval)
+            }
+
+            fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+                self.0.poll_tick(cx).map(|_| ())
+            }
+
+            fn reset(&mut self) {
+                self.0.reset();
+            }
         }
     }
 }
+#[cfg(feature = "framed")]
+pub mod framed;
+#[cfg(feature = "futures-sink")]
+pub mod io;
+pub mod registry;
 pub mod request;
 pub mod runtime;
 
 #[cfg(feature = "tokio-rt")]
 pub mod supervisor;
+#[cfg(feature = "tokio-rt")]
+pub mod system;
 
 #[cfg(feature = "macros")]
-pub use {
-    actix_async_codegen::{handler, main, test},
-    async_trait::async_trait as __async_trait,
-};
+pub use actix_async_codegen::{handler, main, test};
 
 #[cfg(doctest)]
 doc_comment::doctest!("../../README.md");
@@ -220,160 +282,3454 @@ mod test {
     }
 
     #[actix_async::test]
-    async fn delay() {
+    async fn recipient_try_do_send() {
         let addr = TestActor::default().start();
 
-        let res = addr.send(TestDelayMessage).await.unwrap();
-        drop(res);
+        let re = addr.recipient::<TestMsg>();
+        assert!(re.try_do_send(TestMsg).is_ok());
+        assert!(re.try_do_wait(TestMsg).is_ok());
 
-        sleep(Duration::from_millis(300)).await;
-        let res = addr.send(TestMsg).await.unwrap();
-        assert_eq!(996, res);
+        drop(re);
 
-        sleep(Duration::from_millis(300)).await;
-        let res = addr.send(TestMsg).await.unwrap();
-        assert_eq!(997, res);
+        let re = addr.recipient_weak::<TestMsg>();
+        assert!(re.try_do_send(TestMsg).is_ok());
 
-        let res = addr.send(TestDelayMessage).await.unwrap();
+        drop(addr);
 
-        sleep(Duration::from_millis(400)).await;
-        res.cancel();
+        // weak recipient does not panic when actor is gone.
+        re.do_send(TestMsg);
+        re.do_wait(TestMsg);
+        assert_eq!(re.try_do_send(TestMsg), Err(ActixAsyncError::Closed));
+        assert_eq!(re.try_do_wait(TestMsg), Err(ActixAsyncError::Closed));
+    }
 
-        sleep(Duration::from_millis(300)).await;
-        let res = addr.send(TestMsg).await.unwrap();
-        assert_eq!(997, res);
+    // Addr is hashed by the address of it's channel which is never mutated.
+    #[allow(clippy::mutable_key_type)]
+    #[actix_async::test]
+    async fn addr_eq() {
+        let addr = TestActor::default().start();
+        let addr2 = TestActor::default().start();
+
+        let mut set = std::collections::HashSet::new();
+        assert!(set.insert(addr.clone()));
+        assert!(!set.insert(addr.clone()));
+        assert!(set.insert(addr2.clone()));
+        assert!(set.remove(&addr));
+        assert_eq!(set.len(), 1);
+
+        assert_eq!(addr.downgrade(), addr.downgrade());
+        assert_ne!(addr.downgrade(), addr2.downgrade());
+
+        let debug = format!("{:?}", addr);
+        assert!(debug.starts_with("Addr { actor: "));
+        assert!(debug.contains("TestActor"));
     }
 
     #[actix_async::test]
-    async fn stream() {
+    async fn addr_closed() {
         let addr = TestActor::default().start();
+        assert!(addr.is_connected());
 
-        struct TestStream {
-            interval: Interval,
-            state: Arc<AtomicUsize>,
-        }
+        let addr1 = addr.clone();
+        let handle = tokio::task::spawn_local(async move {
+            addr1.closed().await;
+            assert!(!addr1.is_connected());
+        });
 
-        impl futures_util::stream::Stream for TestStream {
-            type Item = TestMsg;
+        sleep(Duration::from_millis(100)).await;
+        assert!(!handle.is_finished());
 
-            fn poll_next(
-                self: Pin<&mut Self>,
-                cx: &mut StdContext<'_>,
-            ) -> Poll<Option<Self::Item>> {
-                let this = self.get_mut();
-                if this.interval.poll_tick(cx).is_pending() {
-                    return Poll::Pending;
-                }
-                this.state.fetch_add(1, Ordering::SeqCst);
-                Poll::Ready(Some(TestMsg))
+        addr.stop(true).await.unwrap();
+        handle.await.unwrap();
+
+        // resolves immediately on closed channel.
+        addr.closed().await;
+        assert!(!addr.is_connected());
+    }
+
+    #[actix_async::test]
+    async fn recipient_from() {
+        use crate::address::{Recipient, RecipientWeak};
+
+        let addr = TestActor::default().start();
+
+        let re: RecipientWeak<_, TestMsg> = addr.downgrade().into();
+        assert_eq!(re.send(TestMsg).await, Ok(996));
+
+        let re: RecipientWeak<_, TestMsg> = (&addr).into();
+        assert_eq!(re.wait(TestMsg).await, Ok(251));
+
+        let re: Recipient<_, TestMsg> = (&addr).into();
+        assert!(re == addr.recipient());
+
+        let re: Recipient<_, TestMsg> = addr.into();
+        assert_eq!(re.send(TestMsg).await, Ok(996));
+    }
+
+    #[actix_async::test]
+    async fn recipient_with() {
+        struct Outer;
+        message!(Outer, usize);
+
+        let addr = TestActor::default().start();
+
+        let re = addr.recipient::<TestMsg>().with(|_: Outer| TestMsg);
+        assert_eq!(re.send(Outer).await, Ok(996));
+        assert_eq!(re.wait(Outer).await, Ok(251));
+        assert!(re.try_do_send(Outer).is_ok());
+
+        drop(re);
+
+        let re = addr.recipient_weak::<TestMsg>().with(|_: Outer| TestMsg);
+        assert_eq!(re.send(Outer).await, Ok(996));
+
+        drop(addr);
+
+        assert_eq!(re.try_do_wait(Outer), Err(ActixAsyncError::Closed));
+    }
+
+    #[actix_async::test]
+    async fn forward_to() {
+        use crate::address::Recipient;
+
+        struct Forwarded(usize);
+        message!(Forwarded, ());
+
+        impl From<usize> for Forwarded {
+            fn from(res: usize) -> Self {
+                Forwarded(res)
             }
         }
 
-        let state = Arc::new(AtomicUsize::new(0));
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        let re = Recipient::<TokioRuntime, Forwarded>::from_sender(tx);
 
-        let handle = addr
-            .run_wait({
-                let state = state.clone();
-                move |_, ctx| {
-                    Box::pin(async move {
-                        ctx.add_stream(TestStream {
-                            interval: interval(Duration::from_millis(500)),
-                            state,
-                        })
-                    })
-                }
-            })
-            .await
-            .unwrap();
+        let addr = TestActor::default().start();
 
-        sleep(Duration::from_millis(750)).await;
-        handle.cancel();
-        sleep(Duration::from_millis(1000)).await;
-        assert_eq!(2, state.load(Ordering::SeqCst));
+        addr.send(TestMsg).forward_to(re).await.unwrap();
+        assert_eq!(rx.recv().await.unwrap().0, 996);
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        let re = Recipient::<TokioRuntime, Forwarded>::from_sender(tx);
+
+        addr.wait(TestMsg).forward_to(re).await.unwrap();
+        assert_eq!(rx.recv().await.unwrap().0, 251);
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        let re = Recipient::<TokioRuntime, Forwarded>::from_sender(tx);
+
+        addr.stop(true).await.unwrap();
+        let res = addr.send(TestMsg).forward_to(re).await;
+        assert_eq!(res, Err(ActixAsyncError::Closed));
+        // recipient is dropped with the request.
+        assert!(rx.recv().await.is_none());
     }
 
     #[actix_async::test]
-    async fn capacity() {
-        let state = Rc::new(Cell::new(0));
+    async fn channel_recipient_dead_letter() {
+        use crate::address::{set_dead_letter_hook, AnyDeadLetter, DeadLetterReason, Recipient};
 
-        let addr = TestCapActor(state.clone()).start();
+        struct ChannelMsg(usize);
+        message!(ChannelMsg, ());
 
-        let addr_clone = addr.clone();
-        tokio::task::spawn_local(async move {
-            let mut futs = futures_util::stream::FuturesUnordered::new();
+        static OVERFLOW: AtomicUsize = AtomicUsize::new(0);
+        static CLOSED: AtomicUsize = AtomicUsize::new(0);
 
-            for _ in 0..4 {
-                futs.push(addr_clone.send(TestCapMsg));
+        fn hook(letter: AnyDeadLetter) {
+            if letter.message_type() != core::any::type_name::<ChannelMsg>() {
+                return;
             }
+            let count = match letter.reason() {
+                DeadLetterReason::Overflow => &OVERFLOW,
+                _ => &CLOSED,
+            };
+            let msg = letter
+                .into_message()
+                .unwrap()
+                .downcast::<ChannelMsg>()
+                .unwrap();
+            count.fetch_add(msg.0, Ordering::SeqCst);
+        }
 
-            while futs.next().await.is_some() {}
-        });
+        set_dead_letter_hook(hook);
 
-        let now = Instant::now();
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        let re = Recipient::<TokioRuntime, ChannelMsg>::from_sender(tx);
 
-        tokio::task::yield_now().await;
+        re.do_send(ChannelMsg(1));
+        // channel is full.
+        re.do_send(ChannelMsg(2));
+        assert_eq!(rx.recv().await.unwrap().0, 1);
 
-        assert_eq!(state.get(), 4);
+        drop(rx);
+        re.do_wait(ChannelMsg(3));
 
-        addr.do_send(TestMsg);
+        assert_eq!(OVERFLOW.load(Ordering::SeqCst), 2);
+        assert_eq!(CLOSED.load(Ordering::SeqCst), 3);
+    }
 
-        let res = addr.send(TestMsg).await.unwrap();
+    #[actix_async::test]
+    async fn then_send() {
+        let addr = TestActor::default().start();
+        let addr2 = TestActor::default().start();
 
-        assert_eq!(res, 5);
-        assert_eq!(state.get(), 6);
-        assert!(now.elapsed() > Duration::from_secs(3));
+        let res = addr
+            .send(TestMsg)
+            .then_send(&addr2, |_| TestMsg, |req| req)
+            .await;
+        assert_eq!(res, Ok(996));
+
+        let res = addr
+            .send(TestTimeoutMessage)
+            .timeout_response(Duration::from_millis(100))
+            .then_send(&addr2, |_| TestMsg, |req| req)
+            .await;
+        assert_eq!(res, Err(ActixAsyncError::ReceiveTimeout));
+
+        // timeout of second hop is configured separately.
+        let res = addr
+            .send(TestMsg)
+            .then_send(
+                &addr2,
+                |_| TestTimeoutMessage,
+                |req| req.timeout_response(Duration::from_millis(100)),
+            )
+            .await;
+        assert_eq!(res, Err(ActixAsyncError::ReceiveTimeout));
+
+        addr2.stop(true).await.unwrap();
+        let res = addr
+            .wait(TestMsg)
+            .then_send(&addr2, |_| TestMsg, |req| req)
+            .await;
+        assert_eq!(res, Err(ActixAsyncError::Closed));
     }
 
     #[actix_async::test]
-    async fn panic_recovery() {
-        let supervisor = Supervisor::builder().workers(1).build();
-        let addr = supervisor
-            .start(1, |_| async { TestActor::default() })
-            .await;
+    async fn broadcast() {
+        struct BroadcastActor(u64);
+        actor!(BroadcastActor);
+
+        #[derive(Clone)]
+        struct Delay;
+        message!(Delay, u64);
+
+        #[actix_async::handler]
+        impl Handler<Delay> for BroadcastActor {
+            async fn handle(&self, _: Delay, _: Context<'_, Self>) -> u64 {
+                sleep(Duration::from_millis(self.0)).await;
+                self.0
+            }
+        }
 
-        let _ = addr.send(TestPanicMsg).await;
-        sleep(Duration::from_millis(1000)).await;
-        let res = addr.send(TestMsg).await;
+        let recipients = [10, 20, 500, 30]
+            .iter()
+            .map(|delay| BroadcastActor(*delay).start().recipient())
+            .collect::<Vec<_>>();
 
-        assert_eq!(996, res.unwrap());
+        let now = Instant::now();
+        let res = crate::request::broadcast(&recipients, Delay, Duration::from_millis(200)).await;
+        assert!(now.elapsed() < Duration::from_millis(500));
+
+        assert_eq!(
+            res,
+            vec![Ok(10), Ok(20), Err(ActixAsyncError::ReceiveTimeout), Ok(30)]
+        );
+
+        let res = crate::request::broadcast(&recipients[..0], Delay, Duration::from_secs(1)).await;
+        assert!(res.is_empty());
     }
 
-    // This is to test if re allocation of actor state would break other concurrent tasks.
     #[actix_async::test]
-    async fn actor_move() {
-        struct TestAct(RefCell<Vec<u8>>);
-        actor!(TestAct);
+    async fn send_later() {
+        struct LaterActor(Arc<AtomicUsize>);
+        actor!(LaterActor);
 
-        let mut v = Vec::with_capacity(3);
-        for i in 1..4 {
-            v.push(i);
+        struct Incr;
+        message!(Incr, ());
+
+        #[actix_async::handler]
+        impl Handler<Incr> for LaterActor {
+            async fn handle(&self, _: Incr, _: Context<'_, Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
         }
 
-        let addr = TestAct(RefCell::new(v)).start();
+        let count = Arc::new(AtomicUsize::new(0));
+        let addr = LaterActor(count.clone()).start();
 
-        let addr_clone = addr.clone();
+        let handle = addr.send_later(Duration::from_millis(100), Incr);
+        let handle2 = addr.send_later(Duration::from_millis(100), Incr);
+        handle2.cancel();
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+        assert!(!handle.is_terminated());
+
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(handle.is_terminated());
+
+        addr.stop(true).await.unwrap();
+        let handle = addr.send_later(Duration::from_millis(100), Incr);
+        assert!(handle.is_terminated());
+    }
+
+    #[actix_async::test]
+    async fn send_at() {
+        use std::time::Instant;
+
+        struct AtActor(Arc<AtomicUsize>);
+        actor!(AtActor);
+
+        struct Incr;
+        message!(Incr, ());
+
+        #[actix_async::handler]
+        impl Handler<Incr> for AtActor {
+            async fn handle(&self, _: Incr, _: Context<'_, Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let addr = AtActor(count.clone()).start();
+
+        // block actor so it receives the schedule late.
+        let addr2 = addr.clone();
         tokio::task::spawn_local(async move {
-            addr_clone
-                .run(|act, _| {
-                    Box::pin(async move {
-                        let this = act;
-                        tokio::task::yield_now().await;
-                        assert_eq!(*this.0.borrow().get(3).unwrap(), 4);
-                    })
-                })
-                .await
-                .unwrap();
+            let _ = addr2
+                .run_wait(|_, _| Box::pin(sleep(Duration::from_millis(60))))
+                .await;
         });
+        sleep(Duration::from_millis(10)).await;
 
-        addr.run(|act, _| {
+        let deadline = Instant::now() + Duration::from_millis(100);
+        let handle = addr.send_at(deadline, Incr);
+
+        sleep(Duration::from_millis(80)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        // deadline does not drift with the blocked time.
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(handle.is_terminated());
+
+        // passed deadline is sent right away.
+        addr.run(move |_, ctx| {
             Box::pin(async move {
-                let this = act;
-                tokio::task::yield_now().await;
-                this.0.borrow_mut().push(4);
+                ctx.run_at(deadline, |act, _| {
+                    act.0.fetch_add(1, Ordering::SeqCst);
+                    Box::pin(async {})
+                });
             })
         })
         .await
         .unwrap();
+        sleep(Duration::from_millis(10)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_async::test]
+    async fn send_interval() {
+        let addr = TestActor::default().start();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let c = count.clone();
+        let handle = addr.send_interval(Duration::from_millis(50), move || {
+            c.fetch_add(1, Ordering::SeqCst);
+            TestMsg
+        });
+
+        let start = Instant::now();
+        while count.load(Ordering::SeqCst) < 3 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            sleep(Duration::from_millis(5)).await;
+        }
+        handle.cancel();
+        let ticks = count.load(Ordering::SeqCst);
+
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(count.load(Ordering::SeqCst), ticks);
+    }
+
+    #[actix_async::test]
+    async fn notify() {
+        struct NotifyActor(Arc<AtomicUsize>);
+        actor!(NotifyActor);
+
+        struct Countdown(usize);
+        message!(Countdown, ());
+
+        #[actix_async::handler]
+        impl Handler<Countdown> for NotifyActor {
+            async fn handle(&self, msg: Countdown, ctx: Context<'_, Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                if msg.0 > 0 {
+                    ctx.notify(Countdown(msg.0 - 1));
+                }
+            }
+
+            async fn handle_wait(&mut self, msg: Countdown, ctx: Context<'_, Self>) {
+                self.0.fetch_add(10, Ordering::SeqCst);
+                if msg.0 > 0 {
+                    ctx.notify_wait_later(Countdown(msg.0 - 1), Duration::from_millis(50));
+                }
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let addr = NotifyActor::builder()
+            .capacity(1)
+            .start(NotifyActor(count.clone()));
+
+        addr.send(Countdown(3)).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 4);
+
+        addr.wait(Countdown(2)).await.unwrap();
+        sleep(Duration::from_millis(80)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 24);
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 34);
+    }
+
+    #[actix_async::test]
+    async fn any_addr() {
+        let addr = TestActor::default().start();
+        let any = addr.clone().into_any();
+
+        assert!(any.is_connected());
+        assert!(any.downcast_ref::<TestCapActor>().is_none());
+        assert_eq!(any.downcast_ref::<TestActor>(), Some(&addr));
+        assert_eq!(any.downcast_ref::<TestActor>().unwrap().id(), any.id());
+
+        let any = any.downcast::<TestCapActor>().unwrap_err();
+        let addr2 = any.clone().downcast::<TestActor>().unwrap();
+        assert_eq!(addr2, addr);
+
+        let watch = any.watch();
+        any.stop(true).await.unwrap();
+        assert_eq!(watch.await, StopReason::Graceful);
+        assert!(!any.is_connected());
+        assert!(!addr.is_connected());
+    }
+
+    #[actix_async::test]
+    async fn registry() {
+        use crate::registry::Registry;
+
+        let registry = Registry::new();
+
+        let addr = TestActor::default().start();
+        assert!(registry.register("test", addr.clone()).is_none());
+
+        assert_eq!(registry.lookup::<TestActor>("test"), Some(addr.clone()));
+        assert!(registry.lookup::<TestCapActor>("test").is_none());
+        assert!(registry.lookup::<TestActor>("none").is_none());
+
+        let addr2 = TestActor::default().start();
+        let prev = registry.register("test", addr2.clone()).unwrap();
+        assert_eq!(prev.downcast::<TestActor>().unwrap(), addr);
+
+        let watch = addr2.watch();
+        addr2.stop(true).await.unwrap();
+        watch.await;
+        assert!(registry.lookup_any("test").is_none());
+
+        registry.register("test", addr.clone());
+        assert!(registry.unregister("test").is_some());
+        assert!(registry.lookup::<TestActor>("test").is_none());
+    }
+
+    #[actix_async::test]
+    async fn system_service() {
+        use crate::registry::SystemService;
+
+        let addr = ServiceActor::from_registry();
+        assert_eq!(addr, ServiceActor::from_registry());
+        assert_eq!(addr.send(TestMsg).await, Ok(996));
+
+        let watch = addr.watch();
+        addr.stop(true).await.unwrap();
+        watch.await;
+
+        let addr2 = ServiceActor::from_registry();
+        assert_ne!(addr, addr2);
+        assert!(addr2.is_connected());
+    }
+
+    #[actix_async::test]
+    async fn send_stream() {
+        struct StreamActor(Arc<AtomicUsize>);
+        actor!(StreamActor);
+
+        struct Tail(usize);
+        message!(Tail, ());
+
+        #[actix_async::handler]
+        impl Handler<Tail> for StreamActor {
+            async fn handle(&self, msg: Tail, ctx: Context<'_, Self>) {
+                assert!(ctx.response_sink::<String>().is_none());
+                let sink = ctx.response_sink::<usize>().unwrap();
+                for i in 0..msg.0 {
+                    if sink.send(i).await.is_err() {
+                        break;
+                    }
+                    self.0.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let addr = StreamActor(count.clone()).start();
+
+        let items = addr
+            .send_stream::<_, usize>(Tail(3))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(items, vec![0, 1, 2]);
+
+        // dropped stream would close the sink.
+        let mut stream = addr.send_stream::<_, usize>(Tail(1000));
+        assert_eq!(stream.next().await, Some(0));
+        drop(stream);
+        sleep(Duration::from_millis(50)).await;
+        assert!(count.load(Ordering::SeqCst) < 100);
+
+        // response stream is Send and can be consumed on other task.
+        let addr2 = addr.clone();
+        let items = tokio::spawn(async move {
+            addr2
+                .send_stream::<_, usize>(Tail(2))
+                .collect::<Vec<_>>()
+                .await
+        })
+        .await
+        .unwrap();
+        assert_eq!(items, vec![0, 1]);
+
+        addr.stop(true).await.unwrap();
+        let items = addr
+            .send_stream::<_, usize>(Tail(3))
+            .collect::<Vec<_>>()
+            .await;
+        assert!(items.is_empty());
+    }
+
+    #[actix_async::test]
+    async fn flatten() {
+        struct FlattenActor;
+        actor!(FlattenActor);
+
+        struct Fallible(bool);
+        message!(Fallible, Result<usize, u8>);
+
+        #[actix_async::handler]
+        impl Handler<Fallible> for FlattenActor {
+            async fn handle(&self, msg: Fallible, _: Context<'_, Self>) -> Result<usize, u8> {
+                if msg.0 {
+                    Ok(996)
+                } else {
+                    Err(251)
+                }
+            }
+        }
+
+        #[derive(Debug, PartialEq)]
+        enum FlattenError {
+            Actor(ActixAsyncError),
+            Handler(u8),
+        }
+
+        impl From<ActixAsyncError> for FlattenError {
+            fn from(e: ActixAsyncError) -> Self {
+                Self::Actor(e)
+            }
+        }
+
+        impl From<u8> for FlattenError {
+            fn from(e: u8) -> Self {
+                Self::Handler(e)
+            }
+        }
+
+        let addr = FlattenActor.start();
+
+        let res = addr.send(Fallible(true)).flatten::<FlattenError>().await;
+        assert_eq!(res, Ok(996));
+
+        let res = addr.send(Fallible(false)).flatten::<FlattenError>().await;
+        assert_eq!(res, Err(FlattenError::Handler(251)));
+
+        addr.stop(true).await.unwrap();
+        let res = addr.send(Fallible(true)).flatten::<FlattenError>().await;
+        assert_eq!(res, Err(FlattenError::Actor(ActixAsyncError::Closed)));
+    }
+
+    #[actix_async::test]
+    async fn response_handle() {
+        use crate::request::ResponseHandle;
+
+        #[derive(Default)]
+        struct DeferActor(RefCell<Option<ResponseHandle<usize>>>);
+        actor!(DeferActor);
+
+        struct Ask;
+        message!(Ask, usize);
+
+        struct Reply(usize);
+        message!(Reply, ());
+
+        #[actix_async::handler]
+        impl Handler<Ask> for DeferActor {
+            async fn handle(&self, _: Ask, ctx: Context<'_, Self>) -> usize {
+                assert!(ctx.response_handle::<Reply>().is_none());
+                if let Some(handle) = ctx.response_handle::<Ask>() {
+                    assert!(ctx.response_handle::<Ask>().is_none());
+                    *self.0.borrow_mut() = Some(handle);
+                }
+                0
+            }
+        }
+
+        #[actix_async::handler]
+        impl Handler<Reply> for DeferActor {
+            async fn handle(&self, msg: Reply, _: Context<'_, Self>) {
+                if let Some(handle) = self.0.borrow_mut().take() {
+                    if msg.0 > 0 {
+                        handle.send(msg.0).unwrap();
+                    }
+                }
+            }
+        }
+
+        let addr = DeferActor::default().start();
+
+        let req = addr.send(Ask);
+        let reply = async {
+            sleep(Duration::from_millis(50)).await;
+            addr.send(Reply(996)).await.unwrap();
+        };
+        let (res, _) = futures_util::future::join(req, reply).await;
+        assert_eq!(res, Ok(996));
+
+        // dropped handle closes the response.
+        let req = addr.send(Ask);
+        let reply = async {
+            sleep(Duration::from_millis(50)).await;
+            addr.send(Reply(0)).await.unwrap();
+        };
+        let (res, _) = futures_util::future::join(req, reply).await;
+        assert_eq!(res, Err(ActixAsyncError::Closed));
+
+        // message without caller has no handle.
+        addr.do_send(Ask);
+        addr.send(Reply(0)).await.unwrap();
+        assert!(addr
+            .run(|act, _| Box::pin(async move { act.0.borrow().is_none() }))
+            .await
+            .unwrap());
+    }
+
+    #[actix_async::test]
+    async fn recipient_set() {
+        use crate::address::RecipientSet;
+
+        #[derive(Clone)]
+        struct Ping;
+        message!(Ping, usize);
+
+        #[actix_async::handler]
+        impl Handler<Ping> for TestActor {
+            async fn handle(&self, _: Ping, _: Context<'_, Self>) -> usize {
+                self.0
+            }
+        }
+
+        let addr = TestActor::default().start();
+        let addr2 = TestActor::default().start();
+
+        let mut set = RecipientSet::<TokioRuntime, Ping>::new();
+        assert!(set.insert(&addr));
+        assert!(!set.insert(addr.downgrade()));
+        assert!(set.insert(&addr2));
+        assert_eq!(set.len(), 2);
+
+        let res = set.send_all(Ping).await;
+        assert_eq!(res, vec![Ok(996), Ok(996)]);
+
+        // stopped actor is pruned on broadcast.
+        addr.stop(true).await.unwrap();
+        set.broadcast(Ping);
+        assert_eq!(set.len(), 1);
+
+        assert!(set.remove(&addr2.recipient_weak()));
+        assert!(set.is_empty());
+        assert!(set.send_all(Ping).await.is_empty());
+    }
+
+    #[actix_async::test]
+    async fn spawn() {
+        struct DropGuard(Arc<AtomicUsize>);
+
+        impl Drop for DropGuard {
+            fn drop(&mut self) {
+                self.0.fetch_add(100, Ordering::SeqCst);
+            }
+        }
+
+        let addr = TestActor::default().start();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let c = count.clone();
+        let (handle, handle2) = addr
+            .run(move |_, ctx| {
+                Box::pin(async move {
+                    let c1 = c.clone();
+                    let handle = ctx.spawn(async move {
+                        sleep(Duration::from_millis(50)).await;
+                        c1.fetch_add(1, Ordering::SeqCst);
+                    });
+                    let handle2 = ctx.spawn(async move {
+                        let _guard = DropGuard(c);
+                        sleep(Duration::from_secs(10)).await;
+                    });
+                    (handle, handle2)
+                })
+            })
+            .await
+            .unwrap();
+
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(handle.is_terminated());
+        assert!(!handle2.is_terminated());
+
+        // spawned future is dropped when actor stopped.
+        let watch = addr.watch();
+        addr.stop(true).await.unwrap();
+        watch.await;
+        assert_eq!(count.load(Ordering::SeqCst), 101);
+        assert!(handle2.is_terminated());
+    }
+
+    #[actix_async::test]
+    async fn ctx_wait() {
+        let addr = TestActor::default().start();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let c = count.clone();
+        addr.run(move |_, ctx| {
+            Box::pin(async move {
+                ctx.wait(async move {
+                    sleep(Duration::from_millis(50)).await;
+                    c.fetch_add(1, Ordering::SeqCst);
+                });
+            })
+        })
+        .await
+        .unwrap();
+
+        // message sent after the wait is handled when the future is resolved.
+        let c = count.clone();
+        let res = addr
+            .run(move |_, _| Box::pin(async move { c.load(Ordering::SeqCst) }))
+            .await
+            .unwrap();
+        assert_eq!(res, 1);
+
+        // wait from concurrent task blocks new message while other concurrent task is pending.
+        let addr2 = addr.clone();
+        tokio::task::spawn_local(async move {
+            let _ = addr2
+                .run(|_, _| Box::pin(sleep(Duration::from_millis(100))))
+                .await;
+        });
+
+        let c = count.clone();
+        let addr2 = addr.clone();
+        tokio::task::spawn_local(async move {
+            let _ = addr2
+                .run(move |_, ctx| {
+                    Box::pin(async move {
+                        sleep(Duration::from_millis(10)).await;
+                        ctx.wait(async move {
+                            c.fetch_add(1, Ordering::SeqCst);
+                        });
+                    })
+                })
+                .await;
+        });
+
+        sleep(Duration::from_millis(30)).await;
+
+        let c = count.clone();
+        let res = addr
+            .run(move |_, _| Box::pin(async move { c.load(Ordering::SeqCst) }))
+            .await
+            .unwrap();
+        assert_eq!(res, 2);
+    }
+
+    #[actix_async::test]
+    async fn run_interval_mut() {
+        let addr = TestActor::default().start();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let c = count.clone();
+        let handle = addr
+            .run(move |_, ctx| {
+                Box::pin(async move {
+                    let mut ticks = 0;
+                    ctx.run_wait_interval_mut(Duration::from_millis(20), move |_, _| {
+                        // closure state is kept across ticks.
+                        ticks += 1;
+                        c.store(ticks, Ordering::SeqCst);
+                        Box::pin(async {})
+                    })
+                })
+            })
+            .await
+            .unwrap();
+
+        sleep(Duration::from_millis(110)).await;
+        handle.cancel();
+        let ticks = count.load(Ordering::SeqCst);
+        assert!(ticks >= 3);
+
+        sleep(Duration::from_millis(60)).await;
+        assert_eq!(count.load(Ordering::SeqCst), ticks);
+    }
+
+    #[actix_async::test]
+    async fn interval_break() {
+        use core::ops::ControlFlow;
+
+        let addr = TestActor::default().start();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let c = count.clone();
+        let handle = addr
+            .run(move |_, ctx| {
+                Box::pin(async move {
+                    ctx.run_interval_mut(Duration::from_millis(20), move |_, _| {
+                        let c = c.clone();
+                        Box::pin(async move {
+                            // interval cancel itself on the third tick.
+                            if c.fetch_add(1, Ordering::SeqCst) == 2 {
+                                ControlFlow::Break(())
+                            } else {
+                                ControlFlow::Continue(())
+                            }
+                        })
+                    })
+                })
+            })
+            .await
+            .unwrap();
+
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+        assert!(handle.is_terminated());
+
+        // cloned closure of run_wait_interval can break as well.
+        count.store(0, Ordering::SeqCst);
+        let c = count.clone();
+        let handle = addr
+            .run(move |_, ctx| {
+                Box::pin(async move {
+                    ctx.run_wait_interval(Duration::from_millis(20), move |_, _| {
+                        Box::pin(async move {
+                            if c.fetch_add(1, Ordering::SeqCst) == 1 {
+                                ControlFlow::Break(())
+                            } else {
+                                ControlFlow::Continue(())
+                            }
+                        })
+                    })
+                })
+            })
+            .await
+            .unwrap();
+
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert!(handle.is_terminated());
+    }
+
+    #[cfg(feature = "cron")]
+    #[actix_async::test]
+    async fn run_cron() {
+        use core::ops::ControlFlow;
+
+        let addr = TestActor::default().start();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let c = count.clone();
+        let handle = addr
+            .run(move |_, ctx| {
+                Box::pin(async move {
+                    assert!(ctx.run_cron("invalid", |_, _| Box::pin(async {})).is_err());
+
+                    ctx.run_cron("* * * * * *", move |_, _| {
+                        let ticks = c.fetch_add(1, Ordering::SeqCst) + 1;
+                        Box::pin(async move {
+                            if ticks == 2 {
+                                ControlFlow::Break(())
+                            } else {
+                                ControlFlow::Continue(())
+                            }
+                        })
+                    })
+                    .unwrap()
+                })
+            })
+            .await
+            .unwrap();
+
+        sleep(Duration::from_millis(3100)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert!(handle.is_terminated());
+    }
+
+    #[actix_async::test]
+    async fn pause_interval() {
+        let addr = TestActor::default().start();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let c = count.clone();
+        let (handle, later) = addr
+            .run(move |_, ctx| {
+                Box::pin(async move {
+                    let c1 = c.clone();
+                    let handle = ctx.run_interval_mut(Duration::from_millis(20), move |_, _| {
+                        c1.fetch_add(1, Ordering::SeqCst);
+                        Box::pin(async {})
+                    });
+                    let later = ctx.run_later(Duration::from_millis(50), move |_, _| {
+                        c.fetch_add(100, Ordering::SeqCst);
+                        Box::pin(async {})
+                    });
+                    (handle, later)
+                })
+            })
+            .await
+            .unwrap();
+
+        handle.pause();
+        later.pause();
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        // delayed task runs right away and interval restarts on resume.
+        later.resume();
+        handle.resume();
+        sleep(Duration::from_millis(10)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 100);
+
+        sleep(Duration::from_millis(60)).await;
+        assert!(count.load(Ordering::SeqCst) > 100);
+        assert!(later.is_terminated());
+        assert!(!handle.is_terminated());
+    }
+
+    #[actix_async::test]
+    async fn cancel_all() {
+        let addr = TestActor::default().start();
+
+        let (later, stream, spawned) = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    let later = ctx.run_later(Duration::from_secs(1), |_, _| Box::pin(async {}));
+                    ctx.run_interval_mut(Duration::from_secs(1), |_, _| Box::pin(async {}));
+                    let stream = ctx.add_stream(futures_util::stream::pending::<TestMsg>());
+                    let spawned = ctx.spawn(futures_util::future::pending());
+                    (later, stream, spawned)
+                })
+            })
+            .await
+            .unwrap();
+
+        let counts =
+            || addr.run(|_, ctx| Box::pin(async move { (ctx.timer_count(), ctx.stream_count()) }));
+
+        assert_eq!(counts().await.unwrap(), (2, 1));
+
+        addr.run(|_, ctx| Box::pin(async move { ctx.cancel_all_streams() }))
+            .await
+            .unwrap();
+        assert_eq!(counts().await.unwrap(), (2, 0));
+        assert!(stream.is_terminated());
+
+        addr.run(|_, ctx| Box::pin(async move { ctx.cancel_all_timers() }))
+            .await
+            .unwrap();
+        assert_eq!(counts().await.unwrap(), (0, 0));
+        assert!(later.is_terminated());
+
+        // spawned future is not affected.
+        assert!(!spawned.is_terminated());
+    }
+
+    #[actix_async::test]
+    async fn add_stream_map() {
+        struct SumActor(Arc<AtomicUsize>);
+        actor!(SumActor);
+
+        struct Add(usize);
+        message!(Add, ());
+
+        #[actix_async::handler]
+        impl Handler<Add> for SumActor {
+            async fn handle(&self, msg: Add, _: Context<'_, Self>) {
+                self.0.fetch_add(msg.0, Ordering::SeqCst);
+            }
+        }
+
+        let sum = Arc::new(AtomicUsize::new(0));
+        let s = sum.clone();
+        let _addr = SumActor::create(move |ctx| {
+            let mut seen = 0;
+            ctx.add_stream_map(futures_util::stream::iter(1..=4usize), move |item| {
+                seen += 1;
+                Add(item * seen)
+            });
+            SumActor(s)
+        });
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(sum.load(Ordering::SeqCst), 1 + 4 + 9 + 16);
+    }
+
+    #[actix_async::test]
+    async fn add_stream_limited() {
+        #[derive(Default)]
+        struct LimitActor {
+            running: AtomicUsize,
+            max: AtomicUsize,
+            done: Arc<AtomicUsize>,
+        }
+        actor!(LimitActor);
+
+        struct Job;
+        message!(Job, ());
+
+        #[actix_async::handler]
+        impl Handler<Job> for LimitActor {
+            async fn handle(&self, _: Job, _: Context<'_, Self>) {
+                let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+                self.max.fetch_max(running, Ordering::SeqCst);
+                sleep(Duration::from_millis(10)).await;
+                self.running.fetch_sub(1, Ordering::SeqCst);
+                self.done.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let done = Arc::new(AtomicUsize::new(0));
+        let d = done.clone();
+        let addr = LimitActor::create(move |ctx| {
+            ctx.add_stream_limited(futures_util::stream::iter((0..10).map(|_| Job)), 2);
+            LimitActor {
+                done: d,
+                ..Default::default()
+            }
+        });
+
+        sleep(Duration::from_millis(150)).await;
+        assert_eq!(done.load(Ordering::SeqCst), 10);
+
+        let max = addr
+            .run(|act, _| {
+                let max = act.max.load(Ordering::SeqCst);
+                Box::pin(async move { max })
+            })
+            .await
+            .unwrap();
+        assert_eq!(max, 2);
+    }
+
+    #[actix_async::test]
+    async fn add_sink() {
+        use crate::io::{SinkHandle, WriteHandler};
+
+        struct SinkActor {
+            sink: SinkHandle<usize>,
+            events: Arc<std::sync::Mutex<Vec<&'static str>>>,
+        }
+        actor!(SinkActor);
+
+        impl WriteHandler<&'static str> for SinkActor {
+            async fn error(&self, err: &'static str, _: Context<'_, Self>) {
+                self.events.lock().unwrap().push(err);
+            }
+
+            async fn finished(&self, _: Context<'_, Self>) {
+                self.events.lock().unwrap().push("finished");
+            }
+        }
+
+        let written = Arc::new(AtomicUsize::new(0));
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let w = written.clone();
+        let e = events.clone();
+        let addr = SinkActor::create(move |ctx| {
+            let sink = futures_util::sink::unfold(w, |w, item: usize| async move {
+                if item == 0 {
+                    return Err("bad item");
+                }
+                w.fetch_add(item, Ordering::SeqCst);
+                Ok(w)
+            });
+            SinkActor {
+                sink: ctx.add_sink_with_capacity(Box::pin(sink), 2),
+                events: e,
+            }
+        });
+
+        addr.run(|act, _| {
+            Box::pin(async move {
+                assert!(act.sink.try_write(1).is_ok());
+                assert!(act.sink.try_write(2).is_ok());
+                // buffer is full.
+                assert_eq!(act.sink.try_write(3), Err(3));
+                act.sink.write(3).await.unwrap();
+            })
+        })
+        .await
+        .unwrap();
+
+        sleep(Duration::from_millis(20)).await;
+        assert_eq!(written.load(Ordering::SeqCst), 6);
+
+        // write to full buffer in exclusive task does not wait for context.
+        addr.run_wait(|act, _| {
+            Box::pin(async move {
+                for i in 1..=4 {
+                    act.sink.write(i).await.unwrap();
+                }
+            })
+        })
+        .timeout(Duration::from_secs(1))
+        .await
+        .unwrap();
+
+        sleep(Duration::from_millis(20)).await;
+        assert_eq!(written.load(Ordering::SeqCst), 16);
+
+        // error closes the sink.
+        let closed = addr
+            .run(|act, _| {
+                Box::pin(async move {
+                    act.sink.try_write(0).unwrap();
+                    sleep(Duration::from_millis(20)).await;
+                    act.sink.is_closed()
+                })
+            })
+            .await
+            .unwrap();
+        assert!(closed);
+        assert_eq!(*events.lock().unwrap(), vec!["bad item"]);
+
+        // sink is closed when handle is closed.
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let e = events.clone();
+        let addr = SinkActor::create(move |ctx| SinkActor {
+            sink: ctx.add_sink(futures_util::SinkExt::sink_map_err(
+                futures_util::sink::drain(),
+                |_| "",
+            )),
+            events: e,
+        });
+        addr.run(|act, _| Box::pin(async move { act.sink.close() }))
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(20)).await;
+        assert_eq!(*events.lock().unwrap(), vec!["finished"]);
+    }
+
+    #[cfg(feature = "framed")]
+    #[actix_async::test]
+    async fn add_framed() {
+        use crate::framed::Frame;
+        use crate::io::{SinkHandle, WriteHandler};
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        use tokio_util::codec::{LinesCodec, LinesCodecError};
+
+        struct Echo {
+            sink: SinkHandle<String>,
+            closed: Arc<AtomicUsize>,
+        }
+        actor!(Echo);
+
+        impl WriteHandler<LinesCodecError> for Echo {}
+
+        #[actix_async::handler]
+        impl Handler<Frame<String, LinesCodecError>> for Echo {
+            async fn handle(&self, msg: Frame<String, LinesCodecError>, ctx: Context<'_, Self>) {
+                match msg {
+                    Frame::Item(line) => self.sink.write(line.to_uppercase()).await.unwrap(),
+                    Frame::Error(_) => ctx.stop(),
+                    Frame::Closed => {
+                        self.closed.fetch_add(1, Ordering::SeqCst);
+                        ctx.stop();
+                    }
+                }
+            }
+        }
+
+        let (client, server) = tokio::io::duplex(64);
+        let closed = Arc::new(AtomicUsize::new(0));
+        let c = closed.clone();
+        let addr = Echo::create(move |ctx| Echo {
+            sink: ctx.add_framed(server, LinesCodec::new()),
+            closed: c,
+        });
+
+        let (read, mut write) = tokio::io::split(client);
+        let mut lines = BufReader::new(read).lines();
+
+        write.write_all(b"hello\nworld\n").await.unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "HELLO");
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "WORLD");
+
+        // closing client end stops the actor.
+        write.shutdown().await.unwrap();
+        addr.watch().await;
+        assert_eq!(closed.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "framed")]
+    #[actix_async::test]
+    async fn serve_tcp() {
+        use crate::framed::{serve_tcp, Frame};
+        use crate::io::{SinkHandle, WriteHandler};
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        use tokio::net::{TcpListener, TcpStream};
+        use tokio_util::codec::{LinesCodec, LinesCodecError};
+
+        struct Session {
+            sink: SinkHandle<String>,
+        }
+        actor!(Session);
+
+        impl WriteHandler<LinesCodecError> for Session {}
+
+        #[actix_async::handler]
+        impl Handler<Frame<String, LinesCodecError>> for Session {
+            async fn handle(&self, msg: Frame<String, LinesCodecError>, ctx: Context<'_, Self>) {
+                match msg {
+                    Frame::Item(line) => self.sink.write(line).await.unwrap(),
+                    Frame::Error(_) | Frame::Closed => ctx.stop(),
+                }
+            }
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local = listener.local_addr().unwrap();
+
+        let server = tokio::task::spawn_local(serve_tcp(listener, |stream, _, ctx| Session {
+            sink: ctx.add_framed(stream, LinesCodec::new()),
+        }));
+
+        let (read, mut write) = TcpStream::connect(local).await.unwrap().into_split();
+        let mut lines = BufReader::new(read).lines();
+
+        write.write_all(b"hello\n").await.unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "hello");
+
+        // session is not tied to the accept loop.
+        server.abort();
+        let _ = server.await;
+
+        write.write_all(b"world\n").await.unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), "world");
+    }
+
+    #[actix_async::test]
+    async fn run_blocking() {
+        struct BlockingActor(Arc<AtomicUsize>);
+        actor!(BlockingActor);
+
+        struct Output(std::thread::ThreadId);
+        message!(Output, ());
+
+        #[actix_async::handler]
+        impl Handler<Output> for BlockingActor {
+            async fn handle(&self, msg: Output, _: Context<'_, Self>) {
+                assert_ne!(msg.0, std::thread::current().id());
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let c = count.clone();
+        let addr = BlockingActor::create(move |ctx| {
+            ctx.run_blocking(|| {
+                std::thread::sleep(Duration::from_millis(50));
+                Output(std::thread::current().id())
+            });
+            BlockingActor(c)
+        });
+
+        // actor is not blocked by the closure.
+        let res = addr.run(|act, _| {
+            let count = act.0.load(Ordering::SeqCst);
+            Box::pin(async move { count })
+        });
+        assert_eq!(res.await.unwrap(), 0);
+
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[actix_async::test]
+    async fn emit_subscribe() {
+        struct EventActor;
+        actor!(EventActor);
+
+        #[derive(Clone, Debug, PartialEq)]
+        struct Event(usize);
+
+        let addr = EventActor.start();
+
+        let mut events = addr.subscribe::<Event>();
+        let other = addr.subscribe::<usize>();
+
+        let emitted = addr.run(|_, ctx| {
+            let count = ctx.emit(Event(1)) + ctx.emit(Event(2));
+            Box::pin(async move { count })
+        });
+        assert_eq!(emitted.await.unwrap(), 2);
+
+        assert_eq!(events.next().await, Some(Event(1)));
+        assert_eq!(events.next().await, Some(Event(2)));
+        assert!(other.is_empty());
+
+        // dropped subscriber does not receive event.
+        drop(other);
+        let emitted = addr.run(|_, ctx| {
+            let count = ctx.emit(996usize);
+            Box::pin(async move { count })
+        });
+        assert_eq!(emitted.await.unwrap(), 0);
+
+        let _ = addr.stop(true).await;
+        assert_eq!(events.next().await, None);
+        assert_eq!(addr.subscribe::<Event>().next().await, None);
+    }
+
+    #[actix_async::test]
+    async fn handler_boxed() {
+        struct BoxedActor(usize);
+        actor!(BoxedActor);
+
+        struct Count;
+        message!(Count, usize);
+
+        impl HandlerBoxed<Count> for BoxedActor {
+            fn handle<'act, 'ctx, 'res>(
+                &'act self,
+                _: Count,
+                _: Context<'ctx, Self>,
+            ) -> LocalBoxFuture<'res, usize>
+            where
+                'act: 'res,
+                'ctx: 'res,
+            {
+                Box::pin(async move { self.0 })
+            }
+
+            fn handle_wait<'act, 'ctx, 'res>(
+                &'act mut self,
+                _: Count,
+                _: Context<'ctx, Self>,
+            ) -> LocalBoxFuture<'res, usize>
+            where
+                'act: 'res,
+                'ctx: 'res,
+            {
+                Box::pin(async move {
+                    self.0 += 1;
+                    self.0
+                })
+            }
+        }
+
+        handler_boxed!(BoxedActor, Count);
+
+        let addr = BoxedActor(0).start();
+        assert_eq!(addr.wait(Count).await.unwrap(), 1);
+        assert_eq!(addr.send(Count).await.unwrap(), 1);
+    }
+
+    #[actix_async::test]
+    async fn handler_timeout() {
+        struct TimeoutActor;
+
+        impl Actor for TimeoutActor {
+            type Runtime = TokioRuntime;
+
+            fn handler_timeout() -> Option<Duration> {
+                Some(Duration::from_millis(50))
+            }
+        }
+
+        struct Stuck;
+        message!(Stuck, ());
+
+        #[actix_async::handler]
+        impl Handler<Stuck> for TimeoutActor {
+            async fn handle(&self, _: Stuck, _: Context<'_, Self>) {
+                core::future::pending::<()>().await;
+            }
+        }
+
+        struct Exclusive;
+        message!(Exclusive, usize);
+
+        #[actix_async::handler]
+        impl Handler<Exclusive> for TimeoutActor {
+            async fn handle(&self, _: Exclusive, _: Context<'_, Self>) -> usize {
+                251
+            }
+
+            async fn handle_wait(&mut self, _: Exclusive, _: Context<'_, Self>) -> usize {
+                996
+            }
+        }
+
+        let addr = TimeoutActor.start();
+
+        let (stuck, exclusive) =
+            futures_util::future::join(addr.send(Stuck), addr.wait(Exclusive)).await;
+
+        assert_eq!(stuck, Err(ActixAsyncError::HandlerTimeout));
+        // exclusive message is handled after the stuck task is aborted.
+        assert_eq!(exclusive, Ok(996));
+    }
+
+    #[actix_async::test]
+    async fn stop_with() {
+        struct StopActor(Arc<AtomicUsize>);
+
+        impl Actor for StopActor {
+            type Runtime = TokioRuntime;
+
+            async fn on_stop(&mut self, reason: StopReason, _: Context<'_, Self>) {
+                if reason == StopReason::Failed {
+                    self.0.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let addr = StopActor(count.clone()).start();
+        let watch = addr.watch();
+
+        let _ = addr
+            .run(|_, ctx| {
+                ctx.stop_with(StopReason::Failed);
+                // the first reason is kept.
+                ctx.stop_with(StopReason::Finished);
+                Box::pin(async {})
+            })
+            .await;
+
+        assert_eq!(watch.await, StopReason::Failed);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        // typed custom reason.
+        #[derive(Debug, PartialEq)]
+        struct JobError(usize);
+
+        let addr = StopActor(count.clone()).start();
+        let watch = addr.watch();
+
+        let _ = addr
+            .run(|_, ctx| {
+                ctx.stop_with(StopReason::custom(JobError(996)));
+                Box::pin(async {})
+            })
+            .await;
+
+        match watch.await {
+            StopReason::Custom(reason) => {
+                assert!(!reason.is::<usize>());
+                assert_eq!(reason.downcast_ref::<JobError>(), Some(&JobError(996)));
+                let reason = StopReason::Custom(reason);
+                assert_ne!(reason, reason.clone());
+            }
+            reason => panic!("unexpected stop reason: {:?}", reason),
+        }
+    }
+
+    #[actix_async::test]
+    async fn scheduled_tasks() {
+        use crate::context::ScheduledKind;
+
+        struct ScheduleActor;
+        actor!(ScheduleActor);
+
+        struct Tick;
+        message!(Tick, ());
+
+        #[actix_async::handler]
+        impl Handler<Tick> for ScheduleActor {
+            async fn handle(&self, _: Tick, _: Context<'_, Self>) {}
+        }
+
+        let addr = ScheduleActor.start();
+
+        let kinds = addr.run(|_, ctx| {
+            let handle = ctx.run_later(Duration::from_secs(1), |_, _| Box::pin(async {}));
+            handle.pause();
+            ctx.run_interval(Duration::from_secs(1), |_, _| Box::pin(async {}));
+            ctx.add_stream(futures_util::stream::pending::<Tick>());
+            ctx.add_stream(futures_util::stream::pending::<Tick>())
+                .pause();
+            let mut kinds = ctx
+                .scheduled_tasks()
+                .into_iter()
+                .map(|task| (task.kind(), task.is_paused(), task.remaining().is_some()))
+                .collect::<alloc::vec::Vec<_>>();
+            kinds.sort_by_key(|(kind, paused, _)| (*kind as usize, *paused));
+            Box::pin(async move { kinds })
+        });
+
+        assert_eq!(
+            kinds.await.unwrap(),
+            [
+                (ScheduledKind::Later, true, true),
+                (ScheduledKind::Interval, false, true),
+                (ScheduledKind::Stream, false, false),
+                (ScheduledKind::Stream, true, false),
+            ]
+        );
+    }
+
+    #[actix_async::test]
+    async fn missed_tick_behavior() {
+        use crate::runtime::{MissedTickBehavior, SleepInterval};
+
+        let period = Duration::from_millis(10);
+        let deadline = std::time::Instant::now();
+        let now = deadline + Duration::from_millis(35);
+
+        let next = |missed: MissedTickBehavior| missed.next_deadline(deadline, now, period);
+        assert_eq!(next(MissedTickBehavior::Burst), deadline + period);
+        assert_eq!(next(MissedTickBehavior::Delay), now + period);
+        assert_eq!(
+            next(MissedTickBehavior::Skip),
+            deadline + Duration::from_millis(40)
+        );
+
+        // runtime without native interval.
+        struct SleepRuntime;
+
+        impl RuntimeService for SleepRuntime {
+            type Sleep = tokio::time::Sleep;
+            type Interval = SleepInterval<Self>;
+
+            fn spawn<F: core::future::Future<Output = ()> + 'static>(f: F) {
+                tokio::task::spawn_local(f);
+            }
+
+            fn sleep(dur: Duration) -> Self::Sleep {
+                tokio::time::sleep(dur)
+            }
+        }
+
+        struct SleepActor;
+
+        impl Actor for SleepActor {
+            type Runtime = SleepRuntime;
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let c = count.clone();
+        let addr = SleepActor::create(move |ctx| {
+            ctx.run_interval(Duration::from_millis(20), move |_, _| {
+                c.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async {})
+            });
+            SleepActor
+        });
+
+        // wait for ticks instead of counting them in a fixed window.
+        let start = Instant::now();
+        while count.load(Ordering::SeqCst) < 3 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            sleep(Duration::from_millis(5)).await;
+        }
+        // sleep interval must not tick faster than it's period.
+        let ticks = count.load(Ordering::SeqCst) as u128;
+        assert!(ticks <= start.elapsed().as_millis() / 20 + 1);
+
+        let _ = addr.stop(true).await;
+    }
+
+    #[actix_async::test]
+    async fn derive_message() {
+        struct DeriveActor;
+        actor!(DeriveActor);
+
+        #[derive(Message)]
+        #[rtype(result = "usize")]
+        struct Len(&'static str);
+
+        #[derive(Message)]
+        #[rtype(Vec<T>)]
+        struct Repeat<T: Clone>(T, usize);
+
+        #[derive(Message)]
+        struct Unit;
+
+        impl Handler<Len> for DeriveActor {
+            async fn handle(&self, msg: Len, _: Context<'_, Self>) -> usize {
+                msg.0.len()
+            }
+        }
+
+        impl<T: Clone + Send + 'static> Handler<Repeat<T>> for DeriveActor {
+            async fn handle(&self, msg: Repeat<T>, _: Context<'_, Self>) -> Vec<T> {
+                alloc::vec![msg.0; msg.1]
+            }
+        }
+
+        impl Handler<Unit> for DeriveActor {
+            async fn handle(&self, _: Unit, _: Context<'_, Self>) {}
+        }
+
+        let addr = DeriveActor.start();
+
+        assert_eq!(addr.send(Len("996")).await.unwrap(), 3);
+        assert_eq!(addr.send(Repeat(251u8, 2)).await.unwrap(), [251, 251]);
+        addr.send(Unit).await.unwrap();
+    }
+
+    #[actix_async::test]
+    async fn handler_inherent() {
+        use core::cell::Cell;
+
+        struct InherentActor(Cell<usize>);
+        actor!(InherentActor);
+
+        struct Get;
+        message!(Get, usize);
+
+        struct Set(usize);
+        message!(Set, ());
+
+        struct Reset;
+        message!(Reset, ());
+
+        #[actix_async::handler]
+        impl InherentActor {
+            #[handle]
+            async fn get(&self, _: Get, _: Context<'_, Self>) -> usize {
+                self.0.get()
+            }
+
+            #[handle]
+            async fn get_mut(&mut self, _: Get, _: Context<'_, Self>) -> usize {
+                self.0.get() + 1
+            }
+
+            #[handle]
+            async fn set(&self, msg: Set, _: Context<'_, Self>) {
+                self.0.set(msg.0);
+            }
+
+            #[handle(exclusive)]
+            async fn reset(&mut self, _: Reset, _: Context<'_, Self>) {
+                self.0 = Cell::new(0);
+            }
+
+            // not a handler.
+            fn value(&self) -> usize {
+                self.0.get()
+            }
+
+            // not a handler without marker.
+            #[allow(dead_code)]
+            async fn helper(&self, _: Set, _: Context<'_, Self>) {}
+        }
+
+        let addr = InherentActor(Cell::new(0)).start();
+
+        addr.wait(Set(996)).await.unwrap();
+        assert_eq!(addr.send(Get).await.unwrap(), 996);
+        assert_eq!(addr.wait(Get).await.unwrap(), 997);
+
+        addr.send(Set(251)).await.unwrap();
+
+        let res = addr.run(|act, _| {
+            let value = act.value();
+            Box::pin(async move { value })
+        });
+        assert_eq!(res.await.unwrap(), 251);
+
+        addr.wait(Reset).await.unwrap();
+        assert_eq!(addr.send(Get).await.unwrap(), 0);
+    }
+
+    #[actix_async::test]
+    async fn message_generic() {
+        use core::marker::PhantomData;
+
+        struct GenericActor;
+        actor!(GenericActor);
+
+        struct Repeat<T>(T, usize);
+        message!(impl<T> Repeat<T>, Vec<T> where T: Clone + Send);
+
+        struct Parse<T, E>(&'static str, PhantomData<(T, E)>);
+        message!(impl<T, E,> Parse<T, E>, Result<T, E>);
+
+        impl<T: Clone + Send + 'static> Handler<Repeat<T>> for GenericActor {
+            async fn handle(&self, msg: Repeat<T>, _: Context<'_, Self>) -> Vec<T> {
+                alloc::vec![msg.0; msg.1]
+            }
+        }
+
+        impl<T, E> Handler<Parse<T, E>> for GenericActor
+        where
+            T: core::str::FromStr<Err = E> + Send + 'static,
+            E: Send + 'static,
+        {
+            async fn handle(&self, msg: Parse<T, E>, _: Context<'_, Self>) -> Result<T, E> {
+                msg.0.parse()
+            }
+        }
+
+        let addr = GenericActor.start();
+
+        assert_eq!(addr.send(Repeat(996u32, 2)).await.unwrap(), [996, 996]);
+        assert_eq!(
+            addr.send(Parse::<u8, _>("251", PhantomData)).await.unwrap(),
+            Ok(251)
+        );
+        assert!(addr
+            .send(Parse::<u8, _>("996", PhantomData))
+            .await
+            .unwrap()
+            .is_err());
+
+        // concrete type params are not generic params.
+        struct Wrap<T>(T);
+        message!(Wrap<u32>, bool);
+        message!(Wrap<String>, usize);
+
+        impl Handler<Wrap<u32>> for GenericActor {
+            async fn handle(&self, msg: Wrap<u32>, _: Context<'_, Self>) -> bool {
+                msg.0 == 996
+            }
+        }
+
+        impl Handler<Wrap<String>> for GenericActor {
+            async fn handle(&self, msg: Wrap<String>, _: Context<'_, Self>) -> usize {
+                msg.0.len()
+            }
+        }
+
+        assert!(addr.send(Wrap(996u32)).await.unwrap());
+        assert_eq!(addr.send(Wrap(String::from("251"))).await.unwrap(), 3);
+
+        let recipient = addr.recipient::<Repeat<&'static str>>();
+        assert_eq!(recipient.send(Repeat("a", 1)).await.unwrap(), ["a"]);
+    }
+
+    #[actix_async::test]
+    async fn recipient_eq() {
+        let addr = TestActor::default().start();
+        let addr2 = TestActor::default().start();
+
+        let mut set = std::collections::HashSet::new();
+        assert!(set.insert(addr.recipient::<TestMsg>()));
+        assert!(!set.insert(addr.clone().recipient::<TestMsg>()));
+        assert!(set.insert(addr2.recipient::<TestMsg>()));
+        assert_eq!(set.len(), 2);
+
+        assert!(set.remove(&addr.recipient::<TestMsg>()));
+        assert_eq!(set.len(), 1);
+
+        assert!(addr2.recipient_weak::<TestMsg>() == addr2.recipient_weak::<TestMsg>());
+        assert!(addr.recipient_weak::<TestMsg>() != addr2.recipient_weak::<TestMsg>());
+    }
+
+    #[actix_async::test]
+    async fn blocking_send() {
+        let addr = TestActor::default().start();
+        let re = addr.recipient::<TestMsg>();
+
+        let res = tokio::task::spawn_blocking(move || {
+            let res = addr.blocking_send(TestMsg);
+            (res, re.blocking_send(TestMsg))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(res, (Ok(996), Ok(996)));
+    }
+
+    #[actix_async::test]
+    async fn delay() {
+        let addr = TestActor::default().start();
+
+        let res = addr.send(TestDelayMessage).await.unwrap();
+        drop(res);
+
+        sleep(Duration::from_millis(300)).await;
+        let res = addr.send(TestMsg).await.unwrap();
+        assert_eq!(996, res);
+
+        sleep(Duration::from_millis(300)).await;
+        let res = addr.send(TestMsg).await.unwrap();
+        assert_eq!(997, res);
+
+        let res = addr.send(TestDelayMessage).await.unwrap();
+
+        sleep(Duration::from_millis(400)).await;
+        res.cancel();
+
+        sleep(Duration::from_millis(300)).await;
+        let res = addr.send(TestMsg).await.unwrap();
+        assert_eq!(997, res);
+    }
+
+    struct TestStream {
+        interval: Interval,
+        state: Arc<AtomicUsize>,
+    }
+
+    impl futures_util::stream::Stream for TestStream {
+        type Item = TestMsg;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+            if this.interval.poll_tick(cx).is_pending() {
+                return Poll::Pending;
+            }
+            this.state.fetch_add(1, Ordering::SeqCst);
+            Poll::Ready(Some(TestMsg))
+        }
+    }
+
+    #[actix_async::test]
+    async fn stream() {
+        let addr = TestActor::default().start();
+
+        let state = Arc::new(AtomicUsize::new(0));
+
+        let handle = addr
+            .run_wait({
+                let state = state.clone();
+                move |_, ctx| {
+                    Box::pin(async move {
+                        ctx.add_stream(TestStream {
+                            interval: interval(Duration::from_millis(500)),
+                            state,
+                        })
+                    })
+                }
+            })
+            .await
+            .unwrap();
+
+        sleep(Duration::from_millis(750)).await;
+        handle.cancel();
+        sleep(Duration::from_millis(1000)).await;
+        assert_eq!(2, state.load(Ordering::SeqCst));
+    }
+
+    #[cfg(feature = "envelope")]
+    #[actix_async::test]
+    async fn envelope() {
+        struct EnvelopeActor;
+        actor!(EnvelopeActor);
+
+        struct EnvelopeMsg;
+        message!(EnvelopeMsg, Option<(usize, Option<usize>)>);
+
+        #[actix_async::handler]
+        impl Handler<EnvelopeMsg> for EnvelopeActor {
+            async fn handle(
+                &self,
+                _: EnvelopeMsg,
+                ctx: Context<'_, Self>,
+            ) -> Option<(usize, Option<usize>)> {
+                ctx.current_envelope().map(|e| (e.id(), e.sender()))
+            }
+        }
+
+        let addr = EnvelopeActor.start();
+        let addr2 = addr.clone();
+        assert_ne!(addr.id(), addr2.id());
+
+        let (id, sender) = addr.send(EnvelopeMsg).await.unwrap().unwrap();
+        assert_eq!(sender, Some(addr.id()));
+
+        let (id2, sender) = addr2.wait(EnvelopeMsg).await.unwrap().unwrap();
+        assert_eq!(sender, Some(addr2.id()));
+        assert_ne!(id, id2);
+
+        let res = addr
+            .run(|_, ctx| {
+                let res = ctx.current_envelope().map(|e| e.elapsed());
+                Box::pin(async move { res })
+            })
+            .await
+            .unwrap();
+        assert!(res.is_some());
+    }
+
+    #[actix_async::test]
+    async fn observer() {
+        #[derive(Default)]
+        struct Observer {
+            enqueue: AtomicUsize,
+            dequeue: AtomicUsize,
+            start: AtomicUsize,
+            end: AtomicUsize,
+        }
+
+        impl MailboxObserver for Observer {
+            fn on_enqueue(&self, type_name: &'static str) {
+                assert!(type_name.ends_with("TestMsg"));
+                self.enqueue.fetch_add(1, Ordering::SeqCst);
+            }
+
+            fn on_dequeue(&self, _: &'static str, _: Duration) {
+                self.dequeue.fetch_add(1, Ordering::SeqCst);
+            }
+
+            fn on_handle_start(&self, _: &'static str) {
+                self.start.fetch_add(1, Ordering::SeqCst);
+            }
+
+            fn on_handle_end(&self, _: &'static str, _: Duration) {
+                self.end.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let observer = Arc::new(Observer::default());
+
+        let addr = TestActor::builder()
+            .observer(observer.clone())
+            .start(TestActor::default());
+
+        assert_eq!(addr.send(TestMsg).await, Ok(996));
+        assert_eq!(addr.wait(TestMsg).await, Ok(251));
+
+        let addr = TestActor::builder()
+            .observer(observer.clone())
+            .start_local(TestActor::default());
+
+        assert_eq!(addr.send(TestMsg).await, Ok(996));
+
+        assert_eq!(observer.enqueue.load(Ordering::SeqCst), 3);
+        assert_eq!(observer.dequeue.load(Ordering::SeqCst), 3);
+        assert_eq!(observer.start.load(Ordering::SeqCst), 3);
+        assert_eq!(observer.end.load(Ordering::SeqCst), 3);
+    }
+
+    #[actix_async::test]
+    async fn add_channel() {
+        struct ChannelActor(Cell<usize>);
+        actor!(ChannelActor);
+
+        struct ChannelMsg(usize);
+        message!(ChannelMsg, ());
+
+        #[actix_async::handler]
+        impl Handler<ChannelMsg> for ChannelActor {
+            async fn handle(&self, msg: ChannelMsg, _: Context<'_, Self>) {
+                self.0.set(self.0.get() + msg.0);
+            }
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let addr = ChannelActor::create(|ctx| {
+            ctx.add_channel(rx);
+            ChannelActor(Cell::new(0))
+        });
+
+        std::thread::spawn(move || {
+            for i in 1..=4 {
+                tx.send(ChannelMsg(i)).unwrap();
+            }
+        });
+
+        sleep(Duration::from_millis(200)).await;
+
+        let res = addr
+            .run(|act, _| Box::pin(async move { act.0.get() }))
+            .await;
+        assert_eq!(res, Ok(10));
+    }
+
+    #[actix_async::test]
+    async fn stream_pause() {
+        let addr = TestActor::default().start();
+
+        let state = Arc::new(AtomicUsize::new(0));
+
+        let handle = addr
+            .run_wait({
+                let state = state.clone();
+                move |_, ctx| {
+                    Box::pin(async move {
+                        ctx.add_stream(TestStream {
+                            interval: interval(Duration::from_millis(500)),
+                            state,
+                        })
+                    })
+                }
+            })
+            .await
+            .unwrap();
+
+        sleep(Duration::from_millis(750)).await;
+        handle.pause();
+        assert!(handle.is_paused());
+        assert_eq!(2, state.load(Ordering::SeqCst));
+
+        sleep(Duration::from_millis(1000)).await;
+        assert_eq!(2, state.load(Ordering::SeqCst));
+
+        handle.resume();
+        sleep(Duration::from_millis(100)).await;
+        assert!(state.load(Ordering::SeqCst) > 2);
+
+        handle.cancel();
+    }
+
+    #[actix_async::test]
+    async fn capacity() {
+        let state = Rc::new(Cell::new(0));
+
+        let addr = TestCapActor(state.clone()).start();
+
+        let addr_clone = addr.clone();
+        tokio::task::spawn_local(async move {
+            let mut futs = futures_util::stream::FuturesUnordered::new();
+
+            for _ in 0..4 {
+                futs.push(addr_clone.send(TestCapMsg));
+            }
+
+            while futs.next().await.is_some() {}
+        });
+
+        let now = Instant::now();
+
+        tokio::task::yield_now().await;
+
+        assert_eq!(state.get(), 4);
+
+        addr.do_send(TestMsg);
+
+        let res = addr.send(TestMsg).await.unwrap();
+
+        assert_eq!(res, 5);
+        assert_eq!(state.get(), 6);
+        assert!(now.elapsed() > Duration::from_secs(3));
+    }
+
+    #[actix_async::test]
+    async fn panic_recovery() {
+        let supervisor = Supervisor::builder().workers(1).build();
+        let addr = supervisor
+            .start(1, |_| async { TestActor::default() })
+            .await;
+
+        let _ = addr.send(TestPanicMsg).await;
+        sleep(Duration::from_millis(1000)).await;
+        let res = addr.send(TestMsg).await;
+
+        assert_eq!(996, res.unwrap());
+    }
+
+    #[actix_async::test]
+    async fn panic_restart_payload() {
+        static PAYLOAD: AtomicUsize = AtomicUsize::new(0);
+
+        struct PayloadActor;
+
+        #[actix_async::handler]
+        impl Actor for PayloadActor {
+            type Runtime = TokioRuntime;
+
+            fn trap_panic() -> bool {
+                true
+            }
+
+            async fn on_panic(&mut self, _: &PanicInfo, _: Context<'_, Self>) -> PanicAction {
+                PanicAction::Restart
+            }
+
+            fn supervised(mut state: crate::supervisor::SupervisedState) -> ActorState {
+                // the original payload is resumed.
+                if let Some(e) = state.take_error() {
+                    if let Ok(payload) = e.into_panic().downcast::<usize>() {
+                        PAYLOAD.store(*payload, Ordering::SeqCst);
+                    }
+                }
+                ActorState::Stop
+            }
+        }
+
+        #[actix_async::handler]
+        impl Handler<TestPanicMsg> for PayloadActor {
+            async fn handle(&self, _: TestPanicMsg, _: Context<'_, Self>) {
+                std::panic::panic_any(996usize);
+            }
+        }
+
+        let supervisor = Supervisor::builder().workers(1).build();
+        let addr = supervisor.start(1, |_| async { PayloadActor }).await;
+
+        let _ = addr.send(TestPanicMsg).await;
+        sleep(Duration::from_millis(500)).await;
+        assert_eq!(PAYLOAD.load(Ordering::SeqCst), 996);
+    }
+
+    #[actix_async::test]
+    async fn panic_restart_limit() {
+        let escalated = Arc::new(AtomicUsize::new(0));
+        let escalated1 = escalated.clone();
+
+        // failures are escalated to parent supervisor.
+        let parent = Supervisor::builder()
+            .workers(1)
+            .on_escalate(move |escalation| {
+                escalated1.fetch_add(escalation.restarts(), Ordering::SeqCst);
+            })
+            .build();
+
+        let supervisor = Supervisor::builder()
+            .workers(1)
+            .max_restarts(1)
+            .restart_window(Duration::from_secs(60))
+            .parent(&parent)
+            .build();
+        let addr = supervisor
+            .start(1, |_| async { TestActor::default() })
+            .await;
+
+        let _ = addr.send(TestPanicMsg).await;
+        sleep(Duration::from_millis(500)).await;
+        assert_eq!(996, addr.send(TestMsg).await.unwrap());
+
+        // second panic within the window reaches the limit and actor stays stopped.
+        let _ = addr.send(TestPanicMsg).await;
+        sleep(Duration::from_millis(500)).await;
+        assert!(addr.send(TestMsg).await.is_err());
+        assert_eq!(escalated.load(Ordering::SeqCst), 1);
+    }
+
+    #[actix_async::test]
+    async fn trap_panic() {
+        struct TrapActor;
+
+        impl Actor for TrapActor {
+            type Runtime = TokioRuntime;
+
+            fn trap_panic() -> bool {
+                true
+            }
+        }
+
+        #[actix_async::handler]
+        impl Handler<TestPanicMsg> for TrapActor {
+            async fn handle(&self, _: TestPanicMsg, _: Context<'_, Self>) {
+                panic!("This is a purpose panic to test panic trap");
+            }
+        }
+
+        #[actix_async::handler]
+        impl Handler<TestMsg> for TrapActor {
+            async fn handle(&self, _: TestMsg, _: Context<'_, Self>) -> usize {
+                996
+            }
+        }
+
+        let addr = TrapActor.start();
+
+        assert!(addr.send(TestPanicMsg).await.is_err());
+        assert!(addr.wait(TestPanicMsg).await.is_err());
+
+        assert_eq!(996, addr.send(TestMsg).await.unwrap());
+        assert_eq!(996, addr.wait(TestMsg).await.unwrap());
+    }
+
+    #[actix_async::test]
+    async fn on_panic() {
+        struct PanicActor(Rc<Cell<usize>>);
+
+        #[actix_async::handler]
+        impl Actor for PanicActor {
+            type Runtime = TokioRuntime;
+
+            fn trap_panic() -> bool {
+                true
+            }
+
+            async fn on_panic(&mut self, info: &PanicInfo, _: Context<'_, Self>) -> PanicAction {
+                assert_eq!(
+                    info.message(),
+                    Some("This is a purpose panic to test on_panic")
+                );
+                self.0.set(self.0.get() + 1);
+                if self.0.get() == 2 {
+                    PanicAction::Stop
+                } else {
+                    PanicAction::Continue
+                }
+            }
+        }
+
+        #[actix_async::handler]
+        impl Handler<TestPanicMsg> for PanicActor {
+            async fn handle(&self, _: TestPanicMsg, _: Context<'_, Self>) {
+                panic!("This is a purpose panic to test on_panic");
+            }
+        }
+
+        let count = Rc::new(Cell::new(0));
+        let addr = PanicActor(count.clone()).start();
+
+        assert!(addr.send(TestPanicMsg).await.is_err());
+        assert!(addr.wait(TestPanicMsg).await.is_err());
+
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(count.get(), 2);
+        assert!(addr.send(TestPanicMsg).await.is_err());
+        assert_eq!(count.get(), 2);
+    }
+
+    #[actix_async::test]
+    async fn stop_reason() {
+        struct StopActor(Rc<Cell<Option<StopReason>>>);
+
+        #[actix_async::handler]
+        impl Actor for StopActor {
+            type Runtime = TokioRuntime;
+
+            async fn on_stop(&mut self, reason: StopReason, _: Context<'_, Self>) {
+                self.0.set(Some(reason));
+            }
+        }
+
+        let reason = Rc::new(Cell::new(None));
+
+        let addr = StopActor(reason.clone()).start();
+        let _ = addr.stop(true).await;
+        assert_eq!(reason.take(), Some(StopReason::Graceful));
+
+        let addr = StopActor(reason.clone()).start();
+        let _ = addr.stop(false).await;
+        assert_eq!(reason.take(), Some(StopReason::Forced));
+
+        let addr = StopActor(reason.clone()).start();
+        drop(addr);
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(reason.take(), Some(StopReason::AddrDropped));
+    }
+
+    #[actix_async::test]
+    async fn idle_timeout() {
+        struct IdleActor;
+
+        #[actix_async::handler]
+        impl Actor for IdleActor {
+            type Runtime = TokioRuntime;
+
+            fn idle_timeout() -> Option<Duration> {
+                Some(Duration::from_millis(50))
+            }
+        }
+
+        #[actix_async::handler]
+        impl Handler<TestMsg> for IdleActor {
+            async fn handle(&self, _: TestMsg, _: Context<'_, Self>) -> usize {
+                996
+            }
+        }
+
+        let addr = IdleActor.start();
+
+        // active actor is not stopped.
+        for _ in 0..10 {
+            sleep(Duration::from_millis(20)).await;
+            assert_eq!(addr.send(TestMsg).await.unwrap(), 996);
+        }
+
+        // idle actor is stopped by default on_idle hook.
+        sleep(Duration::from_millis(150)).await;
+        assert_eq!(addr.watch().await, StopReason::Graceful);
+        assert!(addr.send(TestMsg).await.is_err());
+    }
+
+    #[actix_async::test]
+    async fn try_start() {
+        use crate::error::StartError;
+
+        struct StartActor(bool);
+
+        #[actix_async::handler]
+        impl Actor for StartActor {
+            type Runtime = TokioRuntime;
+
+            async fn on_start_fallible(&mut self, _: Context<'_, Self>) -> Result<(), StartError> {
+                if self.0 {
+                    Ok(())
+                } else {
+                    Err(StartError::new("start failed"))
+                }
+            }
+        }
+
+        let addr = StartActor(true).try_start().await.unwrap();
+        assert!(addr.run(|_, _| Box::pin(async {})).await.is_ok());
+
+        let err = StartActor(false).try_start().await.err().unwrap();
+        assert_eq!(err.to_string(), "Actor failed to start: start failed");
+
+        let addr = StartActor(false).start();
+        assert_eq!(addr.watch().await, StopReason::StartFailed);
+        assert!(addr.run(|_, _| Box::pin(async {})).await.is_err());
+    }
+
+    #[actix_async::test]
+    async fn try_create_async() {
+        let (addr, fut) = TestActor::try_create_async(|_| async {
+            sleep(Duration::from_millis(100)).await;
+            Ok::<_, ()>(TestActor::default())
+        });
+        let (res, _) = tokio::join!(addr.send(TestMsg), fut);
+        assert_eq!(res.unwrap(), 996);
+
+        // pending message is resolved when actor failed to construct.
+        let (addr, fut) = TestActor::try_create_async(|_| async {
+            sleep(Duration::from_millis(100)).await;
+            Err::<TestActor, _>("construct failed")
+        });
+        let (res, err) = tokio::join!(addr.send(TestMsg), fut);
+        assert_eq!(err, Err("construct failed"));
+        assert_eq!(res, Err(ActixAsyncError::Closed));
+        assert_eq!(addr.watch().await, StopReason::StartFailed);
+    }
+
+    #[actix_async::test]
+    async fn start_lazy() {
+        let started = Rc::new(Cell::new(false));
+
+        let started1 = started.clone();
+        let addr = TestActor::start_lazy(move |_| async move {
+            started1.set(true);
+            TestActor::default()
+        });
+
+        sleep(Duration::from_millis(100)).await;
+        assert!(!started.get());
+
+        assert_eq!(addr.send(TestMsg).await.unwrap(), 996);
+        assert!(started.get());
+
+        // actor never started when address is dropped without sending message.
+        let started1 = started.clone();
+        started.set(false);
+        let addr = TestActor::start_lazy(move |_| async move {
+            started1.set(true);
+            TestActor::default()
+        });
+        let watch = addr.watch();
+        drop(addr);
+        assert_eq!(watch.await, StopReason::AddrDropped);
+        assert!(!started.get());
+    }
+
+    #[actix_async::test]
+    async fn restart() {
+        struct RestartActor(usize, Rc<Cell<Option<StopReason>>>);
+
+        #[actix_async::handler]
+        impl Actor for RestartActor {
+            type Runtime = TokioRuntime;
+
+            async fn on_stop(&mut self, reason: StopReason, _: Context<'_, Self>) {
+                self.1.set(Some(reason));
+            }
+        }
+
+        #[actix_async::handler]
+        impl Handler<TestMsg> for RestartActor {
+            async fn handle(&self, _: TestMsg, _: Context<'_, Self>) -> usize {
+                self.0
+            }
+        }
+
+        let reason = Rc::new(Cell::new(None));
+
+        let addr = RestartActor(996, reason.clone()).start();
+
+        let restart = addr.restart(|_| async { RestartActor(251, Default::default()) });
+        let (res, msg) = tokio::join!(restart, addr.send(TestMsg));
+        res.unwrap();
+
+        // message sent after restart is handled by new instance.
+        assert_eq!(msg.unwrap(), 251);
+        assert_eq!(reason.take(), Some(StopReason::Restart));
+        assert_eq!(addr.send(TestMsg).await.unwrap(), 251);
+    }
+
+    #[actix_async::test]
+    async fn lifecycle_events() {
+        use crate::address::LifecycleEvent;
+
+        let addr = TestActor::default().start();
+        let events = addr.lifecycle_events();
+
+        let _ = addr.restart(|_| async { TestActor::default() }).await;
+        let _ = addr.stop(false).await;
+
+        let events = events.collect::<Vec<_>>().await;
+        assert_eq!(
+            events,
+            vec![
+                LifecycleEvent::Started,
+                LifecycleEvent::Stopping,
+                LifecycleEvent::Restarted,
+                LifecycleEvent::Stopping,
+                LifecycleEvent::Stopped(StopReason::Forced)
+            ]
+        );
+    }
+
+    #[actix_async::test]
+    async fn actor_id() {
+        use crate::address::LifecycleEvent;
+
+        let addr = TestActor::default().start();
+        let addr2 = TestActor::default().start();
+        let mut events = addr.lifecycle_events();
+
+        let id = addr.actor_id();
+        assert_eq!(id.generation(), 0);
+        assert_eq!(addr.clone().actor_id(), id);
+        assert_eq!(addr.downgrade().actor_id(), id);
+        assert_ne!(addr2.actor_id().id(), id.id());
+
+        let ctx_id = addr
+            .run(|_, ctx| Box::pin(async move { ctx.actor_id() }))
+            .await
+            .unwrap();
+        assert_eq!(ctx_id, id);
+
+        let _ = addr.restart(|_| async { TestActor::default() }).await;
+        let id2 = addr.actor_id();
+        assert_eq!(id2.id(), id.id());
+        assert_eq!(id2.generation(), 1);
+
+        assert_eq!(events.next().await, Some(LifecycleEvent::Started));
+        assert_eq!(events.actor_id(), id);
+        assert_eq!(events.next().await, Some(LifecycleEvent::Stopping));
+        assert_eq!(events.next().await, Some(LifecycleEvent::Restarted));
+        assert_eq!(events.actor_id(), id2);
+    }
+
+    #[actix_async::test]
+    async fn system_stop() {
+        use crate::system::{StopMode, System};
+
+        struct OrderActor(usize, Rc<RefCell<Vec<usize>>>);
+
+        #[actix_async::handler]
+        impl Actor for OrderActor {
+            type Runtime = TokioRuntime;
+
+            async fn on_stop(&mut self, _: StopReason, _: Context<'_, Self>) {
+                self.1.borrow_mut().push(self.0);
+            }
+        }
+
+        let order = Rc::new(RefCell::new(Vec::new()));
+
+        let system = System::new();
+
+        let addr1 = system.start_in_phase(1, OrderActor(1, order.clone()));
+        let addr2 = system.start_in_phase(0, OrderActor(2, order.clone()));
+        let addr3 = system.start(OrderActor(3, order.clone()));
+
+        system
+            .stop(StopMode::Graceful {
+                deadline: Duration::from_secs(1),
+            })
+            .await;
+
+        assert_eq!(*order.borrow(), vec![2, 3, 1]);
+        assert_eq!(addr1.watch().await, StopReason::Graceful);
+        assert_eq!(addr2.watch().await, StopReason::Graceful);
+        assert_eq!(addr3.watch().await, StopReason::Graceful);
+    }
+
+    #[actix_async::test]
+    async fn state() {
+        use crate::address::LifecycleState;
+
+        let addr = TestActor::default().start();
+        assert_eq!(addr.state(), LifecycleState::Starting);
+
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(addr.state(), LifecycleState::Running);
+        assert_eq!(addr.in_flight(), 0);
+
+        for _ in 0..3 {
+            addr.do_send(TestTimeoutMessage);
+        }
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(addr.in_flight(), 3);
+
+        let _ = addr.stop(false).await;
+        addr.wait_for_stop().await;
+        assert_eq!(addr.state(), LifecycleState::Stopped);
+        assert_eq!(addr.in_flight(), 0);
+    }
+
+    #[actix_async::test]
+    async fn pause() {
+        let addr = TestActor::default().start();
+
+        let _ = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    ctx.pause();
+                    ctx.run_later(Duration::from_millis(300), |_, ctx| {
+                        Box::pin(async move { ctx.resume() })
+                    });
+                })
+            })
+            .await;
+
+        let res = addr
+            .send(TestMsg)
+            .timeout_response(Duration::from_millis(100))
+            .await;
+        assert_eq!(res, Err(ActixAsyncError::ReceiveTimeout));
+
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(addr.send(TestMsg).await.unwrap(), 996);
+
+        // stop is not blocked by paused context and messages queued before it are handled.
+        let _ = addr
+            .run(|_, ctx| Box::pin(async move { ctx.pause() }))
+            .await;
+
+        let req = addr.send(TestMsg);
+        let (stop, res) = tokio::join!(
+            async {
+                sleep(Duration::from_millis(50)).await;
+                addr.stop(true).await
+            },
+            req
+        );
+        assert!(stop.is_ok());
+        assert_eq!(res.unwrap(), 996);
+    }
+
+    #[actix_async::test]
+    async fn shrink_policy() {
+        struct ShrinkActor;
+
+        #[actix_async::handler]
+        impl Actor for ShrinkActor {
+            type Runtime = TokioRuntime;
+
+            fn shrink_policy() -> Option<usize> {
+                Some(1)
+            }
+        }
+
+        #[actix_async::handler]
+        impl Handler<TestMsg> for ShrinkActor {
+            async fn handle(&self, _: TestMsg, _: Context<'_, Self>) -> usize {
+                sleep(Duration::from_millis(1)).await;
+                996
+            }
+        }
+
+        let addr = ShrinkActor.start();
+
+        for _ in 0..2 {
+            let mut futs = futures_util::stream::FuturesUnordered::new();
+            for _ in 0..512 {
+                futs.push(addr.send(TestMsg));
+            }
+            while let Some(res) = futs.next().await {
+                assert_eq!(res.unwrap(), 996);
+            }
+        }
+    }
+
+    #[actix_async::test]
+    async fn task_ref_shrink() {
+        use crate::context_future::TaskRef;
+        use crate::waker::WakeQueue;
+
+        let queue = WakeQueue::new();
+        let mut task_ref = TaskRef::new::<TestActor>(&queue);
+
+        for _ in 0..512 {
+            task_ref.add_task(Box::pin(sleep(Duration::from_millis(1))));
+        }
+        let (task, wake) = task_ref.capacity();
+        assert!(task >= 512);
+        assert!(wake >= 512);
+
+        task_ref.graceful_resolve().await;
+        task_ref.shrink::<TestActor>();
+
+        let (task, wake) = task_ref.capacity();
+        assert_eq!(task, TestActor::size_hint());
+        assert!(wake <= TestActor::size_hint());
+    }
+
+    #[actix_async::test]
+    async fn stop_with_timeout() {
+        let addr = TestActor::default().start();
+        let reason = addr.stop_with_timeout(Duration::from_secs(1)).await;
+        assert_eq!(reason, StopReason::Graceful);
+
+        // blocked exclusive task is dropped when forced to stop.
+        let addr = TestActor::default().start();
+        addr.do_wait(TestTimeoutMessage);
+        sleep(Duration::from_millis(100)).await;
+
+        let now = Instant::now();
+        let reason = addr.stop_with_timeout(Duration::from_millis(100)).await;
+        assert_eq!(reason, StopReason::Forced);
+        assert!(now.elapsed() < Duration::from_secs(1));
+    }
+
+    #[actix_async::test]
+    async fn ttl() {
+        static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+        struct TtlActor;
+        actor!(TtlActor);
+
+        struct Ttl;
+        message!(Ttl, ());
+
+        #[actix_async::handler]
+        impl Handler<Ttl> for TtlActor {
+            async fn handle(&self, _: Ttl, _: Context<'_, Self>) {
+                HANDLED.fetch_add(1, Ordering::SeqCst);
+            }
+
+            async fn handle_wait(&mut self, _: Ttl, _: Context<'_, Self>) {
+                sleep(Duration::from_millis(200)).await;
+                HANDLED.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let addr = TtlActor.start();
+
+        // block actor with exclusive message.
+        addr.do_wait(Ttl);
+        sleep(Duration::from_millis(50)).await;
+
+        let res = addr.send(Ttl).ttl(Duration::from_millis(50)).await;
+        assert_eq!(res, Err(ActixAsyncError::Expired));
+
+        // message started before expired is not affected.
+        let res = addr.wait(Ttl).ttl(Duration::from_millis(150)).await;
+        assert!(res.is_ok());
+        assert_eq!(HANDLED.load(Ordering::SeqCst), 2);
+
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(HANDLED.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_async::test]
+    async fn dead_letter() {
+        use actix_async::address::{DeadLetter, DeadLetterReason};
+
+        static STOPPED: AtomicUsize = AtomicUsize::new(0);
+        static CLOSED: AtomicUsize = AtomicUsize::new(0);
+
+        struct LetterActor;
+
+        impl Actor for LetterActor {
+            type Runtime = TokioRuntime;
+
+            fn on_dead_letter(letter: DeadLetter<Self>) {
+                let counter = match letter.reason() {
+                    DeadLetterReason::Stopped => &STOPPED,
+                    DeadLetterReason::Closed => &CLOSED,
+                    DeadLetterReason::Overflow => unreachable!(),
+                };
+                let msg = letter.downcast::<Letter>().ok().unwrap();
+                counter.fetch_add(msg.0, Ordering::SeqCst);
+            }
+        }
+
+        struct Letter(usize);
+        message!(Letter, ());
+
+        #[actix_async::handler]
+        impl Handler<Letter> for LetterActor {
+            async fn handle(&self, _: Letter, _: Context<'_, Self>) {}
+        }
+
+        struct Block;
+        message!(Block, ());
+
+        #[actix_async::handler]
+        impl Handler<Block> for LetterActor {
+            async fn handle(&self, _: Block, _: Context<'_, Self>) {
+                sleep(Duration::from_millis(500)).await;
+            }
+        }
+
+        let addr = LetterActor.start();
+
+        // block actor with exclusive task so letters stay in mailbox.
+        addr.do_wait(Block);
+        sleep(Duration::from_millis(10)).await;
+
+        addr.do_send(Letter(1));
+        addr.do_send(Letter(2));
+
+        let reason = addr.stop_with_timeout(Duration::from_millis(100)).await;
+        assert_eq!(reason, StopReason::Forced);
+        assert_eq!(STOPPED.load(Ordering::SeqCst), 3);
+
+        addr.do_send(Letter(4));
+        assert_eq!(CLOSED.load(Ordering::SeqCst), 4);
+    }
+
+    #[actix_async::test]
+    async fn mailbox_full() {
+        struct FullActor(Rc<Cell<bool>>);
+
+        #[actix_async::handler]
+        impl Actor for FullActor {
+            type Runtime = TokioRuntime;
+
+            async fn on_mailbox_full(&mut self, _: Context<'_, Self>) {
+                self.0.set(true);
+            }
+
+            fn size_hint() -> usize {
+                2
+            }
+
+            fn mailbox_full_threshold() -> Option<usize> {
+                Some(1)
+            }
+        }
+
+        #[actix_async::handler]
+        impl Handler<TestMsg> for FullActor {
+            async fn handle(&self, _: TestMsg, _: Context<'_, Self>) -> usize {
+                996
+            }
+        }
+
+        let full = Rc::new(Cell::new(false));
+        let addr = FullActor(full.clone()).start();
+
+        let _ = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    ctx.pause();
+                    ctx.run_later(Duration::from_millis(200), |_, ctx| {
+                        Box::pin(async move { ctx.resume() })
+                    });
+                })
+            })
+            .await;
+
+        addr.do_send(TestMsg);
+        addr.do_send(TestMsg);
+
+        let res = addr.send(TestMsg).fail_fast().await;
+        assert_eq!(res, Err(ActixAsyncError::MailboxFull));
+
+        sleep(Duration::from_millis(300)).await;
+        assert!(full.get());
+        assert_eq!(addr.send(TestMsg).fail_fast().await.unwrap(), 996);
+    }
+
+    #[actix_async::test]
+    async fn send_priority() {
+        struct PriorityActor(RefCell<Vec<usize>>);
+        actor!(PriorityActor);
+
+        struct Order(usize);
+        message!(Order, ());
+
+        #[actix_async::handler]
+        impl Handler<Order> for PriorityActor {
+            async fn handle(&self, msg: Order, _: Context<'_, Self>) {
+                self.0.borrow_mut().push(msg.0);
+            }
+        }
+
+        let addr = PriorityActor(RefCell::new(Vec::new())).start();
+
+        let _ = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    ctx.pause();
+                    ctx.run_later(Duration::from_millis(100), |_, ctx| {
+                        Box::pin(async move { ctx.resume() })
+                    });
+                })
+            })
+            .await;
+
+        addr.do_send(Order(1));
+        addr.do_send(Order(2));
+        addr.send_priority(Order(3)).await.unwrap();
+
+        let res = addr
+            .run(|act, _| Box::pin(async move { act.0.borrow().clone() }))
+            .await
+            .unwrap();
+        assert_eq!(res, vec![3, 1, 2]);
+    }
+
+    #[actix_async::test]
+    async fn stop_full_mailbox() {
+        struct OrderActor(Rc<RefCell<Vec<usize>>>);
+
+        #[actix_async::handler]
+        impl Actor for OrderActor {
+            type Runtime = TokioRuntime;
+
+            fn size_hint() -> usize {
+                2
+            }
+        }
+
+        struct Order(usize);
+        message!(Order, ());
+
+        #[actix_async::handler]
+        impl Handler<Order> for OrderActor {
+            async fn handle(&self, msg: Order, _: Context<'_, Self>) {
+                self.0.borrow_mut().push(msg.0);
+            }
+        }
+
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let addr = OrderActor(order.clone()).start();
+
+        let _ = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    ctx.pause();
+                    ctx.run_later(Duration::from_millis(200), |_, ctx| {
+                        Box::pin(async move { ctx.resume() })
+                    });
+                })
+            })
+            .await;
+
+        addr.do_send(Order(1));
+        addr.do_send(Order(2));
+
+        // stop message is not blocked by full mailbox.
+        let res = addr.stop(true).timeout(Duration::from_millis(100)).await;
+        assert!(res.is_ok());
+        assert_eq!(*order.borrow(), vec![1, 2]);
+    }
+
+    #[actix_async::test]
+    async fn overflow_policy() {
+        use actix_async::address::DeadLetter;
+
+        static DEAD_LETTERS: AtomicUsize = AtomicUsize::new(0);
+
+        struct OrderActor(Rc<RefCell<Vec<usize>>>);
+
+        #[actix_async::handler]
+        impl Actor for OrderActor {
+            type Runtime = TokioRuntime;
+
+            fn size_hint() -> usize {
+                2
+            }
+
+            fn overflow_policy() -> OverflowPolicy {
+                OverflowPolicy::DropOldest
+            }
+
+            fn on_dead_letter(letter: DeadLetter<Self>) {
+                assert!(letter.message_type().ends_with("Order"));
+                DEAD_LETTERS.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        struct Order(usize);
+        message!(Order, ());
+
+        #[actix_async::handler]
+        impl Handler<Order> for OrderActor {
+            async fn handle(&self, msg: Order, _: Context<'_, Self>) {
+                self.0.borrow_mut().push(msg.0);
+            }
+        }
+
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let addr = OrderActor(order.clone()).start();
+
+        let _ = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    ctx.pause();
+                    ctx.run_later(Duration::from_millis(100), |_, ctx| {
+                        Box::pin(async move { ctx.resume() })
+                    });
+                })
+            })
+            .await;
+
+        for i in 1..5 {
+            addr.do_send(Order(i));
+        }
+
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(*order.borrow(), vec![3, 4]);
+        assert_eq!(DEAD_LETTERS.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_async::test]
+    async fn start_with_capacity() {
+        let addr = TestActor::default().start_with_capacity(1);
+
+        let _ = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    ctx.pause();
+                    ctx.run_later(Duration::from_millis(100), |_, ctx| {
+                        Box::pin(async move { ctx.resume() })
+                    });
+                })
+            })
+            .await;
+
+        addr.do_send(TestMsg);
+        let res = addr.send(TestMsg).fail_fast().await;
+        assert_eq!(res, Err(ActixAsyncError::MailboxFull));
+
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(addr.send(TestMsg).await.unwrap(), 996);
+    }
+
+    #[actix_async::test]
+    async fn set_mailbox_capacity() {
+        let addr = TestActor::default().start();
+
+        let cap = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    ctx.set_mailbox_capacity(1);
+                    ctx.pause();
+                    ctx.run_later(Duration::from_millis(100), |_, ctx| {
+                        Box::pin(async move { ctx.resume() })
+                    });
+                    ctx.mailbox_capacity()
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(cap, 1);
+
+        addr.do_send(TestMsg);
+        let res = addr.send(TestMsg).fail_fast().await;
+        assert_eq!(res, Err(ActixAsyncError::MailboxFull));
+
+        sleep(Duration::from_millis(200)).await;
+        let cap = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    ctx.set_mailbox_capacity(8);
+                    ctx.mailbox_capacity()
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(cap, 8);
+        assert_eq!(addr.send(TestMsg).await.unwrap(), 996);
+    }
+
+    #[actix_async::test]
+    async fn try_send() {
+        let addr = TestActor::default().start_with_capacity(1);
+
+        let _ = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    ctx.pause();
+                    ctx.run_later(Duration::from_millis(100), |_, ctx| {
+                        Box::pin(async move { ctx.resume() })
+                    });
+                })
+            })
+            .await;
+
+        let req = addr.try_send(TestMsg).ok().unwrap();
+
+        let err = addr.try_send(TestMsg).err().unwrap();
+        assert!(err.is_full());
+        let _msg: TestMsg = err.into_inner();
+
+        assert_eq!(req.await.unwrap(), 996);
+
+        let _ = addr.stop(true).await;
+        let err = addr.try_send(TestMsg).err().unwrap();
+        assert!(err.is_closed());
+    }
+
+    #[actix_async::test]
+    async fn send_batch() {
+        let addr = TestActor::default().start_with_capacity(1);
+
+        let res = addr.send_batch(vec![TestMsg, TestMsg, TestMsg]).await;
+        assert_eq!(res.unwrap(), vec![996, 996, 996]);
+
+        let res = addr.send_batch(Vec::<TestMsg>::new()).await;
+        assert!(res.unwrap().is_empty());
+    }
+
+    #[actix_async::test]
+    async fn sink() {
+        use futures_util::SinkExt;
+
+        let addr = TestActor::default().start_with_capacity(1);
+
+        let _ = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    ctx.pause();
+                    ctx.run_later(Duration::from_millis(200), |_, ctx| {
+                        Box::pin(async move { ctx.resume() })
+                    });
+                })
+            })
+            .await;
+
+        let mut sink = addr.clone().into_sink::<TestMsg>();
+
+        let start = Instant::now();
+        sink.send(TestMsg).await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(200));
+
+        // mailbox is full until actor resumes.
+        sink.send(TestMsg).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+
+        let stream = futures_util::stream::iter(vec![TestMsg, TestMsg, TestMsg]);
+        assert!(addr.send_all(stream).await.is_ok());
+
+        let _ = addr.stop(true).await;
+        assert_eq!(sink.send(TestMsg).await, Err(ActixAsyncError::Closed));
+        let stream = futures_util::stream::iter(vec![TestMsg]);
+        assert_eq!(addr.send_all(stream).await, Err(ActixAsyncError::Closed));
+    }
+
+    #[actix_async::test]
+    async fn local_addr() {
+        struct LocalActor(Cell<usize>);
+        actor!(LocalActor);
+
+        struct LocalMsg(Rc<usize>);
+        message!(LocalMsg, usize);
+
+        #[actix_async::handler]
+        impl Handler<LocalMsg> for LocalActor {
+            async fn handle(&self, msg: LocalMsg, _: Context<'_, Self>) -> usize {
+                self.0.set(self.0.get() + *msg.0);
+                self.0.get()
+            }
+
+            async fn handle_wait(&mut self, msg: LocalMsg, _: Context<'_, Self>) -> usize {
+                *self.0.get_mut() += *msg.0 * 10;
+                self.0.get()
+            }
+        }
+
+        let addr = LocalActor::builder()
+            .capacity(1)
+            .start_local(LocalActor(Cell::new(0)));
+
+        assert_eq!(addr.send(LocalMsg(Rc::new(1))).await, Ok(1));
+        assert_eq!(addr.wait(LocalMsg(Rc::new(1))).await, Ok(11));
+
+        // local channel ignores capacity for do_send.
+        addr.do_send(LocalMsg(Rc::new(1)));
+        addr.do_wait(LocalMsg(Rc::new(1)));
+        assert_eq!(addr.send(LocalMsg(Rc::new(1))).await, Ok(23));
+
+        // messages from Addr are received too.
+        let res = addr
+            .addr()
+            .run(|act, _| Box::pin(async move { act.0.get() }))
+            .await;
+        assert_eq!(res, Ok(23));
+
+        assert!(addr.stop(true).await.is_ok());
+        assert_eq!(
+            addr.send(LocalMsg(Rc::new(1))).await,
+            Err(ActixAsyncError::Closed)
+        );
+
+        // message can not be delivered is dropped on current thread.
+        let msg = Rc::new(1);
+        addr.do_send(LocalMsg(msg.clone()));
+        assert_eq!(Rc::strong_count(&msg), 1);
+    }
+
+    #[actix_async::test]
+    async fn ordered() {
+        struct OrderedActor;
+        actor!(OrderedActor);
+
+        struct Sleep(u64);
+        message!(Sleep, u64);
+
+        #[actix_async::handler]
+        impl Handler<Sleep> for OrderedActor {
+            async fn handle(&self, msg: Sleep, _: Context<'_, Self>) -> u64 {
+                sleep(Duration::from_millis(msg.0)).await;
+                msg.0
+            }
+        }
+
+        let addr = OrderedActor::builder().ordered().start(OrderedActor);
+        let addr2 = addr.clone();
+
+        let now = Instant::now();
+        let first = addr.try_send(Sleep(300)).ok().unwrap();
+        let second = addr.try_send(Sleep(100)).ok().unwrap();
+        let other = addr2.try_send(Sleep(100)).ok().unwrap();
+
+        // message from another clone of Addr is not ordered.
+        assert_eq!(other.await.unwrap(), 100);
+        assert!(now.elapsed() < Duration::from_millis(300));
+
+        assert_eq!(second.await.unwrap(), 100);
+        assert!(now.elapsed() >= Duration::from_millis(300));
+        assert_eq!(first.await.unwrap(), 300);
+    }
+
+    #[actix_async::test]
+    async fn rate_limit() {
+        let addr = TestActor::builder()
+            .rate_limit(2, Duration::from_millis(300))
+            .start(TestActor::default());
+
+        let start = Instant::now();
+
+        let res = tokio::join!(addr.send(TestMsg), addr.send(TestMsg));
+        assert_eq!(res, (Ok(996), Ok(996)));
+        assert!(start.elapsed() < Duration::from_millis(300));
+
+        assert_eq!(addr.send(TestMsg).await.unwrap(), 996);
+        assert!(start.elapsed() >= Duration::from_millis(300));
+
+        // stop message does not wait for token.
+        let addr = TestActor::builder()
+            .rate_limit(1, Duration::from_secs(10))
+            .start(TestActor::default());
+        assert_eq!(addr.send(TestMsg).await.unwrap(), 996);
+
+        let start = Instant::now();
+        addr.stop(true).await.unwrap();
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[actix_async::test]
+    async fn mailbox_budget() {
+        struct BudgetActor;
+
+        impl Actor for BudgetActor {
+            type Runtime = TokioRuntime;
+
+            fn mailbox_budget() -> Option<usize> {
+                Some(1024)
+            }
+        }
+
+        struct Payload(Vec<u8>);
+
+        impl Message for Payload {
+            type Result = usize;
+
+            fn size_hint_bytes(&self) -> usize {
+                core::mem::size_of::<Self>() + self.0.len()
+            }
+        }
+
+        #[actix_async::handler]
+        impl Handler<Payload> for BudgetActor {
+            async fn handle(&self, msg: Payload, _: Context<'_, Self>) -> usize {
+                msg.0.len()
+            }
+        }
+
+        let addr = BudgetActor.start();
+
+        let _ = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    ctx.pause();
+                    ctx.run_later(Duration::from_millis(100), |_, ctx| {
+                        Box::pin(async move { ctx.resume() })
+                    });
+                })
+            })
+            .await;
+
+        let req = addr.try_send(Payload(vec![0; 800])).ok().unwrap();
+
+        let res = addr.send(Payload(vec![0; 800])).fail_fast().await;
+        assert_eq!(res, Err(ActixAsyncError::MailboxFull));
+
+        let req2 = addr.try_send(Payload(Vec::new())).ok().unwrap();
+
+        assert_eq!(req.await.unwrap(), 800);
+        assert_eq!(req2.await.unwrap(), 0);
+    }
+
+    #[actix_async::test]
+    async fn mailbox_len() {
+        let addr = TestActor::default().start_with_capacity(2);
+
+        let _ = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    ctx.pause();
+                    ctx.run_later(Duration::from_millis(100), |_, ctx| {
+                        Box::pin(async move { ctx.resume() })
+                    });
+                })
+            })
+            .await;
+
+        assert!(addr.is_empty());
+        assert_eq!(addr.capacity(), 2);
+
+        addr.do_send(TestMsg);
+        assert_eq!(addr.len(), 1);
+        assert!(!addr.is_full());
+
+        addr.do_send(TestMsg);
+        assert_eq!(addr.len(), 2);
+        assert!(addr.is_full());
+
+        sleep(Duration::from_millis(200)).await;
+        assert!(addr.is_empty());
+    }
+
+    #[actix_async::test]
+    async fn ready() {
+        let addr = TestActor::default().start_with_capacity(4);
+
+        assert!(addr.ready().await.is_ok());
+
+        let _ = addr
+            .run(|_, ctx| {
+                Box::pin(async move {
+                    ctx.pause();
+                    ctx.run_later(Duration::from_millis(100), |_, ctx| {
+                        Box::pin(async move { ctx.resume() })
+                    });
+                })
+            })
+            .await;
+
+        for _ in 0..4 {
+            addr.do_send(TestMsg);
+        }
+        assert!(addr.is_full());
+
+        let now = Instant::now();
+        assert!(addr.ready().await.is_ok());
+        assert!(now.elapsed() >= Duration::from_millis(100));
+        assert!(addr.len() < 2);
+
+        let _ = addr.stop(false).await;
+        assert_eq!(addr.ready().await, Err(ActixAsyncError::Closed));
+    }
+
+    #[actix_async::test]
+    async fn watch() {
+        let addr = TestActor::default().start();
+        let watch = addr.watch();
+        let weak = addr.downgrade();
+
+        drop(addr);
+        assert_eq!(watch.await, StopReason::AddrDropped);
+        assert!(weak.upgrade().is_none());
+
+        let addr = TestActor::default().start();
+        let _ = addr.send(TestPanicMsg).await;
+        assert_eq!(addr.watch().await, StopReason::Panic);
+
+        // watching a stopped actor resolves immediately.
+        assert_eq!(addr.watch().await, StopReason::Panic);
+    }
+
+    #[actix_async::test]
+    async fn spawn_child() {
+        use crate::context::ChildStopped;
+
+        struct ParentActor(Rc<Cell<Option<StopReason>>>);
+        actor!(ParentActor);
+
+        #[actix_async::handler]
+        impl Handler<ChildStopped<TestActor>> for ParentActor {
+            async fn handle(&self, msg: ChildStopped<TestActor>, _: Context<'_, Self>) {
+                self.0.set(Some(msg.reason()));
+            }
+        }
+
+        let reason = Rc::new(Cell::new(None));
+        let parent = ParentActor(reason.clone()).start();
+
+        let spawn =
+            || parent.run(|_, ctx| Box::pin(async move { ctx.spawn_child(TestActor::default()) }));
+
+        // child stopped would notify parent.
+        let child = spawn().await.unwrap();
+        let _ = child.stop(false).await;
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(reason.take(), Some(StopReason::Forced));
+
+        // parent stopped would stop child.
+        let child = spawn().await.unwrap();
+        let _ = parent.stop(true).await;
+        assert_eq!(child.watch().await, StopReason::Graceful);
+    }
+
+    #[actix_async::test]
+    async fn restart_preserve_mailbox() {
+        struct TestSleepPanicMsg;
+        message!(TestSleepPanicMsg, ());
+
+        #[actix_async::handler]
+        impl Handler<TestSleepPanicMsg> for TestActor {
+            async fn handle(&self, _: TestSleepPanicMsg, _: Context<'_, Self>) {
+                sleep(Duration::from_millis(300)).await;
+                panic!("This is a purpose panic to test mailbox preservation");
+            }
+        }
+
+        let supervisor = Supervisor::builder().workers(1).build();
+        let addr = supervisor
+            .start(1, |_| async { TestActor::default() })
+            .await;
+
+        // exclusive message blocks the mailbox until it panics.
+        addr.do_wait(TestSleepPanicMsg);
+        sleep(Duration::from_millis(100)).await;
+
+        // queued messages are handled by the restarted actor.
+        let res = futures_util::future::join_all((0..3).map(|_| addr.send(TestMsg))).await;
+
+        for res in res {
+            assert_eq!(996, res.unwrap());
+        }
+    }
+
+    #[actix_async::test]
+    async fn supervisor_group() {
+        use crate::supervisor::SupervisionStrategy;
+
+        struct CountActor(Cell<usize>);
+        actor!(CountActor);
+
+        #[actix_async::handler]
+        impl Handler<TestMsg> for CountActor {
+            async fn handle(&self, _: TestMsg, _: Context<'_, Self>) -> usize {
+                self.0.set(self.0.get() + 1);
+                self.0.get()
+            }
+        }
+
+        let supervisor = Supervisor::builder().workers(1).build();
+        let group = supervisor.group(SupervisionStrategy::AllForOne);
+
+        let addr = group.start(|_| async { TestActor::default() }).await;
+        let count = group.start(|_| async { CountActor(Cell::new(0)) }).await;
+
+        assert_eq!(1, count.send(TestMsg).await.unwrap());
+        assert_eq!(2, count.send(TestMsg).await.unwrap());
+
+        // panic of one member restarts all members.
+        let _ = addr.send(TestPanicMsg).await;
+        sleep(Duration::from_millis(500)).await;
+
+        assert_eq!(996, addr.send(TestMsg).await.unwrap());
+        assert_eq!(1, count.send(TestMsg).await.unwrap());
+    }
+
+    // This is to test if re allocation of actor state would break other concurrent tasks.
+    #[actix_async::test]
+    async fn actor_move() {
+        struct TestAct(RefCell<Vec<u8>>);
+        actor!(TestAct);
+
+        let mut v = Vec::with_capacity(3);
+        for i in 1..4 {
+            v.push(i);
+        }
+
+        let addr = TestAct(RefCell::new(v)).start();
+
+        let addr_clone = addr.clone();
+        tokio::task::spawn_local(async move {
+            addr_clone
+                .run(|act, _| {
+                    Box::pin(async move {
+                        let this = act;
+                        tokio::task::yield_now().await;
+                        assert_eq!(*this.0.borrow().get(3).unwrap(), 4);
+                    })
+                })
+                .await
+                .unwrap();
+        });
+
+        addr.run(|act, _| {
+            Box::pin(async move {
+                let this = act;
+                tokio::task::yield_now().await;
+                this.0.borrow_mut().push(4);
+            })
+        })
+        .await
+        .unwrap();
+    }
+
+    #[derive(Default)]
+    struct ServiceActor;
+    actor!(ServiceActor);
+
+    impl crate::registry::SystemService for ServiceActor {}
+
+    impl Handler<TestMsg> for ServiceActor {
+        async fn handle(&self, _: TestMsg, _: Context<'_, Self>) -> usize {
+            996
+        }
     }
 
     struct TestActor(usize);
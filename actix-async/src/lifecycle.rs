@@ -0,0 +1,504 @@
+use core::{
+    any::Any,
+    fmt,
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
+
+use super::actor::StopReason;
+use super::util::{
+    channel::{oneshot, OneshotReceiver, OneshotSender},
+    futures::Stream,
+    smart_pointer::{Lock, RefCounter},
+};
+
+// lifecycle state of actor shared by it's Addr and context.
+pub(crate) struct Lifecycle {
+    id: usize,
+    generation: AtomicUsize,
+    state: AtomicU8,
+    in_flight: AtomicUsize,
+    force_stop: AtomicBool,
+    inner: Lock<LifecycleInner>,
+}
+
+struct LifecycleInner {
+    reason: Option<StopReason>,
+    watchers: Vec<OneshotSender<StopReason>>,
+    started: bool,
+    supervised: bool,
+    events: Vec<SharedQueue<(ActorId, LifecycleEvent)>>,
+    subscribers: Vec<Box<dyn Subscriber>>,
+    force_waker: Option<Waker>,
+}
+
+impl Lifecycle {
+    pub(crate) fn new() -> Self {
+        Self::_new(false)
+    }
+
+    /// lifecycle shared by instances of a supervised actor. event stream would not end when an
+    /// instance is stopped.
+    pub(crate) fn supervised() -> Self {
+        Self::_new(true)
+    }
+
+    fn _new(supervised: bool) -> Self {
+        static ID: AtomicUsize = AtomicUsize::new(1);
+
+        Self {
+            id: ID.fetch_add(1, Ordering::Relaxed),
+            generation: AtomicUsize::new(0),
+            state: AtomicU8::new(LifecycleState::Starting as u8),
+            in_flight: AtomicUsize::new(0),
+            force_stop: AtomicBool::new(false),
+            inner: Lock::new(LifecycleInner {
+                reason: None,
+                watchers: Vec::new(),
+                started: false,
+                supervised,
+                events: Vec::new(),
+                subscribers: Vec::new(),
+                force_waker: None,
+            }),
+        }
+    }
+
+    /// actor is (re)started. reset the stop reason of previous run.
+    pub(crate) fn start(&self) {
+        self.inner.lock().reason = None;
+        self.force_stop.store(false, Ordering::Release);
+        self.set_state(LifecycleState::Starting);
+    }
+
+    /// actor finished it's start hook. emit `LifecycleEvent::Restarted` when it has been
+    /// started before.
+    pub(crate) fn started(&self) {
+        self.set_state(LifecycleState::Running);
+        let mut inner = self.inner.lock();
+        let event = if mem::replace(&mut inner.started, true) {
+            self.generation.fetch_add(1, Ordering::Relaxed);
+            LifecycleEvent::Restarted
+        } else {
+            LifecycleEvent::Started
+        };
+        inner.emit(self.actor_id(), event, false);
+    }
+
+    /// actor is about to call it's stop hook.
+    pub(crate) fn stopping(&self) {
+        self.set_state(LifecycleState::Stopping);
+        self.inner
+            .lock()
+            .emit(self.actor_id(), LifecycleEvent::Stopping, false);
+    }
+
+    pub(crate) fn actor_id(&self) -> ActorId {
+        ActorId {
+            id: self.id,
+            generation: self.generation.load(Ordering::Relaxed),
+        }
+    }
+
+    /// actor is stopped. notify all watchers with the reason.
+    pub(crate) fn stop(&self, reason: StopReason) {
+        self.set_state(LifecycleState::Stopped);
+        self.set_in_flight(0);
+
+        let watchers = {
+            let mut inner = self.inner.lock();
+            inner.reason = Some(reason.clone());
+            let close = !inner.supervised;
+            inner.emit(
+                self.actor_id(),
+                LifecycleEvent::Stopped(reason.clone()),
+                close,
+            );
+            if close {
+                inner.close_subscribers();
+            }
+            mem::take(&mut inner.watchers)
+        };
+
+        for tx in watchers {
+            let _ = tx.send(reason.clone());
+        }
+    }
+
+    /// force running actor to stop without waiting for it's tasks.
+    pub(crate) fn force_stop(&self) {
+        self.force_stop.store(true, Ordering::Release);
+        if let Some(waker) = self.inner.lock().force_waker.take() {
+            waker.wake();
+        }
+    }
+
+    pub(crate) fn poll_force_stop(&self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.force_stop.load(Ordering::Acquire) {
+            return Poll::Ready(());
+        }
+
+        {
+            let mut inner = self.inner.lock();
+            match inner.force_waker {
+                Some(ref waker) if waker.will_wake(cx.waker()) => {}
+                _ => inner.force_waker = Some(cx.waker().clone()),
+            }
+        }
+
+        // check again in case force_stop is called before the waker is registered.
+        if self.force_stop.load(Ordering::Acquire) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn set_state(&self, state: LifecycleState) {
+        self.state.store(state as u8, Ordering::Release);
+    }
+
+    pub(crate) fn state(&self) -> LifecycleState {
+        match self.state.load(Ordering::Acquire) {
+            0 => LifecycleState::Starting,
+            1 => LifecycleState::Running,
+            2 => LifecycleState::Stopping,
+            _ => LifecycleState::Stopped,
+        }
+    }
+
+    pub(crate) fn set_in_flight(&self, count: usize) {
+        self.in_flight.store(count, Ordering::Relaxed);
+    }
+
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.inner.lock().reason.is_some()
+    }
+
+    pub(crate) fn reason(&self) -> Option<StopReason> {
+        self.inner.lock().reason.clone()
+    }
+
+    pub(crate) fn watch(&self) -> Watch {
+        let mut inner = self.inner.lock();
+
+        let state = match inner.reason {
+            Some(ref reason) => WatchState::Stopped(Some(reason.clone())),
+            None => {
+                let (tx, rx) = oneshot();
+                // remove watchers that are dropped before actor stop.
+                inner.watchers.retain(|tx| !tx.is_closed());
+                inner.watchers.push(tx);
+                WatchState::Watching(rx)
+            }
+        };
+
+        Watch { state }
+    }
+
+    pub(crate) fn events(&self) -> LifecycleEvents {
+        let mut inner = self.inner.lock();
+
+        let closed = !inner.supervised && inner.reason.is_some();
+        let queue = EventQueue::new(closed);
+
+        if !closed {
+            inner.events.push(queue.clone());
+        }
+
+        LifecycleEvents {
+            queue,
+            actor_id: self.actor_id(),
+        }
+    }
+
+    pub(crate) fn subscribe<E: Clone + Send + 'static>(&self) -> Subscription<E> {
+        let mut inner = self.inner.lock();
+
+        let closed = !inner.supervised && inner.reason.is_some();
+        let queue = EventQueue::new(closed);
+
+        if !closed {
+            inner.subscribers.push(Box::new(queue.clone()));
+        }
+
+        Subscription { queue }
+    }
+
+    /// push event to all subscribers of it's type. return the count of subscribers received it.
+    pub(crate) fn publish<E: Clone + Send + 'static>(&self, event: E) -> usize {
+        let mut inner = self.inner.lock();
+
+        // remove subscribers that are dropped.
+        inner.subscribers.retain(|sub| !sub.is_dropped());
+
+        let mut count = 0;
+        for sub in inner.subscribers.iter() {
+            if let Some(queue) = sub.as_any().downcast_ref::<SharedQueue<E>>() {
+                queue.lock().push(event.clone());
+                count += 1;
+            }
+        }
+
+        count
+    }
+}
+
+impl LifecycleInner {
+    // emit event to all subscribers and close the streams of them when close is true.
+    fn emit(&mut self, id: ActorId, event: LifecycleEvent, close: bool) {
+        // remove subscribers that are dropped.
+        self.events
+            .retain(|queue| RefCounter::strong_count(queue) > 1);
+
+        for queue in self.events.iter() {
+            let mut queue = queue.lock();
+            queue.closed = close;
+            queue.push((id, event.clone()));
+        }
+
+        if close {
+            self.events.clear();
+        }
+    }
+
+    // close the streams of all event subscribers.
+    fn close_subscribers(&mut self) {
+        for sub in mem::take(&mut self.subscribers) {
+            sub.close();
+        }
+    }
+}
+
+impl Drop for Lifecycle {
+    fn drop(&mut self) {
+        self.inner.lock().close_subscribers();
+        for queue in mem::take(&mut self.inner.lock().events) {
+            queue.lock().close();
+        }
+    }
+}
+
+/// unique id of a started actor. See `Addr::actor_id`.
+///
+/// The generation starts from 0 and increases every time the actor is restarted by
+/// `Supervisor` or `Context` while the id stays the same.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct ActorId {
+    id: usize,
+    generation: usize,
+}
+
+impl ActorId {
+    /// id of actor. unique within the process.
+    #[inline]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// count of restarts of actor.
+    #[inline]
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+impl fmt::Display for ActorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.id, self.generation)
+    }
+}
+
+/// Snapshot of actor's runtime state. See `Addr::state`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum LifecycleState {
+    /// actor is constructing or running it's start hook.
+    Starting = 0,
+    /// actor is handling messages.
+    Running = 1,
+    /// actor is running it's stop hook.
+    Stopping = 2,
+    /// actor is stopped.
+    Stopped = 3,
+}
+
+/// State transition of actor observed by `Addr::lifecycle_events`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LifecycleEvent {
+    /// actor finished `Actor::on_start_fallible` and started to handle messages.
+    Started,
+    /// actor is about to call `Actor::on_stop`.
+    Stopping,
+    /// a new instance of actor finished it's start hook after a restart.
+    Restarted,
+    /// actor is stopped with the reason.
+    Stopped(StopReason),
+}
+
+type SharedQueue<E> = RefCounter<Lock<EventQueue<E>>>;
+
+// event buffer shared by lifecycle and a subscribed stream.
+struct EventQueue<E> {
+    events: VecDeque<E>,
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+impl<E> EventQueue<E> {
+    fn new(closed: bool) -> SharedQueue<E> {
+        RefCounter::new(Lock::new(Self {
+            events: VecDeque::new(),
+            waker: None,
+            closed,
+        }))
+    }
+
+    fn push(&mut self, event: E) {
+        self.events.push_back(event);
+        self.wake();
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        self.wake();
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<E>> {
+        match self.events.pop_front() {
+            Some(event) => Poll::Ready(Some(event)),
+            None if self.closed => Poll::Ready(None),
+            None => {
+                self.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Stream returned by `Addr::lifecycle_events`.
+pub struct LifecycleEvents {
+    queue: SharedQueue<(ActorId, LifecycleEvent)>,
+    actor_id: ActorId,
+}
+
+impl LifecycleEvents {
+    /// id of actor when the last event yielded by stream is emitted. Before any event is
+    /// yielded it's the id when stream is subscribed.
+    #[inline]
+    pub fn actor_id(&self) -> ActorId {
+        self.actor_id
+    }
+}
+
+impl Stream for LifecycleEvents {
+    type Item = LifecycleEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let res = this.queue.lock().poll_next(cx);
+        match res {
+            Poll::Ready(Some((id, event))) => {
+                this.actor_id = id;
+                Poll::Ready(Some(event))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+// type erased subscriber queue stored in lifecycle.
+trait Subscriber: Send {
+    fn as_any(&self) -> &dyn Any;
+
+    fn is_dropped(&self) -> bool;
+
+    fn close(&self);
+}
+
+impl<E: Send + 'static> Subscriber for SharedQueue<E> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_dropped(&self) -> bool {
+        RefCounter::strong_count(self) == 1
+    }
+
+    fn close(&self) {
+        self.lock().close();
+    }
+}
+
+/// Stream returned by `Addr::subscribe`. Yields events of type `E` emitted by actor with
+/// `Context::emit`.
+///
+/// *. Stream ends when actor is stopped. For actor started by `Supervisor` it ends when all
+/// `Addr` of actor are dropped.
+pub struct Subscription<E> {
+    queue: SharedQueue<E>,
+}
+
+impl<E> Subscription<E> {
+    /// count of events buffered in stream and not yielded yet.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.queue.lock().events.len()
+    }
+
+    /// check if there is no buffered event.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<E> Stream for Subscription<E> {
+    type Item = E;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.queue.lock().poll_next(cx)
+    }
+}
+
+/// Future returned by `Addr::watch`. Resolves with the `StopReason` when actor is stopped.
+pub struct Watch {
+    state: WatchState,
+}
+
+enum WatchState {
+    Stopped(Option<StopReason>),
+    Watching(OneshotReceiver<StopReason>),
+}
+
+impl Future for Watch {
+    type Output = StopReason;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.get_mut().state {
+            WatchState::Stopped(ref mut reason) => {
+                Poll::Ready(reason.take().expect("Watch polled after finish"))
+            }
+            // lifecycle is gone without notifying. treat it as a forced stop.
+            WatchState::Watching(ref mut rx) => Pin::new(rx)
+                .poll(cx)
+                .map(|res| res.unwrap_or(StopReason::Forced)),
+        }
+    }
+}
@@ -8,11 +8,187 @@ macro_rules! actor {
     };
 }
 
+/// implement `Handler` for given actor and message type by forwarding to it's `HandlerBoxed`
+/// impl. See `HandlerBoxed` for example.
+#[macro_export]
+macro_rules! handler_boxed {
+    ($act: ty, $msg: ty) => {
+        impl actix_async::prelude::Handler<$msg> for $act {
+            fn handle<'a>(
+                &'a self,
+                msg: $msg,
+                ctx: actix_async::prelude::Context<'a, Self>,
+            ) -> impl ::core::future::Future<
+                Output = <$msg as actix_async::prelude::Message>::Result,
+            > + 'a {
+                <Self as actix_async::prelude::HandlerBoxed<$msg>>::handle(self, msg, ctx)
+            }
+
+            fn handle_wait<'a>(
+                &'a mut self,
+                msg: $msg,
+                ctx: actix_async::prelude::Context<'a, Self>,
+            ) -> impl ::core::future::Future<
+                Output = <$msg as actix_async::prelude::Message>::Result,
+            > + 'a {
+                <Self as actix_async::prelude::HandlerBoxed<$msg>>::handle_wait(self, msg, ctx)
+            }
+        }
+    };
+}
+
+/// implement `Message` for given type with given result type.
+///
+/// # example:
+/// ```rust
+/// use actix_async::prelude::*;
+///
+/// struct MyMsg;
+/// message!(MyMsg, u32);
+///
+/// // message type with concrete type params.
+/// struct Wrap<T>(T);
+/// message!(Wrap<u32>, bool);
+/// message!(Wrap<String>, usize);
+///
+/// // generic message type. generic params are declared with impl prefix and their trait bounds
+/// // go to optional where clause.
+/// struct MyGenericMsg<T, E>(T, E);
+/// message!(impl<T, E> MyGenericMsg<T, E>, Result<Vec<T>, E> where T: Clone + Send, E: Send);
+/// ```
+///
+/// *. Single uppercase letter type params without impl prefix are rejected as undeclared generic
+/// params.
+/// ```compile_fail
+/// use actix_async::prelude::*;
+///
+/// struct MyGenericMsg<T>(T);
+/// message!(MyGenericMsg<T>, Vec<T>);
+/// ```
 #[macro_export]
 macro_rules! message {
+    (impl < $($gen: ident),+ $(,)? > $ty: ty, $res: ty $(where $($bound: tt)+)?) => {
+        impl<$($gen: 'static),+> actix_async::prelude::Message for $ty
+        where
+            $res: Send + 'static,
+            $($($bound)+)?
+        {
+            type Result = $res;
+        }
+    };
+    ($name: ident < $($arg: ident),+ $(,)? >, $res: ty) => {
+        actix_async::__message_check!($name<$($arg),+>, $res; $($arg)+);
+    };
     ($ty: ty, $res: ty) => {
         impl actix_async::prelude::Message for $ty {
             type Result = $res;
         }
     };
 }
+
+// check type params of `message!(Type<..>, Result)` one by one. single uppercase letter is
+// treated as generic param missing the impl prefix.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __message_check {
+    ($ty: ty, $res: ty;) => {
+        impl actix_async::prelude::Message for $ty {
+            type Result = $res;
+        }
+    };
+    ($ty: ty, $res: ty; A $($rest: ident)*) => {
+        actix_async::__message_generic!(A);
+    };
+    ($ty: ty, $res: ty; B $($rest: ident)*) => {
+        actix_async::__message_generic!(B);
+    };
+    ($ty: ty, $res: ty; C $($rest: ident)*) => {
+        actix_async::__message_generic!(C);
+    };
+    ($ty: ty, $res: ty; D $($rest: ident)*) => {
+        actix_async::__message_generic!(D);
+    };
+    ($ty: ty, $res: ty; E $($rest: ident)*) => {
+        actix_async::__message_generic!(E);
+    };
+    ($ty: ty, $res: ty; F $($rest: ident)*) => {
+        actix_async::__message_generic!(F);
+    };
+    ($ty: ty, $res: ty; G $($rest: ident)*) => {
+        actix_async::__message_generic!(G);
+    };
+    ($ty: ty, $res: ty; H $($rest: ident)*) => {
+        actix_async::__message_generic!(H);
+    };
+    ($ty: ty, $res: ty; I $($rest: ident)*) => {
+        actix_async::__message_generic!(I);
+    };
+    ($ty: ty, $res: ty; J $($rest: ident)*) => {
+        actix_async::__message_generic!(J);
+    };
+    ($ty: ty, $res: ty; K $($rest: ident)*) => {
+        actix_async::__message_generic!(K);
+    };
+    ($ty: ty, $res: ty; L $($rest: ident)*) => {
+        actix_async::__message_generic!(L);
+    };
+    ($ty: ty, $res: ty; M $($rest: ident)*) => {
+        actix_async::__message_generic!(M);
+    };
+    ($ty: ty, $res: ty; N $($rest: ident)*) => {
+        actix_async::__message_generic!(N);
+    };
+    ($ty: ty, $res: ty; O $($rest: ident)*) => {
+        actix_async::__message_generic!(O);
+    };
+    ($ty: ty, $res: ty; P $($rest: ident)*) => {
+        actix_async::__message_generic!(P);
+    };
+    ($ty: ty, $res: ty; Q $($rest: ident)*) => {
+        actix_async::__message_generic!(Q);
+    };
+    ($ty: ty, $res: ty; R $($rest: ident)*) => {
+        actix_async::__message_generic!(R);
+    };
+    ($ty: ty, $res: ty; S $($rest: ident)*) => {
+        actix_async::__message_generic!(S);
+    };
+    ($ty: ty, $res: ty; T $($rest: ident)*) => {
+        actix_async::__message_generic!(T);
+    };
+    ($ty: ty, $res: ty; U $($rest: ident)*) => {
+        actix_async::__message_generic!(U);
+    };
+    ($ty: ty, $res: ty; V $($rest: ident)*) => {
+        actix_async::__message_generic!(V);
+    };
+    ($ty: ty, $res: ty; W $($rest: ident)*) => {
+        actix_async::__message_generic!(W);
+    };
+    ($ty: ty, $res: ty; X $($rest: ident)*) => {
+        actix_async::__message_generic!(X);
+    };
+    ($ty: ty, $res: ty; Y $($rest: ident)*) => {
+        actix_async::__message_generic!(Y);
+    };
+    ($ty: ty, $res: ty; Z $($rest: ident)*) => {
+        actix_async::__message_generic!(Z);
+    };
+    ($ty: ty, $res: ty; $arg: ident $($rest: ident)*) => {
+        actix_async::__message_check!($ty, $res; $($rest)*);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __message_generic {
+    ($gen: ident) => {
+        compile_error!(concat!(
+            "generic param `",
+            stringify!($gen),
+            "` is not declared. use `message!(impl<",
+            stringify!($gen),
+            "> Type<..>, Result)` for generic message type",
+        ));
+    };
+}
@@ -1,19 +1,27 @@
 use core::{
+    any::Any,
+    cell::{Cell, RefCell},
+    fmt,
     future::Future,
     marker::PhantomData,
     pin::Pin,
-    task::{Context as StdContext, Poll},
+    task::{Context as StdContext, Poll, Waker},
     time::Duration,
 };
 
-use alloc::boxed::Box;
+#[cfg(feature = "envelope")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use alloc::{boxed::Box, collections::BTreeMap, rc::Rc, vec::Vec};
 
 use super::actor::{Actor, ActorState};
+use super::context::{Context, IntervalFlow, TaskCancel, TaskHandle};
 use super::handler::{Handler, MessageHandler};
-use super::runtime::RuntimeService;
+use super::request::ResponseSink;
+use super::runtime::{RuntimeInterval, RuntimeService};
 use super::util::{
-    channel::{OneshotReceiver, OneshotSender},
-    futures::{ready, LocalBoxStream, Stream},
+    channel::{oneshot, OneshotReceiver, OneshotSender},
+    futures::{ready, LocalBoxFuture, LocalBoxStream, Stream},
     smart_pointer::RefCounter,
 };
 
@@ -33,17 +41,58 @@ use super::util::{
 /// struct MyMsg2;
 /// // a short cut macro would do the same thing as above.
 /// message!(MyMsg2, u32);
+///
+/// // derive macro with result type set by rtype attribute.
+/// #[derive(Message)]
+/// #[rtype(result = "u32")]
+/// struct MyMsg3;
 /// ```
 pub trait Message: 'static {
     type Result: Send + 'static;
+
+    /// estimated bytes of message. used by the memory budget of actor's channel.
+    /// See `Actor::mailbox_budget`.
+    ///
+    /// Override it for message owning large heap allocations.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct Payload(Vec<u8>);
+    ///
+    /// impl Message for Payload {
+    ///     type Result = ();
+    ///
+    ///     fn size_hint_bytes(&self) -> usize {
+    ///         core::mem::size_of::<Self>() + self.0.len()
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Default to `core::mem::size_of_val`.
+    #[inline]
+    fn size_hint_bytes(&self) -> usize {
+        core::mem::size_of_val(self)
+    }
 }
 
 impl<M: Message + ?Sized> Message for RefCounter<M> {
     type Result = M::Result;
+
+    #[inline]
+    fn size_hint_bytes(&self) -> usize {
+        (**self).size_hint_bytes()
+    }
 }
 
 impl<M: Message + ?Sized> Message for Box<M> {
     type Result = M::Result;
+
+    #[inline]
+    fn size_hint_bytes(&self) -> usize {
+        (**self).size_hint_bytes()
+    }
 }
 
 pub(crate) struct FunctionMessage<F, R> {
@@ -108,14 +157,153 @@ where
     type Result = R;
 }
 
+// message type convert between the message/result type of a `Recipient` and the ones of
+// actor's `Handler`.
+pub(crate) struct ConvertMessage<M, R> {
+    pub(crate) msg: M,
+    _res: PhantomData<R>,
+}
+
+impl<M, R> ConvertMessage<M, R> {
+    pub(crate) fn new(msg: M) -> Self {
+        Self {
+            msg,
+            _res: PhantomData,
+        }
+    }
+}
+
+impl<M, R> Message for ConvertMessage<M, R>
+where
+    M: 'static,
+    R: Send + 'static,
+{
+    type Result = R;
+}
+
+// a batch of messages sent with one channel reservation. See `Addr::send_batch`.
+pub(crate) struct BatchMessage<M>(pub(crate) Vec<M>);
+
+impl<M: Message> Message for BatchMessage<M> {
+    type Result = Vec<M::Result>;
+
+    fn size_hint_bytes(&self) -> usize {
+        self.0.iter().map(M::size_hint_bytes).sum()
+    }
+}
+
+// message with the sink of it's streaming response. See `Addr::send_stream`.
+pub(crate) struct StreamRequest<M, R> {
+    pub(crate) msg: M,
+    pub(crate) sink: ResponseSink<R>,
+}
+
+impl<M: Message, R: Send + 'static> Message for StreamRequest<M, R> {
+    type Result = M::Result;
+
+    fn size_hint_bytes(&self) -> usize {
+        self.msg.size_hint_bytes()
+    }
+}
+
+/// metadata of a message queued in actor's channel. See `Context::current_envelope`.
+///
+/// *. Correlation id and enqueue time are only tracked with `envelope` feature. Without it
+/// constructing a message does not touch global counter or clock.
+#[derive(Debug, Clone, Copy)]
+pub struct Envelope {
+    #[cfg(feature = "envelope")]
+    id: usize,
+    // id of `Addr` sent the message. 0 when message is not from an `Addr`.
+    pub(crate) sender: usize,
+    #[cfg(feature = "envelope")]
+    enqueued_at: std::time::Instant,
+}
+
+impl Envelope {
+    pub(crate) fn new() -> Self {
+        #[cfg(feature = "envelope")]
+        static ID: AtomicUsize = AtomicUsize::new(1);
+
+        Self {
+            #[cfg(feature = "envelope")]
+            id: ID.fetch_add(1, Ordering::Relaxed),
+            sender: 0,
+            #[cfg(feature = "envelope")]
+            enqueued_at: std::time::Instant::now(),
+        }
+    }
+
+    /// correlation id of message. auto assigned and unique within the process.
+    ///
+    /// *. Only available with `envelope` feature.
+    #[cfg(feature = "envelope")]
+    #[inline]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// id of `Addr` sent the message. See `Addr::id`.
+    ///
+    /// Return `None` when message is not sent from an `Addr`.
+    #[inline]
+    pub fn sender(&self) -> Option<usize> {
+        if self.sender == 0 {
+            None
+        } else {
+            Some(self.sender)
+        }
+    }
+
+    /// the time message is constructed and enqueued.
+    ///
+    /// *. Only available with `envelope` feature.
+    #[cfg(feature = "envelope")]
+    #[inline]
+    pub fn enqueued_at(&self) -> std::time::Instant {
+        self.enqueued_at
+    }
+
+    /// time passed since message is enqueued.
+    ///
+    /// *. Only available with `envelope` feature.
+    #[cfg(feature = "envelope")]
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        self.enqueued_at.elapsed()
+    }
+}
+
 // concrete type for dyn MessageHandler trait object that provide the message and the response
 // channel.
 pub(crate) struct MessageContainer<M: Message> {
     pub(crate) msg: Option<M>,
     pub(crate) tx: Option<OneshotSender<M::Result>>,
+    pub(crate) envelope: Envelope,
+    // box message as Any. only set when message is Send. See `DeadLetter::into_any`.
+    pub(crate) any: Option<fn(M) -> Box<dyn Any + Send>>,
 }
 
 impl<M: Message> MessageContainer<M> {
+    pub(crate) fn new(msg: M, tx: Option<OneshotSender<M::Result>>) -> Self {
+        Self {
+            msg: Some(msg),
+            tx,
+            envelope: Envelope::new(),
+            any: None,
+        }
+    }
+
+    pub(crate) fn new_send(msg: M, tx: Option<OneshotSender<M::Result>>) -> Self
+    where
+        M: Send,
+    {
+        Self {
+            any: Some(|msg| Box::new(msg)),
+            ..Self::new(msg, tx)
+        }
+    }
+
     pub(crate) fn take(&mut self) -> (M, Option<OneshotSender<M::Result>>) {
         (self.msg.take().unwrap(), self.tx.take())
     }
@@ -127,8 +315,8 @@ impl<M: Message> MessageContainer<M> {
 
     *. When it's constructed through `Addr`. The caller must make sure the `Message` type
        passed to it is `Send` bound as the object would possibly sent to another thread.
-    *. When it's constructed through `Context`. The container remain on it's thread and never
-       move to other threads so it's safe to bound to `Send` regardless.
+    *. When it's constructed through `Context` or `LocalAddr`. The container remain on it's
+       thread and never move to other threads so it's safe to bound to `Send` regardless.
 */
 unsafe impl<M: Message> Send for MessageContainer<M> {}
 
@@ -160,26 +348,55 @@ where
     M: Message + Sized + Clone + 'static,
 {
     fn clone_object(&self) -> Box<dyn MessageHandler<A> + Send> {
-        Box::new(MessageContainer {
-            msg: Some(self.clone()),
-            tx: None,
-        })
+        Box::new(MessageContainer::new(self.clone(), None))
     }
 }
 
 // message would produced in the future passed to Context<Actor>.
 pub(crate) struct FutureMessage<A: Actor> {
     delay: Pin<Box<<A::Runtime as RuntimeService>::Sleep>>,
-    handle: Option<OneshotReceiver<()>>,
+    handle: TaskHandle,
     msg: Option<ActorMessage<A>>,
+    created_at: CreatedAt,
+    #[cfg(feature = "std")]
+    deadline: std::time::Instant,
 }
 
 impl<A: Actor> FutureMessage<A> {
-    pub(crate) fn new(dur: Duration, rx: OneshotReceiver<()>, msg: ActorMessage<A>) -> Self {
+    pub(crate) fn new(dur: Duration, handle: TaskHandle, msg: ActorMessage<A>) -> Self {
+        let created_at = CreatedAt::now();
         Self {
             delay: Box::pin(<A::Runtime as RuntimeService>::sleep(dur)),
-            handle: Some(rx),
+            handle,
+            msg: Some(msg),
+            #[cfg(feature = "std")]
+            deadline: created_at.0 + dur,
+            created_at,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn new_at(
+        deadline: std::time::Instant,
+        handle: TaskHandle,
+        msg: ActorMessage<A>,
+    ) -> Self {
+        Self {
+            delay: Box::pin(<A::Runtime as RuntimeService>::sleep_until(deadline)),
+            handle,
             msg: Some(msg),
+            created_at: CreatedAt::now(),
+            deadline,
+        }
+    }
+
+    pub(crate) fn describe(&self) -> ScheduledTask {
+        ScheduledTask {
+            kind: ScheduledKind::Later,
+            paused: self.handle.is_paused(),
+            created_at: self.created_at,
+            #[cfg(feature = "std")]
+            deadline: Some(self.deadline),
         }
     }
 }
@@ -190,35 +407,427 @@ impl<A: Actor> Future for FutureMessage<A> {
     fn poll(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
 
-        if let Some(h) = this.handle.as_mut() {
-            match Pin::new(h).poll(cx) {
-                // handle canceled. resolve with nothing.
-                Poll::Ready(Ok(())) => return Poll::Ready(None),
-                // handle dropped. the task is now detached.
-                Poll::Ready(Err(_)) => this.handle = None,
-                Poll::Pending => {}
-            }
+        // handle canceled. resolve with nothing.
+        if this.handle.poll_cancel(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+
+        // message paused. it's sent on resume when the delay is already passed.
+        if this.handle.is_paused() {
+            return Poll::Pending;
         }
 
         this.delay.as_mut().poll(cx).map(|_| this.msg.take())
     }
 }
 
+// time a scheduled task is added to context.
+#[derive(Clone, Copy)]
+pub(crate) struct CreatedAt(#[cfg(feature = "std")] std::time::Instant);
+
+impl CreatedAt {
+    fn now() -> Self {
+        Self(
+            #[cfg(feature = "std")]
+            std::time::Instant::now(),
+        )
+    }
+}
+
+/// kind of task scheduled on context. See `ScheduledTask::kind`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScheduledKind {
+    /// delayed task added by `Context::run_later` and alike.
+    Later,
+    /// interval added by `Context::run_interval` and alike.
+    Interval,
+    /// timer with custom schedule. See `Context::run_cron`.
+    Timer,
+    /// stream added by `Context::add_stream` and alike.
+    Stream,
+}
+
+/// lightweight descriptor of a task scheduled on context. See `Context::scheduled_tasks`.
+#[derive(Clone, Copy)]
+pub struct ScheduledTask {
+    kind: ScheduledKind,
+    paused: bool,
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    created_at: CreatedAt,
+    #[cfg(feature = "std")]
+    deadline: Option<std::time::Instant>,
+}
+
+impl ScheduledTask {
+    /// kind of the task.
+    #[inline]
+    pub fn kind(&self) -> ScheduledKind {
+        self.kind
+    }
+
+    /// check if the task is paused by it's `ContextJoinHandle`.
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// the time task is added to context.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn created_at(&self) -> std::time::Instant {
+        self.created_at.0
+    }
+
+    /// time remaining before the task fires next time. zero when it's due but not handled yet.
+    ///
+    /// Return `None` for streams and timers with custom schedule.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(std::time::Instant::now()))
+    }
+}
+
+impl fmt::Debug for ScheduledTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut fmt = f.debug_struct("ScheduledTask");
+        fmt.field("kind", &self.kind).field("paused", &self.paused);
+
+        #[cfg(feature = "std")]
+        fmt.field("created_at", &self.created_at.0)
+            .field("remaining", &self.remaining());
+
+        fmt.finish()
+    }
+}
+
+// idle timer of actor. See `Actor::idle_timeout`.
+//
+// The timer is not reset on every message. Instead messages mark the timer active and an active
+// timer would re-arm itself when it fires.
+pub(crate) struct IdleTimer<A: Actor> {
+    dur: Duration,
+    delay: Pin<Box<<A::Runtime as RuntimeService>::Sleep>>,
+    active: bool,
+}
+
+impl<A: Actor> IdleTimer<A> {
+    pub(crate) fn new(dur: Duration) -> Self {
+        Self {
+            dur,
+            delay: Box::pin(<A::Runtime as RuntimeService>::sleep(dur)),
+            active: false,
+        }
+    }
+
+    /// mark the actor as active.
+    pub(crate) fn active(&mut self) {
+        self.active = true;
+    }
+
+    /// resolve to a message calling `Actor::on_idle` when no message is received for a timer's
+    /// duration.
+    pub(crate) fn poll_idle(&mut self, cx: &mut StdContext<'_>) -> Poll<ActorMessage<A>> {
+        loop {
+            ready!(self.delay.as_mut().poll(cx));
+
+            self.delay = Box::pin(<A::Runtime as RuntimeService>::sleep(self.dur));
+
+            if !core::mem::replace(&mut self.active, false) {
+                let msg = FunctionMutMessage::<_, ()>::new(on_idle::<A>);
+                return Poll::Ready(ActorMessage::new_mut(msg, None));
+            }
+        }
+    }
+}
+
+// token bucket limiting messages taken from actor's channel. See `ActorBuilder::rate_limit`.
+//
+// The bucket is refilled when a full duration passed since it's drained. So there are at most
+// count of messages taken in any window of the duration.
+pub(crate) struct RateLimiter<A: Actor> {
+    count: usize,
+    dur: Duration,
+    tokens: usize,
+    delay: Option<Pin<Box<<A::Runtime as RuntimeService>::Sleep>>>,
+}
+
+impl<A: Actor> RateLimiter<A> {
+    pub(crate) fn new(count: usize, dur: Duration) -> Self {
+        assert!(count > 0, "rate limit count cannot be zero");
+        Self {
+            count,
+            dur,
+            tokens: count,
+            delay: None,
+        }
+    }
+
+    /// take a token for a message received from channel.
+    pub(crate) fn acquire(&mut self) {
+        self.tokens -= 1;
+        if self.tokens == 0 {
+            self.delay = Some(Box::pin(<A::Runtime as RuntimeService>::sleep(self.dur)));
+        }
+    }
+
+    /// resolve when there is token available.
+    pub(crate) fn poll_ready(&mut self, cx: &mut StdContext<'_>) -> Poll<()> {
+        if let Some(delay) = self.delay.as_mut() {
+            ready!(delay.as_mut().poll(cx));
+            self.delay = None;
+            self.tokens = self.count;
+        }
+        Poll::Ready(())
+    }
+}
+
+fn on_idle<'a, A: Actor>(act: &'a mut A, ctx: Context<'a, A>) -> LocalBoxFuture<'a, ()> {
+    Box::pin(act.on_idle(ctx))
+}
+
+// exclusive task calling `Actor::on_mailbox_full`. See `Actor::mailbox_full_threshold`.
+pub(crate) fn mailbox_full_task<A: Actor>() -> Box<dyn MessageHandler<A> + Send> {
+    let msg = FunctionMutMessage::<_, ()>::new(on_mailbox_full::<A>);
+    Box::new(MessageContainer::new(msg, None))
+}
+
+fn on_mailbox_full<'a, A: Actor>(act: &'a mut A, ctx: Context<'a, A>) -> LocalBoxFuture<'a, ()> {
+    Box::pin(act.on_mailbox_full(ctx))
+}
+
+// exclusive task resolving a future. See `Context::wait`.
+pub(crate) fn wait_task<A, F>(fut: F) -> Box<dyn MessageHandler<A> + Send>
+where
+    A: Actor,
+    F: Future<Output = ()> + 'static,
+{
+    function_mut_task(move |_, _| Box::pin(fut))
+}
+
+pub(crate) fn function_task<A, F>(func: F) -> Box<dyn MessageHandler<A> + Send>
+where
+    A: Actor,
+    F: for<'a> FnOnce(&'a A, Context<'a, A>) -> LocalBoxFuture<'a, ()> + 'static,
+{
+    Box::new(MessageContainer::new(
+        FunctionMessage::<_, ()>::new(func),
+        None,
+    ))
+}
+
+fn function_mut_task<A, F>(func: F) -> Box<dyn MessageHandler<A> + Send>
+where
+    A: Actor,
+    F: for<'a> FnOnce(&'a mut A, Context<'a, A>) -> LocalBoxFuture<'a, ()> + 'static,
+{
+    Box::new(MessageContainer::new(
+        FunctionMutMessage::<_, ()>::new(func),
+        None,
+    ))
+}
+
+// completion order of concurrent messages from the same `Addr`. See `ActorBuilder::ordered`.
+//
+// Every message holds the receiver notified when the previous message from it's sender is
+// completed and the sender notifying the next one.
+pub(crate) struct SenderOrder {
+    last: BTreeMap<usize, OneshotReceiver<()>>,
+    cap: usize,
+}
+
+impl SenderOrder {
+    pub(crate) fn new(cap: usize) -> Self {
+        Self {
+            last: BTreeMap::new(),
+            cap,
+        }
+    }
+
+    pub(crate) fn next(&mut self, sender: usize) -> Order {
+        let (tx, rx) = oneshot();
+        let prev = self.last.insert(sender, rx);
+
+        // remove senders with no message in progress.
+        if self.last.len() > self.cap * 2 {
+            self.last.retain(|_, rx| !rx.is_closed());
+        }
+
+        Order { prev, _next: tx }
+    }
+}
+
+pub(crate) struct Order {
+    prev: Option<OneshotReceiver<()>>,
+    // dropped when message is completed and notify the next message.
+    _next: OneshotSender<()>,
+}
+
+impl Order {
+    // wait for the previous message from the same sender completed.
+    pub(crate) async fn wait(&mut self) {
+        if let Some(prev) = self.prev.take() {
+            let _ = prev.await;
+        }
+    }
+}
+
+// message object produce a new message from closure on every clone.
+pub(crate) struct MessageFactory<F, M> {
+    f: F,
+    _msg: PhantomData<fn() -> M>,
+}
+
+impl<F, M> MessageFactory<F, M> {
+    pub(crate) fn new(f: F) -> Self {
+        Self {
+            f,
+            _msg: PhantomData,
+        }
+    }
+}
+
+impl<A, F, M> MessageObjectClone<A> for MessageFactory<F, M>
+where
+    A: Actor + Handler<M>,
+    F: Fn() -> M,
+    M: Message + Send,
+{
+    fn clone_object(&self) -> Box<dyn MessageHandler<A> + Send> {
+        Box::new(MessageContainer::new_send((self.f)(), None))
+    }
+}
+
+// message object calling a shared FnMut closure on every clone. See `Context::run_interval_mut`.
+pub(crate) struct IntervalFn<F, R> {
+    f: Rc<RefCell<F>>,
+    cancel: TaskCancel,
+    _res: PhantomData<fn() -> R>,
+}
+
+impl<F, R> IntervalFn<F, R> {
+    pub(crate) fn new(f: F, cancel: TaskCancel) -> Self {
+        Self {
+            f: Rc::new(RefCell::new(f)),
+            cancel,
+            _res: PhantomData,
+        }
+    }
+}
+
+impl<A, F, R> MessageObjectClone<A> for IntervalFn<F, R>
+where
+    A: Actor,
+    F: for<'a> FnMut(&'a A, Context<'a, A>) -> LocalBoxFuture<'a, R> + 'static,
+    R: IntervalFlow,
+{
+    fn clone_object(&self) -> Box<dyn MessageHandler<A> + Send> {
+        let f = self.f.clone();
+        let cancel = self.cancel.clone();
+        function_task(move |act, ctx| {
+            // the returned future does not borrow the closure so it's released right away.
+            let fut = (f.borrow_mut())(act, ctx);
+            Box::pin(async move {
+                if fut.await.is_break() {
+                    cancel.cancel();
+                }
+            })
+        })
+    }
+}
+
+// exclusive variant of IntervalFn. See `Context::run_wait_interval_mut`.
+pub(crate) struct IntervalWaitFn<F, R> {
+    f: Rc<RefCell<F>>,
+    cancel: TaskCancel,
+    _res: PhantomData<fn() -> R>,
+}
+
+impl<F, R> IntervalWaitFn<F, R> {
+    pub(crate) fn new(f: F, cancel: TaskCancel) -> Self {
+        Self {
+            f: Rc::new(RefCell::new(f)),
+            cancel,
+            _res: PhantomData,
+        }
+    }
+}
+
+impl<A, F, R> MessageObjectClone<A> for IntervalWaitFn<F, R>
+where
+    A: Actor,
+    F: for<'a> FnMut(&'a mut A, Context<'a, A>) -> LocalBoxFuture<'a, R> + 'static,
+    R: IntervalFlow,
+{
+    fn clone_object(&self) -> Box<dyn MessageHandler<A> + Send> {
+        let f = self.f.clone();
+        let cancel = self.cancel.clone();
+        function_mut_task(move |act, ctx| {
+            let fut = (f.borrow_mut())(act, ctx);
+            Box::pin(async move {
+                if fut.await.is_break() {
+                    cancel.cancel();
+                }
+            })
+        })
+    }
+}
+
 // interval message passed to Context<Actor>.
 pub(crate) struct IntervalMessage<A: Actor> {
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
     dur: Duration,
-    delay: Pin<Box<<A::Runtime as RuntimeService>::Sleep>>,
-    handle: Option<OneshotReceiver<()>>,
+    interval: <A::Runtime as RuntimeService>::Interval,
+    handle: TaskHandle,
     msg: ActorMessageClone<A>,
+    paused: bool,
+    created_at: CreatedAt,
+    #[cfg(feature = "std")]
+    deadline: std::time::Instant,
 }
 
 impl<A: Actor> IntervalMessage<A> {
-    pub(crate) fn new(dur: Duration, rx: OneshotReceiver<()>, msg: ActorMessageClone<A>) -> Self {
+    pub(crate) fn new(dur: Duration, handle: TaskHandle, msg: ActorMessageClone<A>) -> Self {
+        let created_at = CreatedAt::now();
         Self {
             dur,
-            delay: Box::pin(<A::Runtime as RuntimeService>::sleep(dur)),
-            handle: Some(rx),
+            interval: RuntimeInterval::new(dur, A::missed_tick_behavior()),
+            handle,
             msg,
+            paused: false,
+            #[cfg(feature = "std")]
+            deadline: created_at.0 + dur,
+            created_at,
+        }
+    }
+
+    // restart the period of interval from now.
+    fn reset(&mut self) {
+        self.interval.reset();
+        #[cfg(feature = "std")]
+        {
+            self.deadline = std::time::Instant::now() + self.dur;
+        }
+    }
+
+    fn poll_tick(&mut self, cx: &mut StdContext<'_>) -> Poll<()> {
+        ready!(self.interval.poll_tick(cx));
+        #[cfg(feature = "std")]
+        {
+            let now = std::time::Instant::now();
+            self.deadline = A::missed_tick_behavior().next_deadline(self.deadline, now, self.dur);
+        }
+        Poll::Ready(())
+    }
+
+    fn describe(&self) -> ScheduledTask {
+        ScheduledTask {
+            kind: ScheduledKind::Interval,
+            paused: self.handle.is_paused(),
+            created_at: self.created_at,
+            #[cfg(feature = "std")]
+            deadline: Some(self.deadline),
         }
     }
 }
@@ -229,27 +838,118 @@ impl<A: Actor> Stream for IntervalMessage<A> {
     fn poll_next(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
 
-        if let Some(h) = this.handle.as_mut() {
-            match Pin::new(h).poll(cx) {
-                // handle canceled. resolve with nothing.
-                Poll::Ready(Ok(())) => return Poll::Ready(None),
-                // handle dropped. the task is now detached.
-                Poll::Ready(Err(_)) => this.handle = None,
-                Poll::Pending => {}
-            }
+        // handle canceled. resolve with nothing.
+        if this.handle.poll_cancel(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+
+        // interval paused. it would be woken up by ContextJoinHandle::resume.
+        if this.handle.is_paused() {
+            this.paused = true;
+            return Poll::Pending;
+        }
+
+        // ticks missed when paused are skipped. restart the interval from now.
+        if this.paused {
+            this.paused = false;
+            this.reset();
         }
 
-        ready!(Pin::new(&mut this.delay).poll(cx));
+        ready!(this.poll_tick(cx));
 
-        this.delay = Box::pin(<A::Runtime as RuntimeService>::sleep(this.dur));
-        // wake self one more time to register the new sleep.
+        // wake self one more time to register the next tick.
         cx.waker().wake_by_ref();
         Poll::Ready(Some(this.msg.clone()))
     }
 }
 
+// cron schedule message passed to Context<Actor>. See `Context::run_cron`.
+#[cfg(feature = "cron")]
+pub(crate) struct CronMessage<A: Actor> {
+    schedule: cron::Schedule,
+    // next tick is always after the last one. sleep can resolve slightly ahead of deadline.
+    last: chrono::DateTime<chrono::Utc>,
+    delay: Option<Pin<Box<<A::Runtime as RuntimeService>::Sleep>>>,
+    handle: TaskHandle,
+    msg: ActorMessageClone<A>,
+}
+
+#[cfg(feature = "cron")]
+impl<A: Actor> CronMessage<A> {
+    pub(crate) fn new(
+        schedule: cron::Schedule,
+        handle: TaskHandle,
+        msg: ActorMessageClone<A>,
+    ) -> Self {
+        Self {
+            schedule,
+            last: chrono::Utc::now(),
+            delay: None,
+            handle,
+            msg,
+        }
+    }
+}
+
+#[cfg(feature = "cron")]
+impl<A: Actor> Stream for CronMessage<A> {
+    type Item = ActorMessage<A>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        // handle canceled. resolve with nothing.
+        if this.handle.poll_cancel(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+
+        // schedule paused. ticks missed when paused are skipped by evaluating the next tick
+        // on resume.
+        if this.handle.is_paused() {
+            this.delay = None;
+            return Poll::Pending;
+        }
+
+        loop {
+            match this.delay {
+                Some(ref mut delay) => {
+                    ready!(delay.as_mut().poll(cx));
+                    this.delay = None;
+                    // wake self one more time to register the next sleep.
+                    cx.waker().wake_by_ref();
+                    return Poll::Ready(Some(this.msg.clone()));
+                }
+                None => {
+                    let now = chrono::Utc::now();
+                    let after = core::cmp::max(now, this.last);
+                    // schedule has no upcoming tick. end the stream.
+                    let next = match this.schedule.after(&after).next() {
+                        Some(next) => next,
+                        None => return Poll::Ready(None),
+                    };
+                    this.last = next;
+                    let dur = (next - now).to_std().unwrap_or_default();
+                    this.delay = Some(Box::pin(<A::Runtime as RuntimeService>::sleep(dur)));
+                }
+            }
+        }
+    }
+}
+
 pub(crate) enum StreamMessage<A: Actor> {
     Interval(IntervalMessage<A>),
+    // timer with custom schedule. See `Context::run_cron`.
+    Timer(
+        LocalBoxStream<'static, ActorMessage<A>>,
+        CreatedAt,
+        TaskCancel,
+    ),
+    // stream added to context. See `Context::add_stream`.
+    Stream(
+        LocalBoxStream<'static, ActorMessage<A>>,
+        CreatedAt,
+        TaskCancel,
+    ),
     Boxed(LocalBoxStream<'static, ActorMessage<A>>),
 }
 
@@ -258,12 +958,53 @@ impl<A: Actor> StreamMessage<A> {
         Self::Interval(msg)
     }
 
+    #[cfg_attr(not(feature = "cron"), allow(dead_code))]
+    pub(crate) fn new_timer<S>(stream: S, handle: TaskCancel) -> Self
+    where
+        S: Stream<Item = ActorMessage<A>> + 'static,
+    {
+        Self::Timer(Box::pin(stream), CreatedAt::now(), handle)
+    }
+
+    pub(crate) fn new_stream<S>(stream: S, handle: TaskCancel) -> Self
+    where
+        S: Stream<Item = ActorMessage<A>> + 'static,
+    {
+        Self::Stream(Box::pin(stream), CreatedAt::now(), handle)
+    }
+
     pub(crate) fn new_boxed<S>(stream: S) -> Self
     where
         S: Stream<Item = ActorMessage<A>> + 'static,
     {
         Self::Boxed(Box::pin(stream))
     }
+
+    pub(crate) fn is_timer(&self) -> bool {
+        matches!(self, Self::Interval(_) | Self::Timer(..))
+    }
+
+    pub(crate) fn is_stream(&self) -> bool {
+        matches!(self, Self::Stream(..))
+    }
+
+    /// descriptor of the stream. None for streams used internally by context.
+    pub(crate) fn describe(&self) -> Option<ScheduledTask> {
+        let (kind, created_at, handle) = match *self {
+            Self::Interval(ref msg) => return Some(msg.describe()),
+            Self::Timer(_, created_at, ref handle) => (ScheduledKind::Timer, created_at, handle),
+            Self::Stream(_, created_at, ref handle) => (ScheduledKind::Stream, created_at, handle),
+            Self::Boxed(_) => return None,
+        };
+
+        Some(ScheduledTask {
+            kind,
+            paused: handle.is_paused(),
+            created_at,
+            #[cfg(feature = "std")]
+            deadline: None,
+        })
+    }
 }
 
 impl<A: Actor> Stream for StreamMessage<A> {
@@ -272,8 +1013,144 @@ impl<A: Actor> Stream for StreamMessage<A> {
     fn poll_next(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Option<Self::Item>> {
         match self.get_mut() {
             StreamMessage::Interval(stream) => Pin::new(stream).poll_next(cx),
-            StreamMessage::Boxed(stream) => stream.as_mut().poll_next(cx),
+            StreamMessage::Timer(stream, ..)
+            | StreamMessage::Stream(stream, ..)
+            | StreamMessage::Boxed(stream) => stream.as_mut().poll_next(cx),
+        }
+    }
+}
+
+// stream yield the output of future once.
+pub(crate) struct FutureStream<Fut>(Option<Fut>);
+
+impl<Fut> FutureStream<Fut> {
+    pub(crate) fn new(fut: Fut) -> Self {
+        Self(Some(fut))
+    }
+}
+
+impl<Fut: Future + Unpin> Stream for FutureStream<Fut> {
+    type Item = Fut::Output;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        match this.0.as_mut() {
+            Some(fut) => {
+                let item = ready!(Pin::new(fut).poll(cx));
+                this.0 = None;
+                Poll::Ready(Some(item))
+            }
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+// message counted as in flight until it's handled. See `Context::add_stream_limited`.
+pub(crate) struct InFlightMessage<M> {
+    pub(crate) msg: M,
+    pub(crate) permit: InFlightPermit,
+}
+
+impl<M: Message> Message for InFlightMessage<M> {
+    type Result = M::Result;
+
+    fn size_hint_bytes(&self) -> usize {
+        self.msg.size_hint_bytes()
+    }
+}
+
+// in flight count shared by stream and it's messages.
+struct InFlightState {
+    count: Cell<usize>,
+    waker: RefCell<Option<Waker>>,
+}
+
+pub(crate) struct InFlightPermit(Rc<InFlightState>);
+
+impl Drop for InFlightPermit {
+    fn drop(&mut self) {
+        let state = &self.0;
+        state.count.set(state.count.get() - 1);
+        if let Some(waker) = state.waker.borrow_mut().take() {
+            waker.wake();
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    // stream stop producing message when max in flight messages is reached.
+    pub(crate) struct InFlightStream<S> {
+        #[pin]
+        stream: S,
+        max: usize,
+        state: Rc<InFlightState>,
+    }
+}
+
+impl<S> InFlightStream<S> {
+    pub(crate) fn new(stream: S, max: usize) -> Self {
+        Self {
+            stream,
+            max: core::cmp::max(max, 1),
+            state: Rc::new(InFlightState {
+                count: Cell::new(0),
+                waker: RefCell::new(None),
+            }),
+        }
+    }
+}
+
+impl<S: Stream> Stream for InFlightStream<S> {
+    type Item = InFlightMessage<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        // woken up when one of the in flight message is handled.
+        if this.state.count.get() >= *this.max {
+            *this.state.waker.borrow_mut() = Some(cx.waker().clone());
+            return Poll::Pending;
         }
+
+        let state = this.state;
+        this.stream.poll_next(cx).map(|item| {
+            item.map(|msg| {
+                state.count.set(state.count.get() + 1);
+                InFlightMessage {
+                    msg,
+                    permit: InFlightPermit(state.clone()),
+                }
+            })
+        })
+    }
+}
+
+pin_project_lite::pin_project! {
+    // stream map it's items to message. See `Context::add_stream_map`.
+    pub(crate) struct MapStream<S, F> {
+        #[pin]
+        stream: S,
+        f: F,
+    }
+}
+
+impl<S, F> MapStream<S, F> {
+    pub(crate) fn new(stream: S, f: F) -> Self {
+        Self { stream, f }
+    }
+}
+
+impl<S, F, M> Stream for MapStream<S, F>
+where
+    S: Stream,
+    F: FnMut(S::Item) -> M,
+{
+    type Item = M;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let f = this.f;
+        this.stream.poll_next(cx).map(|item| item.map(f))
     }
 }
 
@@ -281,17 +1158,17 @@ pin_project_lite::pin_project! {
     pub(crate) struct StreamContainer<A, S, F> {
         #[pin]
         stream: S,
-        handle: Option<OneshotReceiver<()>>,
+        handle: TaskHandle,
         to_msg: F,
         _act: PhantomData<A>,
     }
 }
 
 impl<A, S, F> StreamContainer<A, S, F> {
-    pub(crate) fn new(stream: S, handle: OneshotReceiver<()>, to_msg: F) -> Self {
+    pub(crate) fn new(stream: S, handle: TaskHandle, to_msg: F) -> Self {
         Self {
             stream,
-            handle: Some(handle),
+            handle,
             to_msg,
             _act: PhantomData,
         }
@@ -310,14 +1187,14 @@ where
     fn poll_next(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Option<Self::Item>> {
         let this = self.project();
 
-        if let Some(h) = this.handle.as_mut() {
-            match Pin::new(h).poll(cx) {
-                // handle canceled. resolve with nothing.
-                Poll::Ready(Ok(())) => return Poll::Ready(None),
-                // handle dropped. the task is now detached.
-                Poll::Ready(Err(_)) => *this.handle = None,
-                Poll::Pending => {}
-            }
+        // handle canceled. resolve with nothing.
+        if this.handle.poll_cancel(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+
+        // stream paused. it would be woken up by ContextJoinHandle::resume.
+        if this.handle.is_paused() {
+            return Poll::Pending;
         }
 
         match ready!(this.stream.poll_next(cx)) {
@@ -330,6 +1207,50 @@ where
     }
 }
 
+pin_project_lite::pin_project! {
+    // future spawned on context. See `Context::spawn`.
+    pub(crate) struct SpawnedFuture<A, Fut> {
+        #[pin]
+        fut: Fut,
+        handle: TaskHandle,
+        _act: PhantomData<fn() -> A>,
+    }
+}
+
+impl<A, Fut> SpawnedFuture<A, Fut> {
+    pub(crate) fn new(fut: Fut, handle: TaskHandle) -> Self {
+        Self {
+            fut,
+            handle,
+            _act: PhantomData,
+        }
+    }
+}
+
+impl<A, Fut> Stream for SpawnedFuture<A, Fut>
+where
+    Fut: Future<Output = ()>,
+{
+    type Item = ActorMessage<A>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        // handle canceled. resolve with nothing.
+        if this.handle.poll_cancel(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+
+        // future paused. it would be woken up by ContextJoinHandle::resume.
+        if this.handle.is_paused() {
+            return Poll::Pending;
+        }
+
+        // future never produce message.
+        this.fut.poll(cx).map(|_| None)
+    }
+}
+
 // main type of message goes through actor's channel.
 pub enum ActorMessage<A> {
     Ref(Box<dyn MessageHandler<A> + Send>),
@@ -341,16 +1262,95 @@ impl<A> ActorMessage<A> {
     pub(crate) fn new_ref<M>(msg: M, tx: Option<OneshotSender<M::Result>>) -> Self
     where
         A: Handler<M>,
-        M: Message,
+        M: Message + Send,
     {
-        Self::Ref(Box::new(MessageContainer { msg: Some(msg), tx }))
+        Self::Ref(Box::new(MessageContainer::new_send(msg, tx)))
     }
 
     pub(crate) fn new_mut<M>(msg: M, tx: Option<OneshotSender<M::Result>>) -> Self
+    where
+        A: Handler<M>,
+        M: Message + Send,
+    {
+        Self::Mut(Box::new(MessageContainer::new_send(msg, tx)))
+    }
+
+    // message of `Context` and `LocalAddr` can be !Send. It's never passed as dead letter.
+    pub(crate) fn new_ref_local<M>(msg: M, tx: Option<OneshotSender<M::Result>>) -> Self
+    where
+        A: Handler<M>,
+        M: Message,
+    {
+        Self::Ref(Box::new(MessageContainer::new(msg, tx)))
+    }
+
+    pub(crate) fn new_mut_local<M>(msg: M, tx: Option<OneshotSender<M::Result>>) -> Self
     where
         A: Handler<M>,
         M: Message,
     {
-        Self::Mut(Box::new(MessageContainer { msg: Some(msg), tx }))
+        Self::Mut(Box::new(MessageContainer::new(msg, tx)))
+    }
+}
+
+impl<A: Actor> ActorMessage<A> {
+    // estimated bytes of message. used by actor's mailbox memory budget.
+    pub(crate) fn size(&self) -> usize {
+        match self {
+            Self::Ref(msg) | Self::Mut(msg) => msg.size(),
+            Self::State(..) => 0,
+        }
+    }
+
+    // take back the message of given type. panic when message is not constructed from it.
+    pub(crate) fn into_message<M: Message>(self) -> M {
+        match self.downcast() {
+            Ok(msg) => msg,
+            Err(_) => panic!("message type mismatch"),
+        }
+    }
+
+    // take back the message when it's the given type.
+    pub(crate) fn downcast<M: Message>(mut self) -> Result<M, Self> {
+        match self {
+            Self::Ref(ref mut msg) | Self::Mut(ref mut msg) => {
+                match msg.as_any_mut().downcast_mut::<MessageContainer<M>>() {
+                    Some(c) => Ok(c.msg.take().expect("message is already taken")),
+                    None => Err(self),
+                }
+            }
+            Self::State(..) => Err(self),
+        }
+    }
+
+    // take the message boxed as Any.
+    pub(crate) fn into_any(self) -> Option<Box<dyn Any + Send>> {
+        match self {
+            Self::Ref(mut msg) | Self::Mut(mut msg) => msg.take_any(),
+            Self::State(..) => None,
+        }
+    }
+
+    // tag message with id of the `Addr` sending it.
+    pub(crate) fn with_sender(mut self, sender: usize) -> Self {
+        if let Self::Ref(ref mut msg) | Self::Mut(ref mut msg) = self {
+            msg.set_sender(sender);
+        }
+        self
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn envelope(&self) -> Option<Envelope> {
+        match self {
+            Self::Ref(msg) | Self::Mut(msg) => Some(msg.envelope()),
+            Self::State(..) => None,
+        }
+    }
+
+    pub(crate) fn type_name(&self) -> &'static str {
+        match self {
+            Self::Ref(msg) | Self::Mut(msg) => msg.type_name(),
+            Self::State(..) => core::any::type_name::<ActorState>(),
+        }
     }
 }
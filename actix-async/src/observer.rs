@@ -0,0 +1,105 @@
+use core::time::Duration;
+
+use super::util::smart_pointer::RefCounter;
+
+/// observer of messages going through actor's mailbox. Installed per actor with
+/// `ActorBuilder::observer`.
+///
+/// It's the extension point for metrics and tracing integrations. Every method is called with
+/// the type name of message and default to do nothing.
+///
+/// *. `MailboxObserver::on_enqueue` is called on the thread message is sent from. Other methods
+/// are called on the thread actor runs on.
+///
+/// # example:
+/// ```rust
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use actix_async::prelude::*;
+///
+/// struct TestActor;
+/// actor!(TestActor);
+///
+/// struct TestMessage;
+/// message!(TestMessage, ());
+///
+/// #[actix_async::handler]
+/// impl Handler<TestMessage> for TestActor {
+///     async fn handle(&self, _: TestMessage, _: Context<'_, Self>) {}
+/// }
+///
+/// #[derive(Default)]
+/// struct Metrics {
+///     handled: AtomicUsize,
+/// }
+///
+/// impl MailboxObserver for Metrics {
+///     fn on_handle_end(&self, type_name: &'static str, elapsed: Duration) {
+///         println!("{} handled in {:?}", type_name, elapsed);
+///         self.handled.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// #[actix_async::main]
+/// async fn main() {
+///     let metrics = Arc::new(Metrics::default());
+///
+///     let addr = TestActor::builder()
+///         .observer(metrics.clone())
+///         .start(TestActor);
+///
+///     addr.send(TestMessage).await.unwrap();
+///     assert_eq!(metrics.handled.load(Ordering::Relaxed), 1);
+/// }
+/// ```
+pub trait MailboxObserver: Send + Sync + 'static {
+    /// message is enqueued to actor's mailbox.
+    #[inline]
+    fn on_enqueue(&self, type_name: &'static str) {
+        let _ = type_name;
+    }
+
+    /// message is taken from actor's mailbox. `waited` is the time it spent in mailbox.
+    ///
+    /// *. `waited` is always zero without `envelope` feature.
+    #[inline]
+    fn on_dequeue(&self, type_name: &'static str, waited: Duration) {
+        let _ = (type_name, waited);
+    }
+
+    /// handler of message is about to start.
+    #[inline]
+    fn on_handle_start(&self, type_name: &'static str) {
+        let _ = type_name;
+    }
+
+    /// handler of message is finished. `elapsed` is the time spent in handler.
+    #[inline]
+    fn on_handle_end(&self, type_name: &'static str, elapsed: Duration) {
+        let _ = (type_name, elapsed);
+    }
+}
+
+impl<O: MailboxObserver + ?Sized> MailboxObserver for RefCounter<O> {
+    #[inline]
+    fn on_enqueue(&self, type_name: &'static str) {
+        (**self).on_enqueue(type_name)
+    }
+
+    #[inline]
+    fn on_dequeue(&self, type_name: &'static str, waited: Duration) {
+        (**self).on_dequeue(type_name, waited)
+    }
+
+    #[inline]
+    fn on_handle_start(&self, type_name: &'static str) {
+        (**self).on_handle_start(type_name)
+    }
+
+    #[inline]
+    fn on_handle_end(&self, type_name: &'static str, elapsed: Duration) {
+        (**self).on_handle_end(type_name, elapsed)
+    }
+}
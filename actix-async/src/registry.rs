@@ -0,0 +1,157 @@
+#[cfg(feature = "std")]
+use core::{any::TypeId, cell::RefCell};
+
+use alloc::{collections::BTreeMap, string::String};
+
+use super::actor::Actor;
+use super::address::{Addr, AnyAddr};
+use super::util::smart_pointer::{Lock, RefCounter};
+
+/// a registry of actor addresses looked up by name.
+///
+/// Registry holds strong `Addr` of registered actors. A registered actor is never stopped with
+/// `StopReason::AddrDropped` as long as the registry or any of it's clones is alive. Call
+/// `Registry::unregister` or stop the actor explicitly to release it.
+///
+/// Registry is cheap to clone and all clones share the same addresses.
+///
+/// *. Stopped actors are removed from registry automatically.
+///
+/// # example:
+/// ```rust
+/// use actix_async::prelude::*;
+/// use actix_async::registry::Registry;
+///
+/// struct DbWriter;
+/// actor!(DbWriter);
+///
+/// #[actix_async::main]
+/// async fn main() {
+///     let registry = Registry::new();
+///
+///     registry.register("db-writer", DbWriter.start());
+///
+///     let addr = registry.lookup::<DbWriter>("db-writer").unwrap();
+///
+///     // stopped actor is removed from registry.
+///     let _ = addr.stop(true).await;
+///     assert!(registry.lookup::<DbWriter>("db-writer").is_none());
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Registry {
+    actors: RefCounter<Lock<BTreeMap<String, AnyAddr>>>,
+}
+
+impl Registry {
+    /// construct an empty registry.
+    pub fn new() -> Self {
+        Self {
+            actors: RefCounter::new(Lock::new(BTreeMap::new())),
+        }
+    }
+
+    /// register actor's address with given name. Return the previous address registered with
+    /// the same name if it's actor is not stopped.
+    pub fn register<A: Actor>(&self, name: impl Into<String>, addr: Addr<A>) -> Option<AnyAddr> {
+        let mut actors = self.actors.lock();
+        // remove actors that are already stopped.
+        actors.retain(|_, addr| addr.is_connected());
+        actors.insert(name.into(), addr.into_any())
+    }
+
+    /// remove the address registered with given name.
+    pub fn unregister(&self, name: &str) -> Option<AnyAddr> {
+        self.actors
+            .lock()
+            .remove(name)
+            .filter(|addr| addr.is_connected())
+    }
+
+    /// lookup the address registered with given name. Return `None` when the name is not
+    /// registered, the actor is stopped or the actor is not the given type.
+    pub fn lookup<A: Actor>(&self, name: &str) -> Option<Addr<A>> {
+        self.lookup_any(name)?.downcast().ok()
+    }
+
+    /// lookup the type erased address registered with given name.
+    pub fn lookup_any(&self, name: &str) -> Option<AnyAddr> {
+        let mut actors = self.actors.lock();
+        match actors.get(name) {
+            Some(addr) if addr.is_connected() => Some(addr.clone()),
+            Some(_) => {
+                actors.remove(name);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// an actor started on first use and shared by the current thread.
+///
+/// `SystemService::from_registry` always return the same `Addr` on the current thread. The
+/// actor is started again when the previous one is stopped.
+///
+/// *. Like `Registry` the thread holds strong `Addr` of service actor. It keeps running until
+/// it's stopped explicitly or the thread exits.
+///
+/// # example:
+/// ```rust
+/// use actix_async::prelude::*;
+/// use actix_async::registry::SystemService;
+///
+/// #[derive(Default)]
+/// struct Metrics;
+/// actor!(Metrics);
+///
+/// impl SystemService for Metrics {}
+///
+/// #[actix_async::main]
+/// async fn main() {
+///     let addr = Metrics::from_registry();
+///
+///     assert_eq!(addr, Metrics::from_registry());
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub trait SystemService: Actor + Default {
+    /// start the service actor. Override it to start actor with customized `ActorBuilder`.
+    fn start_service() -> Addr<Self> {
+        Self::default().start()
+    }
+
+    /// get the address of service actor. Start it when it's not started yet or already
+    /// stopped.
+    fn from_registry() -> Addr<Self> {
+        let addr = SERVICES.with(|services| {
+            services
+                .borrow()
+                .get(&TypeId::of::<Self>())
+                .filter(|addr| addr.is_connected())
+                .and_then(|addr| addr.downcast_ref::<Self>().cloned())
+        });
+
+        addr.unwrap_or_else(|| {
+            // start actor without holding the borrow as it can call from_registry recursively.
+            let addr = Self::start_service();
+            SERVICES.with(|services| {
+                services
+                    .borrow_mut()
+                    .insert(TypeId::of::<Self>(), addr.clone().into_any());
+            });
+            addr
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    static SERVICES: RefCell<BTreeMap<TypeId, AnyAddr>> = const { RefCell::new(BTreeMap::new()) };
+}
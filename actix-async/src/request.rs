@@ -6,19 +6,30 @@ use core::{
     time::Duration,
 };
 
+use alloc::{boxed::Box, vec::Vec};
+
 use super::actor::Actor;
-use super::error::ActixAsyncError;
-use super::message::ActorMessage;
+use super::address::{Addr, Recipient};
+use super::error::{ActixAsyncError, TrySendError};
+use super::handler::Handler;
+use super::message::{ActorMessage, Message};
 use super::runtime::RuntimeService;
 use super::util::{
-    channel::{OneshotReceiver, SendFuture},
-    futures::LocalBoxFuture,
+    channel::{
+        ChannelBuilder, LocalSendFuture, OneshotReceiver, OneshotSender, Receiver, SendFuture,
+        Sender,
+    },
+    futures::{poll_fn, ready, BoxFuture, LocalBoxFuture, Stream},
 };
 
 /// Message request to actor with timeout setting.
 pub type MessageRequest<'a, A, R> =
     _MessageRequest<<A as Actor>::Runtime, SendFuture<'a, ActorMessage<A>>, R>;
 
+/// Message request to actor sent through it's local channel. See `LocalAddr`.
+pub type LocalMessageRequest<'a, A, R> =
+    _MessageRequest<<A as Actor>::Runtime, LocalSendFuture<'a, ActorMessage<A>>, R>;
+
 /// Box version of MessageRequest that bound to `Message::Result` type.
 pub type BoxedMessageRequest<'a, RT, R> =
     _MessageRequest<RT, LocalBoxFuture<'a, Result<(), ActixAsyncError>>, R>;
@@ -37,17 +48,121 @@ pin_project_lite::pin_project! {
             rx: OneshotReceiver<R>,
             #[pin]
             timeout: Option<RT::Sleep>,
-            timeout_response: Option<Duration>
+            timeout_response: Option<Duration>,
+            fail_fast: bool,
+            ttl: Option<Pin<Box<RT::Sleep>>>
         },
         Response {
             rx: OneshotReceiver<R>,
             #[pin]
-            timeout_response: Option<RT::Sleep>
+            timeout_response: Option<RT::Sleep>,
+            ttl: Option<Pin<Box<RT::Sleep>>>
         },
         PlaceHolder,
     }
 }
 
+/// send the same concurrent message to all recipients and collect the results in the order of
+/// recipients.
+///
+/// Messages are sent concurrently and `deadline` limits the total time of sending and
+/// receiving. A request not resolved when deadline passes resolves with
+/// `ActixAsyncError::SendTimeout` when it's message is not sent yet and
+/// `ActixAsyncError::ReceiveTimeout` otherwise.
+///
+/// # example:
+/// ```rust
+/// use std::time::Duration;
+///
+/// use actix_async::prelude::*;
+/// use actix_async::request::broadcast;
+///
+/// struct Shard(u32);
+/// actor!(Shard);
+///
+/// #[derive(Clone)]
+/// struct Count;
+/// message!(Count, u32);
+///
+/// #[actix_async::handler]
+/// impl Handler<Count> for Shard {
+///     async fn handle(&self, _: Count, _: Context<'_, Self>) -> u32 {
+///         self.0
+///     }
+/// }
+///
+/// #[actix_async::main]
+/// async fn main() {
+///     let recipients = (0..4)
+///         .map(|i| Shard(i).start().recipient())
+///         .collect::<Vec<_>>();
+///
+///     let res = broadcast(&recipients, Count, Duration::from_secs(1)).await;
+///
+///     let sum = res.into_iter().map(Result::unwrap).sum::<u32>();
+///     assert_eq!(sum, 6);
+/// }
+/// ```
+pub async fn broadcast<RT, M>(
+    recipients: &[Recipient<RT, M>],
+    msg: M,
+    deadline: Duration,
+) -> Vec<Result<M::Result, ActixAsyncError>>
+where
+    RT: RuntimeService + 'static,
+    M: Message + Send + Clone,
+{
+    let mut reqs = recipients
+        .iter()
+        .map(|re| Box::pin(re.send(msg.clone())))
+        .collect::<Vec<_>>();
+
+    let res = join_requests(&mut reqs, RT::sleep(deadline)).await;
+
+    reqs.iter()
+        .zip(res)
+        .map(|(req, res)| match res {
+            Some(res) => res,
+            None if req.is_response() => Err(ActixAsyncError::ReceiveTimeout),
+            None => Err(ActixAsyncError::SendTimeout),
+        })
+        .collect()
+}
+
+// poll requests concurrently until all of them are resolved or deadline resolves. Output of
+// request not resolved yet is None.
+pub(crate) async fn join_requests<F, D>(reqs: &mut [F], deadline: D) -> Vec<Option<F::Output>>
+where
+    F: Future + Unpin,
+    D: Future<Output = ()>,
+{
+    let mut res = reqs.iter().map(|_| None).collect::<Vec<_>>();
+
+    let mut deadline = core::pin::pin!(deadline);
+
+    poll_fn(|cx| {
+        let mut pending = false;
+
+        for (req, res) in reqs.iter_mut().zip(res.iter_mut()) {
+            if res.is_none() {
+                match Pin::new(&mut *req).poll(cx) {
+                    Poll::Ready(r) => *res = Some(r),
+                    Poll::Pending => pending = true,
+                }
+            }
+        }
+
+        if pending {
+            deadline.as_mut().poll(cx)
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .await;
+
+    res
+}
+
 const TIMEOUT_CONFIGURABLE: &str = "Timeout is not configurable after Request Future is polled";
 
 impl<RT, Fut, R> _MessageRequest<RT, Fut, R>
@@ -60,9 +175,16 @@ where
             rx,
             timeout: None,
             timeout_response: None,
+            fail_fast: false,
+            ttl: None,
         }
     }
 
+    // message is sent and request is waiting for response.
+    fn is_response(&self) -> bool {
+        matches!(self, _MessageRequest::Response { .. })
+    }
+
     /// set the timeout duration for request.
     ///
     /// Default to no timeout.
@@ -72,12 +194,16 @@ where
                 fut,
                 rx,
                 timeout_response,
+                fail_fast,
+                ttl,
                 ..
             } => _MessageRequest::Request {
                 fut,
                 rx,
                 timeout: Some(RT::sleep(dur)),
                 timeout_response,
+                fail_fast,
+                ttl,
             },
             _ => unreachable!(TIMEOUT_CONFIGURABLE),
         }
@@ -89,74 +215,498 @@ where
     pub fn timeout_response(self, dur: Duration) -> Self {
         match self {
             _MessageRequest::Request {
-                fut, rx, timeout, ..
+                fut,
+                rx,
+                timeout,
+                fail_fast,
+                ttl,
+                ..
             } => _MessageRequest::Request {
                 fut,
                 rx,
                 timeout,
                 timeout_response: Some(dur),
+                fail_fast,
+                ttl,
             },
             _ => unreachable!(TIMEOUT_CONFIGURABLE),
         }
     }
+
+    /// resolve with `ActixAsyncError::MailboxFull` instead of waiting when actor's channel is
+    /// full. The message is dropped in this case.
+    ///
+    /// Default to wait for the channel.
+    pub fn fail_fast(self) -> Self {
+        match self {
+            _MessageRequest::Request {
+                fut,
+                rx,
+                timeout,
+                timeout_response,
+                ttl,
+                ..
+            } => _MessageRequest::Request {
+                fut,
+                rx,
+                timeout,
+                timeout_response,
+                fail_fast: true,
+                ttl,
+            },
+            _ => unreachable!("{}", TIMEOUT_CONFIGURABLE),
+        }
+    }
+
+    /// set the time to live of message. When actor has not started handling the message before
+    /// the duration passes the message is discarded and request resolves with
+    /// `ActixAsyncError::Expired`. The duration starts from this call.
+    ///
+    /// *. Once actor starts handling the message it's not expired anymore. Use
+    /// `MessageRequest::timeout_response` to limit the total time of waiting for result.
+    ///
+    /// Default to no time to live.
+    pub fn ttl(self, dur: Duration) -> Self {
+        match self {
+            _MessageRequest::Request {
+                fut,
+                rx,
+                timeout,
+                timeout_response,
+                fail_fast,
+                ..
+            } => _MessageRequest::Request {
+                fut,
+                rx,
+                timeout,
+                timeout_response,
+                fail_fast,
+                ttl: Some(Box::pin(RT::sleep(dur))),
+            },
+            _ => unreachable!("{}", TIMEOUT_CONFIGURABLE),
+        }
+    }
 }
 
-impl<RT, Fut, R> Future for _MessageRequest<RT, Fut, R>
+impl<RT, Fut, R> _MessageRequest<RT, Fut, R>
 where
     RT: RuntimeService,
     Fut: Future<Output = Result<(), ActixAsyncError>>,
 {
-    type Output = Result<R, ActixAsyncError>;
+    /// forward the result to given recipient instead of returning it to caller. The result is
+    /// converted into message type `M` and sent by `AddrHandler::do_send` on the thread actor
+    /// runs on as soon as the handler finishes.
+    ///
+    /// The returned future resolves when message is sent to actor. Timeout, ttl and fail fast
+    /// settings of the request apply to the sending.
+    ///
+    /// *. `MessageRequest::timeout_response` is ignored as no one waits for the result.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::prelude::*;
+    ///
+    /// struct Parser;
+    /// actor!(Parser);
+    ///
+    /// struct Parse(&'static str);
+    /// message!(Parse, usize);
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Parse> for Parser {
+    ///     async fn handle(&self, msg: Parse, _: Context<'_, Self>) -> usize {
+    ///         msg.0.parse().unwrap()
+    ///     }
+    /// }
+    ///
+    /// struct Printer;
+    /// actor!(Printer);
+    ///
+    /// struct Print(usize);
+    /// message!(Print, ());
+    ///
+    /// impl From<usize> for Print {
+    ///     fn from(num: usize) -> Self {
+    ///         Print(num)
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Print> for Printer {
+    ///     async fn handle(&self, msg: Print, _: Context<'_, Self>) {
+    ///         println!("{}", msg.0);
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let parser = Parser.start();
+    ///     let printer = Printer.start();
+    ///
+    ///     let res = parser
+    ///         .send(Parse("996"))
+    ///         .forward_to(printer.recipient::<Print>())
+    ///         .await;
+    ///     assert!(res.is_ok());
+    /// }
+    /// ```
+    pub async fn forward_to<RT2, M>(
+        self,
+        recipient: Recipient<RT2, M>,
+    ) -> Result<(), ActixAsyncError>
+    where
+        RT2: RuntimeService + 'static,
+        M: Message + Send + From<R>,
+        R: Send + 'static,
+    {
+        let mut this = Box::pin(self);
 
-    fn poll(mut self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Self::Output> {
-        loop {
-            match self.as_mut().project() {
-                MessageRequestProj::Request { fut, timeout, .. } => match fut.poll(cx)? {
-                    Poll::Ready(()) => {
-                        match self.as_mut().project_replace(_MessageRequest::PlaceHolder) {
-                            MessageRequestReplaceProj::Request {
+        poll_fn(|cx| this.as_mut().poll_request(cx)).await?;
+
+        match this.as_mut().project_replace(_MessageRequest::PlaceHolder) {
+            MessageRequestReplaceProj::Response { rx, .. } => {
+                rx.forward(move |res| recipient.do_send(M::from(res)));
+                Ok(())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// chain a dependent request. The result of this request is converted into message type `M`
+    /// with given closure and sent to another actor as concurrent message. Resolves with the
+    /// result of the second request.
+    ///
+    /// Both hops are driven in the returned future. Timeouts set on this request only apply to
+    /// the first hop. The second request is passed to `cfg` closure where it's timeout, ttl and
+    /// fail fast settings can be configured. Pass `|req| req` to use the defaults.
+    ///
+    /// # example:
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use actix_async::prelude::*;
+    ///
+    /// struct Db;
+    /// actor!(Db);
+    ///
+    /// struct Query;
+    /// message!(Query, u32);
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Query> for Db {
+    ///     async fn handle(&self, _: Query, _: Context<'_, Self>) -> u32 {
+    ///         996
+    ///     }
+    /// }
+    ///
+    /// struct Render;
+    /// actor!(Render);
+    ///
+    /// struct Html(u32);
+    /// message!(Html, String);
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Html> for Render {
+    ///     async fn handle(&self, msg: Html, _: Context<'_, Self>) -> String {
+    ///         format!("<p>{}</p>", msg.0)
+    ///     }
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let db = Db.start();
+    ///     let render = Render.start();
+    ///
+    ///     let res = db
+    ///         .send(Query)
+    ///         .timeout(Duration::from_secs(1))
+    ///         .then_send(&render, Html, |req| req.timeout(Duration::from_secs(1)))
+    ///         .await;
+    ///     assert_eq!(res.unwrap(), "<p>996</p>");
+    /// }
+    /// ```
+    pub async fn then_send<'b, A, M, F, C>(
+        self,
+        addr: &'b Addr<A>,
+        f: F,
+        cfg: C,
+    ) -> Result<M::Result, ActixAsyncError>
+    where
+        A: Actor + Handler<M>,
+        M: Message + Send,
+        F: FnOnce(R) -> M,
+        C: FnOnce(MessageRequest<'b, A, M::Result>) -> MessageRequest<'b, A, M::Result>,
+    {
+        let res = self.await?;
+        cfg(addr.send(f(res))).await
+    }
+
+    // drive the request until message is sent to actor and the request turns into response.
+    fn poll_request(
+        mut self: Pin<&mut Self>,
+        cx: &mut StdContext<'_>,
+    ) -> Poll<Result<(), ActixAsyncError>> {
+        match self.as_mut().project() {
+            MessageRequestProj::Request {
+                fut,
+                timeout,
+                fail_fast,
+                ttl,
+                ..
+            } => match fut.poll(cx)? {
+                Poll::Ready(()) => {
+                    match self.as_mut().project_replace(_MessageRequest::PlaceHolder) {
+                        MessageRequestReplaceProj::Request {
+                            rx,
+                            timeout_response,
+                            ttl,
+                            ..
+                        } => {
+                            let timeout_response = timeout_response.map(RT::sleep);
+                            self.set(_MessageRequest::Response {
                                 rx,
                                 timeout_response,
-                                ..
-                            } => {
-                                let timeout_response = timeout_response.map(RT::sleep);
-                                self.set(_MessageRequest::Response {
-                                    rx,
-                                    timeout_response,
-                                });
-                            }
-                            // SAFETY:
-                            //
-                            // Replace always return the current variant of an enum
-                            // Which is Request in this case.
-                            _ => unsafe { hint::unreachable_unchecked() },
+                                ttl,
+                            });
+                            Poll::Ready(Ok(()))
+                        }
+                        // SAFETY:
+                        //
+                        // Replace always return the current variant of an enum
+                        // Which is Request in this case.
+                        _ => unsafe { hint::unreachable_unchecked() },
+                    }
+                }
+                Poll::Pending if *fail_fast => Poll::Ready(Err(ActixAsyncError::MailboxFull)),
+                Poll::Pending => {
+                    // message is not sent yet. drop it when expired.
+                    if let Some(ttl) = ttl.as_mut() {
+                        if ttl.as_mut().poll(cx).is_ready() {
+                            return Poll::Ready(Err(ActixAsyncError::Expired));
                         }
                     }
-                    Poll::Pending => {
-                        return match timeout.as_pin_mut() {
-                            Some(timeout) => {
-                                timeout.poll(cx).map(|_| Err(ActixAsyncError::SendTimeout))
+
+                    match timeout.as_pin_mut() {
+                        Some(timeout) => {
+                            timeout.poll(cx).map(|_| Err(ActixAsyncError::SendTimeout))
+                        }
+                        None => Poll::Pending,
+                    }
+                }
+            },
+            _ => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+impl<RT, Fut, T, E> _MessageRequest<RT, Fut, Result<T, E>>
+where
+    RT: RuntimeService,
+    Fut: Future<Output = Result<(), ActixAsyncError>>,
+{
+    /// flatten the result of handler returning `Result<T, E>` and the error of request into
+    /// one error type that can be converted from both.
+    ///
+    /// # example:
+    /// ```rust
+    /// use actix_async::address::Addr;
+    /// use actix_async::prelude::*;
+    ///
+    /// struct Db;
+    /// actor!(Db);
+    ///
+    /// struct Query(u32);
+    /// message!(Query, Result<u32, String>);
+    ///
+    /// #[actix_async::handler]
+    /// impl Handler<Query> for Db {
+    ///     async fn handle(&self, msg: Query, _: Context<'_, Self>) -> Result<u32, String> {
+    ///         if msg.0 == 0 {
+    ///             Err(String::from("not found"))
+    ///         } else {
+    ///             Ok(msg.0)
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// #[derive(Debug)]
+    /// enum AppError {
+    ///     Actor(ActixAsyncError),
+    ///     Db(String),
+    /// }
+    ///
+    /// impl From<ActixAsyncError> for AppError {
+    ///     fn from(e: ActixAsyncError) -> Self {
+    ///         Self::Actor(e)
+    ///     }
+    /// }
+    ///
+    /// impl From<String> for AppError {
+    ///     fn from(e: String) -> Self {
+    ///         Self::Db(e)
+    ///     }
+    /// }
+    ///
+    /// async fn query(db: &Addr<Db>, id: u32) -> Result<u32, AppError> {
+    ///     let res = db.send(Query(id)).flatten::<AppError>().await?;
+    ///     Ok(res)
+    /// }
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let db = Db.start();
+    ///
+    ///     assert_eq!(query(&db, 996).await.unwrap(), 996);
+    ///     assert!(matches!(query(&db, 0).await, Err(AppError::Db(_))));
+    /// }
+    /// ```
+    pub async fn flatten<E2>(self) -> Result<T, E2>
+    where
+        E2: From<E> + From<ActixAsyncError>,
+    {
+        Ok(self.await??)
+    }
+}
+
+impl<RT, Fut, R> Future for _MessageRequest<RT, Fut, R>
+where
+    RT: RuntimeService,
+    Fut: Future<Output = Result<(), ActixAsyncError>>,
+{
+    type Output = Result<R, ActixAsyncError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Self::Output> {
+        ready!(self.as_mut().poll_request(cx))?;
+
+        match self.project() {
+            MessageRequestProj::Response {
+                rx,
+                timeout_response,
+                ttl,
+            } => match Pin::new(&mut *rx).poll(cx) {
+                Poll::Ready(res) => Poll::Ready(Ok(res?)),
+                Poll::Pending => {
+                    if let Some(t) = ttl.as_mut() {
+                        if t.as_mut().poll(cx).is_ready() {
+                            // actor discards message when it's expired before handled.
+                            if rx.expire() {
+                                return Poll::Ready(Err(ActixAsyncError::Expired));
                             }
-                            None => Poll::Pending,
+                            *ttl = None;
                         }
                     }
-                },
-                MessageRequestProj::Response {
-                    rx,
-                    timeout_response,
-                } => {
-                    return match Pin::new(rx).poll(cx) {
-                        Poll::Ready(res) => Poll::Ready(Ok(res?)),
-                        Poll::Pending => match timeout_response.as_pin_mut() {
-                            Some(timeout) => timeout
-                                .poll(cx)
-                                .map(|_| Err(ActixAsyncError::ReceiveTimeout)),
-                            None => Poll::Pending,
-                        },
+
+                    match timeout_response.as_pin_mut() {
+                        Some(timeout) => timeout
+                            .poll(cx)
+                            .map(|_| Err(ActixAsyncError::ReceiveTimeout)),
+                        None => Poll::Pending,
                     }
                 }
-                MessageRequestProj::PlaceHolder => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// sink of streaming response. Obtained from `Context::response_sink` in handler.
+///
+/// See `Addr::send_stream` for example.
+pub struct ResponseSink<R> {
+    tx: Sender<R>,
+}
+
+impl<R> Clone for ResponseSink<R> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<R> ResponseSink<R> {
+    /// send an item to response stream. Wait when the stream's buffer is full.
+    ///
+    /// Return `ActixAsyncError::Closed` when the response stream is dropped.
+    pub async fn send(&self, item: R) -> Result<(), ActixAsyncError> {
+        self.tx.send(item).await
+    }
+
+    /// try to send an item to response stream without waiting.
+    pub fn try_send(&self, item: R) -> Result<(), TrySendError<R>> {
+        self.tx.try_send(item)
+    }
+
+    /// check if the response stream is dropped.
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+/// streaming response of message. See `Addr::send_stream`.
+///
+/// *. The stream ends without yielding any item when message failed to send to actor.
+pub struct ResponseStream<'a, R> {
+    fut: Option<BoxFuture<'a, Result<(), ActixAsyncError>>>,
+    rx: Receiver<R>,
+}
+
+impl<'a, R: Send + 'static> ResponseStream<'a, R> {
+    pub(crate) fn new<F>(f: F) -> Self
+    where
+        F: FnOnce(ResponseSink<R>) -> BoxFuture<'a, Result<(), ActixAsyncError>>,
+    {
+        let (tx, rx) = ChannelBuilder::new(RESPONSE_STREAM_BUFFER).build();
+        Self {
+            fut: Some(f(ResponseSink { tx })),
+            rx,
+        }
+    }
+}
+
+// max count of items buffered in ResponseStream.
+const RESPONSE_STREAM_BUFFER: usize = 16;
+
+impl<R> Stream for ResponseStream<'_, R> {
+    type Item = R;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut StdContext<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if let Some(fut) = this.fut.as_mut() {
+            let res = ready!(fut.as_mut().poll(cx));
+            this.fut = None;
+            if res.is_err() {
+                // message is dropped with the sink. close the stream.
+                this.rx.close();
             }
         }
+
+        Pin::new(&mut this.rx).poll_next(cx)
+    }
+}
+
+/// handle for sending the response of message later. Obtained from `Context::response_handle`.
+pub struct ResponseHandle<R> {
+    tx: OneshotSender<R>,
+}
+
+impl<R> ResponseHandle<R> {
+    pub(crate) fn new(tx: OneshotSender<R>) -> Self {
+        Self { tx }
+    }
+
+    /// send the response to caller.
+    ///
+    /// Return `ActixAsyncError::Closed` when caller is not waiting for it anymore.
+    pub fn send(self, res: R) -> Result<(), ActixAsyncError> {
+        self.tx.send(res)
+    }
+
+    /// check if caller is not waiting for the response anymore.
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
     }
 }
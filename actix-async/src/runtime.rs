@@ -1,4 +1,13 @@
-use core::{future::Future, time::Duration};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use alloc::boxed::Box;
+
+use super::util::futures::ready;
 
 /// Runtime trait for running actor on various runtimes.
 ///
@@ -10,6 +19,7 @@ use core::{future::Future, time::Duration};
 /// use std::time::Duration;
 ///
 /// use actix_async::prelude::*;
+/// use actix_async::runtime::SleepInterval;
 ///
 /// // runtime type.
 /// struct AsyncStdRuntime;
@@ -17,6 +27,8 @@ use core::{future::Future, time::Duration};
 /// // runtime trait method would be called in actor.
 /// impl RuntimeService for AsyncStdRuntime {
 ///     type Sleep = Pin<Box<dyn Future<Output=()> + Send + 'static>>;
+///     // interval driven by RuntimeService::sleep.
+///     type Interval = SleepInterval<Self>;
 ///
 ///     fn spawn<F: Future<Output = ()> + 'static>(f: F) {
 ///         async_std::task::spawn_local(f);
@@ -87,7 +99,132 @@ use core::{future::Future, time::Duration};
 pub trait RuntimeService: Sized {
     type Sleep: Future<Output = ()> + Send + 'static;
 
+    /// interval timer used by `Context::run_interval` and alike.
+    ///
+    /// Use `SleepInterval<Self>` when runtime has no native interval timer.
+    type Interval: RuntimeInterval;
+
     fn spawn<F: Future<Output = ()> + 'static>(f: F);
 
     fn sleep(dur: Duration) -> Self::Sleep;
+
+    /// sleep until the given deadline. See `Context::run_at`.
+    ///
+    /// Default to sleep for the duration from now to deadline. Override it when runtime has
+    /// timer driven by absolute deadline.
+    #[cfg(feature = "std")]
+    fn sleep_until(deadline: std::time::Instant) -> Self::Sleep {
+        Self::sleep(deadline.saturating_duration_since(std::time::Instant::now()))
+    }
+
+    /// run a blocking closure where blocking the thread is allowed. See `Context::add_channel`
+    /// and `Context::run_blocking`.
+    ///
+    /// Default to run the closure on a new thread.
+    #[cfg(feature = "std")]
+    fn spawn_blocking<F: FnOnce() + Send + 'static>(f: F) {
+        std::thread::spawn(f);
+    }
+}
+
+/// behavior of interval when ticks are missed because actor is busy or the thread is blocked.
+/// See `Actor::missed_tick_behavior`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MissedTickBehavior {
+    /// missed ticks fire as fast as possible until the interval catches up with it's schedule.
+    Burst,
+    /// the schedule is delayed. next tick fires one period after the missed tick is observed.
+    Delay,
+    /// missed ticks are skipped. next tick fires at the next multiple of period in schedule.
+    Skip,
+}
+
+impl MissedTickBehavior {
+    /// deadline of next tick after the tick of given deadline is observed at now.
+    #[cfg(feature = "std")]
+    pub(crate) fn next_deadline(
+        self,
+        deadline: std::time::Instant,
+        now: std::time::Instant,
+        period: Duration,
+    ) -> std::time::Instant {
+        let next = deadline + period;
+        if next > now {
+            return next;
+        }
+
+        match self {
+            Self::Burst => next,
+            Self::Delay => now + period,
+            Self::Skip => {
+                let period_nanos = period.as_nanos().max(1);
+                let missed = (now - deadline).as_nanos() / period_nanos + 1;
+                deadline + Duration::from_nanos((missed * period_nanos) as u64)
+            }
+        }
+    }
+}
+
+/// Interval timer of runtime. See `RuntimeService::Interval`.
+pub trait RuntimeInterval: Unpin + 'static {
+    /// construct an interval that first ticks after one period.
+    fn new(period: Duration, missed: MissedTickBehavior) -> Self;
+
+    /// poll for next tick of interval.
+    fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<()>;
+
+    /// restart interval so next tick fires one period from now.
+    fn reset(&mut self);
+}
+
+/// interval driven by `RuntimeService::sleep`. Used as `RuntimeService::Interval` for runtime
+/// without native interval timer.
+///
+/// *. Without `std` feature there is no clock to follow the schedule and every tick is delayed
+/// by the time actor spent observing previous tick. (Same as `MissedTickBehavior::Delay`.)
+pub struct SleepInterval<RT: RuntimeService> {
+    period: Duration,
+    sleep: Pin<Box<RT::Sleep>>,
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    missed: MissedTickBehavior,
+    #[cfg(feature = "std")]
+    deadline: std::time::Instant,
+}
+
+impl<RT: RuntimeService + 'static> RuntimeInterval for SleepInterval<RT> {
+    fn new(period: Duration, missed: MissedTickBehavior) -> Self {
+        Self {
+            period,
+            sleep: Box::pin(RT::sleep(period)),
+            missed,
+            #[cfg(feature = "std")]
+            deadline: std::time::Instant::now() + period,
+        }
+    }
+
+    fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        ready!(self.sleep.as_mut().poll(cx));
+
+        #[cfg(feature = "std")]
+        {
+            let now = std::time::Instant::now();
+            self.deadline = self.missed.next_deadline(self.deadline, now, self.period);
+            self.sleep = Box::pin(RT::sleep_until(self.deadline));
+        }
+
+        #[cfg(not(feature = "std"))]
+        {
+            self.sleep = Box::pin(RT::sleep(self.period));
+        }
+
+        Poll::Ready(())
+    }
+
+    fn reset(&mut self) {
+        #[cfg(feature = "std")]
+        {
+            self.deadline = std::time::Instant::now() + self.period;
+        }
+        self.sleep = Box::pin(RT::sleep(self.period));
+    }
 }
@@ -0,0 +1,71 @@
+use core::{any::type_name, fmt};
+
+use crate::util::smart_pointer::RefCounter;
+
+/// Escalated failure of a supervised actor instance that reached it's restart limit.
+///
+/// See [`SupervisorBuilder::on_escalate`](super::SupervisorBuilder::on_escalate).
+#[derive(Debug)]
+pub struct Escalation {
+    actor: &'static str,
+    restarts: usize,
+}
+
+impl Escalation {
+    pub(super) fn new<A>(restarts: usize) -> Self {
+        Self {
+            actor: type_name::<A>(),
+            restarts,
+        }
+    }
+
+    /// Type name of the actor.
+    pub fn actor(&self) -> &'static str {
+        self.actor
+    }
+
+    /// Count of restarts of the actor instance within the restart window.
+    pub fn restarts(&self) -> usize {
+        self.restarts
+    }
+}
+
+type EscalateFn = dyn Fn(Escalation) + Send + Sync;
+
+// handler of escalated failures of a supervisor. Failures are passed to it's own callback when
+// it's set and to the escalator of parent supervisor otherwise.
+#[derive(Clone, Default)]
+pub(super) struct Escalator {
+    f: Option<RefCounter<EscalateFn>>,
+    parent: Option<RefCounter<Escalator>>,
+}
+
+impl Escalator {
+    pub(super) fn set_callback<F>(&mut self, f: F)
+    where
+        F: Fn(Escalation) + Send + Sync + 'static,
+    {
+        self.f = Some(RefCounter::new(f));
+    }
+
+    pub(super) fn set_parent(&mut self, parent: &Escalator) {
+        self.parent = Some(RefCounter::new(parent.clone()));
+    }
+
+    pub(super) fn escalate(&self, escalation: Escalation) {
+        match (self.f.as_ref(), self.parent.as_ref()) {
+            (Some(f), _) => f(escalation),
+            (None, Some(parent)) => parent.escalate(escalation),
+            (None, None) => {}
+        }
+    }
+}
+
+impl fmt::Debug for Escalator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Escalator")
+            .field("f", &self.f.as_ref().map(|_| "Fn(Escalation)"))
+            .field("parent", &self.parent)
+            .finish()
+    }
+}
@@ -0,0 +1,151 @@
+use core::{future::Future, pin::pin, task::Poll};
+
+use tokio::sync::Notify;
+
+use crate::actor::Actor;
+use crate::address::Addr;
+use crate::context::Context;
+use crate::util::{
+    futures::poll_fn,
+    smart_pointer::{Lock, RefCounter},
+};
+
+use super::Supervisor;
+
+/// restart strategy of actors in a `SupervisorGroup`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SupervisionStrategy {
+    /// restart only the actor instance that stopped.
+    OneForOne,
+    /// restart every actor instance in group when one of them is restarted.
+    AllForOne,
+}
+
+/// A group of actors supervised together by a `Supervisor`.
+///
+/// Actors of different types can be started in one group. When an actor instance in group stops
+/// and is restarted by [`Actor::supervised`](crate::actor::Actor::supervised), the group decides
+/// which other instances are restarted with it by it's [`SupervisionStrategy`].
+///
+/// *. Instances restarted by group are restarted forcefully and do not count towards their own
+/// restart limit.
+///
+/// *. An instance that stops without restarting leaves the group.
+///
+/// # Examples
+///
+/// ```rust
+/// use actix_async::prelude::*;
+/// use actix_async::supervisor::{SupervisionStrategy, Supervisor};
+///
+/// struct Database;
+/// actor!(Database);
+///
+/// struct Cache;
+/// actor!(Cache);
+///
+/// #[actix_async::main]
+/// async fn main() {
+///     let supervisor = Supervisor::builder().workers(1).build();
+///
+///     // Database and Cache would be restarted together.
+///     let group = supervisor.group(SupervisionStrategy::AllForOne);
+///
+///     let db = group.start(|_| async { Database }).await;
+///     let cache = group.start(|_| async { Cache }).await;
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SupervisorGroup {
+    supervisor: Supervisor,
+    group: Group,
+}
+
+impl SupervisorGroup {
+    pub(super) fn new(supervisor: Supervisor, strategy: SupervisionStrategy) -> Self {
+        Self {
+            supervisor,
+            group: Group {
+                strategy,
+                members: RefCounter::new(Lock::new(Vec::new())),
+            },
+        }
+    }
+
+    /// Start one actor instance in group with the factory closure.
+    ///
+    /// See [`Supervisor::start`] for detail.
+    pub async fn start<F, Fut, A>(&self, func: F) -> Addr<A>
+    where
+        F: for<'c> Fn(Context<'c, A>) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = A> + 'static,
+        A: Actor,
+    {
+        self.supervisor._start(1, func, Some(&self.group)).await
+    }
+
+    /// The strategy of group.
+    pub fn strategy(&self) -> SupervisionStrategy {
+        self.group.strategy
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct Group {
+    strategy: SupervisionStrategy,
+    members: RefCounter<Lock<Vec<RefCounter<Notify>>>>,
+}
+
+impl Group {
+    pub(super) fn join(&self) -> GroupMember {
+        let notify = RefCounter::new(Notify::new());
+        self.members.lock().push(notify.clone());
+
+        GroupMember {
+            group: self.clone(),
+            notify,
+        }
+    }
+}
+
+// one supervised actor instance in group.
+pub(super) struct GroupMember {
+    group: Group,
+    notify: RefCounter<Notify>,
+}
+
+impl GroupMember {
+    /// wait for other member restarting this member.
+    pub(super) async fn restarted(&self) {
+        let mut notified = pin!(self.notify.notified());
+
+        // notification sent when this member is not waiting (e.g. it's restarting) leaves a
+        // permit behind. It's stale and must not restart the new instance.
+        if poll_fn(|cx| Poll::Ready(notified.as_mut().poll(cx).is_ready())).await {
+            notified.set(self.notify.notified());
+        }
+
+        notified.await
+    }
+
+    /// this member is restarted. restart other members according to group strategy.
+    pub(super) fn restart_others(&self) {
+        if self.group.strategy == SupervisionStrategy::AllForOne {
+            self.group
+                .members
+                .lock()
+                .iter()
+                .filter(|notify| !RefCounter::ptr_eq(notify, &self.notify))
+                .for_each(|notify| notify.notify_one());
+        }
+    }
+}
+
+impl Drop for GroupMember {
+    fn drop(&mut self) {
+        self.group
+            .members
+            .lock()
+            .retain(|notify| !RefCounter::ptr_eq(notify, &self.notify));
+    }
+}
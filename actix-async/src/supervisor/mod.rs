@@ -1,24 +1,36 @@
 mod error;
+mod escalate;
+mod group;
+mod restart;
 mod worker;
 
 pub use tokio::task::JoinError;
 
 pub(super) use self::error::SupervisorError;
+pub use self::escalate::Escalation;
+pub use self::group::{SupervisionStrategy, SupervisorGroup};
+pub use self::restart::BackoffStrategy;
 
 use core::{future::Future, mem, time::Duration};
 
 use tokio::{runtime::Handle, select};
 
-use super::actor::{Actor, ActorState};
+use super::actor::{actor_channel, Actor, ActorState, StopReason};
 use super::address::Addr;
 use super::context::Context;
 use super::context_future::{ContextFuture, ContextInner};
+use super::lifecycle::Lifecycle;
+use super::message::ActorMessage;
+use super::runtime::RuntimeService;
 use super::util::{
-    channel::{channel, Sender},
+    channel::{channel, Receiver, Sender},
     futures::BoxFuture,
     smart_pointer::{Lock, RefCounter},
 };
 
+use self::escalate::Escalator;
+use self::group::{Group, GroupMember};
+use self::restart::{RestartPolicy, RestartTracker};
 use self::worker::Worker;
 
 /// A supervisor can be used to manage multiple actors and/or multiple instances of the same actor
@@ -59,6 +71,8 @@ pub struct Supervisor {
     join_handles: RefCounter<Lock<Vec<Worker>>>,
     tx: Sender<BoxFuture<'static, ()>>,
     shutdown_timeout: Duration,
+    restart_policy: RestartPolicy,
+    escalator: Escalator,
 }
 
 impl Supervisor {
@@ -100,43 +114,60 @@ impl Supervisor {
         }
     }
 
+    /// Start given number of actor instances with the factory closure.
+    ///
+    /// Every instance is restarted by calling the closure again when it's future is stopped and
+    /// [`Actor::supervised`](crate::actor::Actor::supervised) returns `ActorState::Running`.
+    /// All instances share the returned `Addr` and it stays valid across restarts.
+    ///
+    /// The mailbox of actor is preserved across restarts. Messages still queued when an instance
+    /// stopped are handled by the restarted instance.
+    ///
+    /// *. Messages already taken from mailbox and being handled by the stopped instance are lost
+    /// and their senders would receive an error.
+    ///
+    /// An instance would not be restarted when it reaches the restart limit. See
+    /// [`SupervisorBuilder::max_restarts`].
     pub async fn start<F, Fut, A>(&self, num: usize, func: F) -> Addr<A>
     where
         F: for<'c> Fn(Context<'c, A>) -> Fut + Clone + Send + 'static,
         Fut: Future<Output = A> + 'static,
         A: Actor,
     {
-        let (tx, rx) = channel(A::size_hint());
+        self._start(num, func, None).await
+    }
+
+    /// Construct a group of actors supervised together with given strategy.
+    ///
+    /// See [`SupervisorGroup`] for detail.
+    pub fn group(&self, strategy: SupervisionStrategy) -> SupervisorGroup {
+        SupervisorGroup::new(self.clone(), strategy)
+    }
+
+    async fn _start<F, Fut, A>(&self, num: usize, func: F, group: Option<&Group>) -> Addr<A>
+    where
+        F: for<'c> Fn(Context<'c, A>) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = A> + 'static,
+        A: Actor,
+    {
+        let (tx, rx) = actor_channel::<A>(A::size_hint());
 
-        let addr = Addr::new(tx);
+        let lifecycle = RefCounter::new(Lifecycle::supervised());
+
+        let addr = Addr::new(tx, lifecycle.clone());
 
         for _ in 0..num {
             let rx = rx.clone();
             let func = func.clone();
+            let lifecycle = lifecycle.clone();
+            let tracker = RestartTracker::new(self.restart_policy);
+            let member = group.map(Group::join);
+            let escalator = self.escalator.clone();
 
             // TODO: handle error.
             let _ = self
                 .tx
-                .send(Box::pin(async move {
-                    loop {
-                        let func = func.clone();
-                        let rx_clone = rx.clone();
-                        let handle = tokio::task::spawn_local(async move {
-                            let ctx = ContextInner::new(rx_clone);
-                            let fut = ContextFuture::start(func, ctx).await;
-                            fut.run().await
-                        });
-
-                        let res = handle.await;
-
-                        let state = SupervisedState { error: res.err() };
-
-                        match A::supervised(state) {
-                            ActorState::Running if !rx.is_closed() => continue,
-                            _ => break,
-                        }
-                    }
-                }) as _)
+                .send(Box::pin(supervise(rx, func, lifecycle, tracker, member, escalator)) as _)
                 .await;
         }
 
@@ -144,6 +175,77 @@ impl Supervisor {
     }
 }
 
+// supervise one instance of actor. restart it when it's stopped and allowed to restart.
+async fn supervise<F, Fut, A>(
+    rx: Receiver<ActorMessage<A>>,
+    func: F,
+    lifecycle: RefCounter<Lifecycle>,
+    mut tracker: RestartTracker,
+    member: Option<GroupMember>,
+    escalator: Escalator,
+) where
+    F: for<'c> Fn(Context<'c, A>) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = A> + 'static,
+    A: Actor,
+{
+    loop {
+        let func = func.clone();
+        let rx_clone = rx.clone();
+        let lifecycle_clone = lifecycle.clone();
+        let mut handle = tokio::task::spawn_local(async move {
+            let ctx = ContextInner::new(rx_clone, lifecycle_clone);
+            let fut = ContextFuture::start(func, ctx).await;
+            fut.run().await
+        });
+
+        let res = match member {
+            Some(ref member) => select! {
+                res = &mut handle => Some(res),
+                _ = member.restarted() => None,
+            },
+            None => Some((&mut handle).await),
+        };
+
+        let res = match res {
+            Some(res) => res,
+            // restarted by group. abort the running instance and restart it.
+            None => {
+                handle.abort();
+                let _ = handle.await;
+                if rx.is_closed() {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        let state = SupervisedState {
+            reason: lifecycle.reason(),
+            error: res.err(),
+            restarts: tracker.count(),
+        };
+
+        match A::supervised(state) {
+            ActorState::Running if !rx.is_closed() => match tracker.try_restart() {
+                Some(delay) => {
+                    if let Some(ref member) = member {
+                        member.restart_others();
+                    }
+
+                    if delay > Duration::from_secs(0) {
+                        <A::Runtime as RuntimeService>::sleep(delay).await;
+                    }
+                }
+                None => {
+                    escalator.escalate(Escalation::new::<A>(tracker.count()));
+                    break;
+                }
+            },
+            _ => break,
+        }
+    }
+}
+
 impl Drop for Supervisor {
     fn drop(&mut self) {
         // Check if is the last copy of supervisor.
@@ -162,6 +264,8 @@ impl Drop for Supervisor {
 pub struct SupervisorBuilder {
     workers: usize,
     shutdown_timeout: Duration,
+    restart_policy: RestartPolicy,
+    escalator: Escalator,
 }
 
 impl Default for SupervisorBuilder {
@@ -175,6 +279,12 @@ impl SupervisorBuilder {
         SupervisorBuilder {
             workers: 4,
             shutdown_timeout: Duration::from_secs(30),
+            restart_policy: RestartPolicy {
+                max_restarts: None,
+                restart_window: Duration::from_secs(5),
+                backoff: BackoffStrategy::Immediate,
+            },
+            escalator: Escalator::default(),
         }
     }
 
@@ -195,6 +305,71 @@ impl SupervisorBuilder {
         self
     }
 
+    /// Change the max restarts of an actor instance within the restart window. Instance reached
+    /// the limit would not be restarted and stay stopped.
+    ///
+    /// Default to no limit.
+    pub fn max_restarts(mut self, max: usize) -> Self {
+        self.restart_policy.max_restarts = Some(max);
+        self
+    }
+
+    /// Change the time window of restarts counted towards the max restarts.
+    ///
+    /// Default to 5 seconds.
+    pub fn restart_window(mut self, dur: Duration) -> Self {
+        self.restart_policy.restart_window = dur;
+        self
+    }
+
+    /// Change the delay strategy between restarts of an actor instance. The delay is awaited
+    /// with `RuntimeService::sleep` of the actor.
+    ///
+    /// Default to `BackoffStrategy::Immediate`.
+    pub fn backoff(mut self, backoff: BackoffStrategy) -> Self {
+        self.restart_policy.backoff = backoff;
+        self
+    }
+
+    /// Set the callback called when an actor instance reached the restart limit and would not
+    /// be restarted. See [`SupervisorBuilder::max_restarts`].
+    ///
+    /// Default to ignore the failure.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use actix_async::supervisor::Supervisor;
+    ///
+    /// #[actix_async::main]
+    /// async fn main() {
+    ///     let supervisor = Supervisor::builder()
+    ///         .max_restarts(3)
+    ///         .on_escalate(|escalation| {
+    ///             eprintln!("{} failed too many times", escalation.actor());
+    ///         })
+    ///         .build();
+    /// }
+    /// ```
+    pub fn on_escalate<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Escalation) + Send + Sync + 'static,
+    {
+        self.escalator.set_callback(f);
+        self
+    }
+
+    /// Escalate failures to the parent supervisor. The failures are passed to the callback
+    /// set by [`SupervisorBuilder::on_escalate`] of parent and further up if parent has no
+    /// callback and it's own parent.
+    ///
+    /// *. The callback set by [`SupervisorBuilder::on_escalate`] of this supervisor takes
+    /// precedence and failures are not escalated to parent when it's set.
+    pub fn parent(mut self, parent: &Supervisor) -> Self {
+        self.escalator.set_parent(&parent.escalator);
+        self
+    }
+
     pub fn build(self) -> Supervisor {
         let (tx, rx) = channel(self.workers);
 
@@ -213,16 +388,31 @@ impl SupervisorBuilder {
             join_handles: RefCounter::new(Lock::new(workers)),
             tx,
             shutdown_timeout: self.shutdown_timeout,
+            restart_policy: self.restart_policy,
+            escalator: self.escalator,
         }
     }
 }
 
 /// peek into the output of supervised actor future after it finished.
 pub struct SupervisedState {
+    reason: Option<StopReason>,
     error: Option<JoinError>,
+    restarts: usize,
 }
 
 impl SupervisedState {
+    /// Count of restarts of the actor instance within the restart window.
+    pub fn restarts(&self) -> usize {
+        self.restarts
+    }
+
+    /// The reason actor instance stopped. `None` when the instance is aborted before it's
+    /// stopped. (e.g. restarted by it's supervisor group.)
+    pub fn reason(&self) -> Option<StopReason> {
+        self.reason.clone()
+    }
+
     /// Take the error if actor future exit with error.
     pub fn take_error(&mut self) -> Option<JoinError> {
         self.error.take()
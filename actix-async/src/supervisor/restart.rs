@@ -0,0 +1,145 @@
+use core::{
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use std::collections::{hash_map::RandomState, VecDeque};
+
+use tokio::time::Instant;
+
+/// delay strategy between restarts of supervised actor.
+///
+/// The max retries of restart is set by [`SupervisorBuilder::max_restarts`](super::SupervisorBuilder::max_restarts).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BackoffStrategy {
+    /// restart immediately.
+    Immediate,
+    /// restart after a fixed delay.
+    Fixed(Duration),
+    /// restart after a delay doubled with every restart happened within the restart window.
+    Exponential {
+        /// delay of the first restart.
+        base: Duration,
+        /// upper bound of delay.
+        max: Duration,
+        /// randomize the delay between half of it and itself. Prevent instances of actor
+        /// from restarting at the same time.
+        jitter: bool,
+    },
+}
+
+impl BackoffStrategy {
+    /// delay of restart after given restarts happened in the restart window.
+    fn delay(&self, restarts: usize) -> Duration {
+        match *self {
+            Self::Immediate => Duration::from_secs(0),
+            Self::Fixed(dur) => dur,
+            Self::Exponential { base, max, jitter } => {
+                let factor = 1u32.checked_shl(restarts as u32).unwrap_or(u32::MAX);
+                let delay = base.checked_mul(factor).map_or(max, |d| d.min(max));
+
+                if jitter {
+                    let half = delay / 2;
+                    let nanos = half.as_nanos() as u64;
+                    if nanos == 0 {
+                        delay
+                    } else {
+                        half + Duration::from_nanos(random() % nanos)
+                    }
+                } else {
+                    delay
+                }
+            }
+        }
+    }
+}
+
+// random number from the random keys of std hasher.
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// restart limit of supervised actor future.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct RestartPolicy {
+    pub(super) max_restarts: Option<usize>,
+    pub(super) restart_window: Duration,
+    pub(super) backoff: BackoffStrategy,
+}
+
+// track restarts of one supervised actor future within the restart window.
+pub(super) struct RestartTracker {
+    policy: RestartPolicy,
+    restarts: VecDeque<Instant>,
+}
+
+impl RestartTracker {
+    pub(super) fn new(policy: RestartPolicy) -> Self {
+        Self {
+            policy,
+            restarts: VecDeque::new(),
+        }
+    }
+
+    /// count of restarts happened within the restart window.
+    pub(super) fn count(&mut self) -> usize {
+        let now = Instant::now();
+        let window = self.policy.restart_window;
+
+        while let Some(instant) = self.restarts.front() {
+            if now.duration_since(*instant) > window {
+                self.restarts.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        self.restarts.len()
+    }
+
+    /// record a restart and return the backoff delay of it. return None when the restart limit
+    /// is reached.
+    pub(super) fn try_restart(&mut self) -> Option<Duration> {
+        let count = self.count();
+
+        if let Some(max) = self.policy.max_restarts {
+            if count >= max {
+                return None;
+            }
+        }
+
+        self.restarts.push_back(Instant::now());
+        Some(self.policy.backoff.delay(count))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn exponential_backoff() {
+        let backoff = BackoffStrategy::Exponential {
+            base: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+            jitter: false,
+        };
+
+        assert_eq!(backoff.delay(0), Duration::from_millis(100));
+        assert_eq!(backoff.delay(1), Duration::from_millis(200));
+        assert_eq!(backoff.delay(3), Duration::from_millis(800));
+        assert_eq!(backoff.delay(4), Duration::from_secs(1));
+        assert_eq!(backoff.delay(64), Duration::from_secs(1));
+
+        let backoff = BackoffStrategy::Exponential {
+            base: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+            jitter: true,
+        };
+
+        for _ in 0..8 {
+            let delay = backoff.delay(1);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use core::time::Duration;
+
+use alloc::{boxed::Box, vec::Vec};
+
+use tokio::time::{timeout_at, Instant};
+
+use super::actor::Actor;
+use super::address::{Addr, Watch};
+use super::util::smart_pointer::{Lock, RefCounter};
+
+/// how actors are stopped by `System::stop`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StopMode {
+    /// stop actors gracefully. Actors not stopped before the deadline are stopped forcefully.
+    Graceful {
+        /// max duration of the whole shutdown process.
+        deadline: Duration,
+    },
+    /// stop actors forcefully.
+    Forced,
+}
+
+/// A collection of actors that are shutdown together.
+///
+/// Actors registered to system are stopped one by one in phase order by `System::stop`. Actors
+/// in the same phase are stopped in their registration order.
+///
+/// *. System does not keep registered actors alive.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use actix_async::prelude::*;
+/// use actix_async::system::{StopMode, System};
+///
+/// struct Server;
+/// actor!(Server);
+///
+/// struct Database;
+/// actor!(Database);
+///
+/// #[actix_async::main]
+/// async fn main() {
+///     let system = System::new();
+///
+///     // database is stopped after server.
+///     let _db = system.start_in_phase(1, Database);
+///     let _server = system.start(Server);
+///
+///     system
+///         .stop(StopMode::Graceful {
+///             deadline: Duration::from_secs(5),
+///         })
+///         .await;
+/// }
+/// ```
+#[derive(Clone)]
+pub struct System {
+    actors: RefCounter<Lock<Vec<PhasedActor>>>,
+}
+
+// registered actor with it's phase.
+type PhasedActor = (usize, Box<dyn SystemActor>);
+
+impl System {
+    /// Construct an empty system.
+    pub fn new() -> Self {
+        Self {
+            actors: RefCounter::new(Lock::new(Vec::new())),
+        }
+    }
+
+    /// start actor and register it to system in phase 0.
+    pub fn start<A: Actor>(&self, act: A) -> Addr<A> {
+        self.start_in_phase(0, act)
+    }
+
+    /// start actor and register it to system in given phase.
+    pub fn start_in_phase<A: Actor>(&self, phase: usize, act: A) -> Addr<A> {
+        let addr = act.start();
+        self.register_in_phase(phase, &addr);
+        addr
+    }
+
+    /// register an started actor to system in phase 0.
+    pub fn register<A: Actor>(&self, addr: &Addr<A>) {
+        self.register_in_phase(0, addr)
+    }
+
+    /// register an started actor to system in given phase. Actors in lower phase are stopped
+    /// first.
+    pub fn register_in_phase<A: Actor>(&self, phase: usize, addr: &Addr<A>) {
+        let mut actors = self.actors.lock();
+        // remove actors that are already stopped.
+        actors.retain(|(_, act)| !act.is_stopped());
+        actors.push((phase, Box::new(addr.downgrade())));
+    }
+
+    /// stop all registered actors one by one and wait for them to finish.
+    ///
+    /// With `StopMode::Graceful` when the deadline is reached all the actors not yet stopped are
+    /// stopped forcefully and the method returns without waiting for them.
+    pub async fn stop(&self, mode: StopMode) {
+        let mut actors = core::mem::take(&mut *self.actors.lock());
+
+        // stable sort keeps the registration order in the same phase.
+        actors.sort_by_key(|(phase, _)| *phase);
+
+        let deadline = match mode {
+            StopMode::Graceful { deadline } => Some(Instant::now() + deadline),
+            StopMode::Forced => None,
+        };
+
+        let mut actors = actors.into_iter();
+
+        for (_, act) in &mut actors {
+            let mut watch = act.watch();
+
+            match deadline {
+                Some(deadline) => {
+                    act.stop(true);
+                    if timeout_at(deadline, &mut watch).await.is_err() {
+                        // deadline is reached. stop current and remaining actors forcefully
+                        // without waiting.
+                        act.stop(false);
+                        break;
+                    }
+                }
+                None => {
+                    act.stop(false);
+                    watch.await;
+                }
+            }
+        }
+
+        for (_, act) in actors {
+            act.stop(false);
+        }
+    }
+}
+
+impl Default for System {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// type erased WeakAddr registered to System.
+pub(crate) trait SystemActor: Send {
+    fn stop(&self, graceful: bool);
+
+    fn watch(&self) -> Watch;
+
+    fn is_stopped(&self) -> bool;
+}
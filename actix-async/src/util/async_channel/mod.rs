@@ -13,7 +13,11 @@ use core::{
     task::{Context, Poll},
 };
 
-use crate::error::ActixAsyncError;
+use alloc::boxed::Box;
+
+use crate::actor::OverflowPolicy;
+use crate::address::DeadLetterReason;
+use crate::error::{ActixAsyncError, TrySendError};
 use crate::util::{
     futures::{ready, Stream},
     smart_pointer::{RefCounter, WeakRefCounter},
@@ -24,22 +28,74 @@ use self::unbounded::Unbounded;
 
 struct Channel<T> {
     queue: Unbounded<T>,
+    // high priority messages. received before messages in queue.
+    priority: Unbounded<T>,
+    // control messages. received before messages in all other queues. They are not counted in
+    // in_queue and never take capacity or budget of channel.
+    control: Unbounded<T>,
     in_queue: AtomicUsize,
-    cap: usize,
+    cap: AtomicUsize,
+    adaptive: Option<Adaptive>,
+    budget: Option<Budget<T>>,
+    overflow: OverflowPolicy,
+    dead_letter: Option<fn(T, DeadLetterReason)>,
+    low_watermark: Option<usize>,
+    on_push: Option<OnPush<T>>,
     send_ops: Event,
     stream_ops: Event,
+    // receive operations waiting for control messages only.
+    control_ops: Event,
+    // operations waiting for channel to drop below low watermark.
+    ready_ops: Event,
+    // operations waiting for channel to close.
+    close_ops: Event,
     sender_count: AtomicUsize,
     receiver_count: AtomicUsize,
 }
 
+// hook called with every message pushed to channel.
+type OnPush<T> = Box<dyn Fn(&T) + Send + Sync>;
+
+// queues of channel. See `Channel`.
+#[derive(Clone, Copy)]
+enum Lane {
+    Control,
+    Priority,
+    Normal,
+}
+
 impl<T> Channel<T> {
+    fn on_push(&self, msg: &T) {
+        if let Some(on_push) = self.on_push.as_ref() {
+            on_push(msg);
+        }
+    }
+
+    fn queue(&self, lane: Lane) -> &Unbounded<T> {
+        match lane {
+            Lane::Control => &self.control,
+            Lane::Priority => &self.priority,
+            Lane::Normal => &self.queue,
+        }
+    }
+
     fn close(&self) -> bool {
         if self.queue.close() {
+            self.priority.close();
+            self.control.close();
+
             // Notify all send operations.
             self.send_ops.notify(usize::MAX);
 
             // Notify all stream operations.
             self.stream_ops.notify(usize::MAX);
+            self.control_ops.notify(usize::MAX);
+
+            // Notify all ready operations.
+            self.ready_ops.notify(usize::MAX);
+
+            // Notify all close operations.
+            self.close_ops.notify(usize::MAX);
 
             true
         } else {
@@ -47,33 +103,289 @@ impl<T> Channel<T> {
         }
     }
 
-    /// return if there is available count.
-    fn dequeue(&self) -> bool {
-        self.cap >= self.in_queue.fetch_sub(1, Ordering::Relaxed)
+    /// return if there is available count or bytes.
+    fn dequeue(&self, msg: &T) -> bool {
+        let in_queue = self.in_queue.fetch_sub(1, Ordering::Relaxed);
+        let cap = self.cap.load(Ordering::Relaxed);
+
+        if let Some(adaptive) = self.adaptive.as_ref() {
+            adaptive.shrink(&self.cap, cap, in_queue);
+        }
+
+        let freed = match self.budget.as_ref() {
+            Some(budget) => budget.release(msg),
+            None => false,
+        };
+
+        cap >= in_queue || freed
+    }
+
+    /// try to reserve bytes of message from budget. return true when there is no budget.
+    fn reserve(&self, msg: &T) -> bool {
+        match self.budget.as_ref() {
+            Some(budget) => budget.reserve(msg),
+            None => true,
+        }
+    }
+
+    /// return true when channel has no available count. try to grow the capacity first.
+    fn is_full(&self) -> bool {
+        let cap = self.cap.load(Ordering::Relaxed);
+        self.in_queue.load(Ordering::Relaxed) >= cap && !self.grow(cap)
+    }
+
+    /// return true when count of messages in channel is below low watermark.
+    /// low watermark default to half of the capacity.
+    fn is_ready(&self) -> bool {
+        let low_watermark = match self.low_watermark {
+            Some(low_watermark) => low_watermark,
+            None => core::cmp::max(self.cap.load(Ordering::Relaxed) / 2, 1),
+        };
+        self.in_queue.load(Ordering::Relaxed) < low_watermark
+    }
+
+    /// evict the oldest message in normal queue. return true when a message is evicted.
+    fn evict(&self) -> bool {
+        match self.queue.pop() {
+            Ok(msg) => {
+                self.dequeue(&msg);
+                self.dead_letter(msg, DeadLetterReason::Overflow);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// pass message can not be delivered to dead letter function.
+    fn dead_letter(&self, msg: T, reason: DeadLetterReason) {
+        if let Some(dead_letter) = self.dead_letter {
+            dead_letter(msg, reason);
+        }
+    }
+
+    /// try to grow the capacity when channel is full. return true when capacity is changed.
+    fn grow(&self, cap: usize) -> bool {
+        match self.adaptive.as_ref() {
+            Some(adaptive) => match adaptive.grow(&self.cap, cap) {
+                Some(new_cap) => {
+                    // notify blocked send operations for the newly available count.
+                    self.send_ops.notify(new_cap - cap);
+                    true
+                }
+                None => false,
+            },
+            None => false,
+        }
+    }
+}
+
+// adaptive capacity of channel.
+//
+// The capacity would double when a send operation observe a full channel and would halve when
+// the receiver drained the channel to empty for a capacity worth of times in a row.
+struct Adaptive {
+    min: usize,
+    max: usize,
+    drained: AtomicUsize,
+}
+
+impl Adaptive {
+    fn grow(&self, cap_ref: &AtomicUsize, cap: usize) -> Option<usize> {
+        if cap >= self.max {
+            return None;
+        }
+
+        let new_cap = core::cmp::min(cap.saturating_mul(2), self.max);
+
+        self.drained.store(0, Ordering::Relaxed);
+
+        cap_ref
+            .compare_exchange(cap, new_cap, Ordering::SeqCst, Ordering::Relaxed)
+            .ok()
+            .map(|_| new_cap)
+    }
+
+    fn shrink(&self, cap_ref: &AtomicUsize, cap: usize, in_queue: usize) {
+        // messages are piling up. reset the drained count.
+        if in_queue > 1 {
+            self.drained.store(0, Ordering::Relaxed);
+            return;
+        }
+
+        if cap > self.min && self.drained.fetch_add(1, Ordering::Relaxed) + 1 >= cap {
+            self.drained.store(0, Ordering::Relaxed);
+            let new_cap = core::cmp::max(cap / 2, self.min);
+            let _ = cap_ref.compare_exchange(cap, new_cap, Ordering::SeqCst, Ordering::Relaxed);
+        }
+    }
+}
+
+// memory budget of channel.
+//
+// The bytes of queued messages are estimated by size function and send operations would wait
+// when the budget is exceeded, even if the channel still has available count.
+struct Budget<T> {
+    max: usize,
+    in_queue: AtomicUsize,
+    size: fn(&T) -> usize,
+}
+
+impl<T> Budget<T> {
+    fn reserve(&self, msg: &T) -> bool {
+        let bytes = (self.size)(msg);
+        let mut in_queue = self.in_queue.load(Ordering::Relaxed);
+
+        loop {
+            // a message always fit into an empty channel regardless it's size.
+            if in_queue != 0 && in_queue.saturating_add(bytes) > self.max {
+                return false;
+            }
+
+            match self.in_queue.compare_exchange_weak(
+                in_queue,
+                in_queue.saturating_add(bytes),
+                Ordering::SeqCst,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(cur) => in_queue = cur,
+            }
+        }
+    }
+
+    fn force_reserve(&self, msg: &T) {
+        self.in_queue.fetch_add((self.size)(msg), Ordering::Relaxed);
+    }
+
+    /// return true when there are bytes released.
+    fn release(&self, msg: &T) -> bool {
+        let bytes = (self.size)(msg);
+        self.in_queue.fetch_sub(bytes, Ordering::Relaxed);
+        bytes != 0
     }
 }
 
 pub(crate) fn channel<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
-    assert!(cap > 0, "capacity cannot be zero");
-
-    let channel = RefCounter::new(Channel {
-        queue: Unbounded::new(),
-        cap,
-        in_queue: AtomicUsize::new(0),
-        send_ops: Event::new(),
-        stream_ops: Event::new(),
-        sender_count: AtomicUsize::new(1),
-        receiver_count: AtomicUsize::new(1),
-    });
-
-    let s = Sender {
-        channel: channel.clone(),
-    };
-    let r = Receiver {
-        channel,
-        listener: None,
-    };
-    (s, r)
+    ChannelBuilder::new(cap).build()
+}
+
+pub(crate) struct ChannelBuilder<T> {
+    cap: usize,
+    adaptive: Option<Adaptive>,
+    budget: Option<Budget<T>>,
+    overflow: OverflowPolicy,
+    dead_letter: Option<fn(T, DeadLetterReason)>,
+    low_watermark: Option<usize>,
+    on_push: Option<OnPush<T>>,
+}
+
+impl<T> ChannelBuilder<T> {
+    pub(crate) fn new(cap: usize) -> Self {
+        Self {
+            cap,
+            adaptive: None,
+            budget: None,
+            overflow: OverflowPolicy::Block,
+            dead_letter: None,
+            low_watermark: None,
+            on_push: None,
+        }
+    }
+
+    /// make channel capacity adaptive. capacity would change between min and max bound.
+    pub(crate) fn adaptive(mut self, min: usize, max: usize) -> Self {
+        assert!(min > 0, "capacity cannot be zero");
+        assert!(
+            min <= max,
+            "min capacity cannot be larger than max capacity"
+        );
+
+        self.cap = core::cmp::min(core::cmp::max(self.cap, min), max);
+        self.adaptive = Some(Adaptive {
+            min,
+            max,
+            drained: AtomicUsize::new(0),
+        });
+        self
+    }
+
+    /// limit the total bytes of queued messages. bytes of every message is estimated by size
+    /// function.
+    pub(crate) fn budget(mut self, max: usize, size: fn(&T) -> usize) -> Self {
+        assert!(max > 0, "memory budget cannot be zero");
+
+        self.budget = Some(Budget {
+            max,
+            in_queue: AtomicUsize::new(0),
+            size,
+        });
+        self
+    }
+
+    /// apply overflow policy when channel is full.
+    pub(crate) fn overflow(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow = policy;
+        self
+    }
+
+    /// function receiving messages dropped by overflow policy and messages remain in channel
+    /// when the last receiver is dropped.
+    pub(crate) fn dead_letter(mut self, dead_letter: fn(T, DeadLetterReason)) -> Self {
+        self.dead_letter = Some(dead_letter);
+        self
+    }
+
+    /// count of messages channel must drop below before `Sender::ready` resolves.
+    pub(crate) fn low_watermark(mut self, low_watermark: usize) -> Self {
+        assert!(low_watermark > 0, "low watermark cannot be zero");
+        self.low_watermark = Some(low_watermark);
+        self
+    }
+
+    /// hook called with every message before it's pushed to channel.
+    #[cfg(feature = "std")]
+    pub(crate) fn on_push<F>(mut self, on_push: F) -> Self
+    where
+        F: Fn(&T) + Send + Sync + 'static,
+    {
+        self.on_push = Some(Box::new(on_push));
+        self
+    }
+
+    pub(crate) fn build(self) -> (Sender<T>, Receiver<T>) {
+        assert!(self.cap > 0, "capacity cannot be zero");
+
+        let channel = RefCounter::new(Channel {
+            queue: Unbounded::new(),
+            priority: Unbounded::new(),
+            control: Unbounded::new(),
+            cap: AtomicUsize::new(self.cap),
+            adaptive: self.adaptive,
+            budget: self.budget,
+            overflow: self.overflow,
+            dead_letter: self.dead_letter,
+            low_watermark: self.low_watermark,
+            on_push: self.on_push,
+            in_queue: AtomicUsize::new(0),
+            send_ops: Event::new(),
+            stream_ops: Event::new(),
+            control_ops: Event::new(),
+            ready_ops: Event::new(),
+            close_ops: Event::new(),
+            sender_count: AtomicUsize::new(1),
+            receiver_count: AtomicUsize::new(1),
+        });
+
+        let s = Sender {
+            channel: channel.clone(),
+        };
+        let r = Receiver {
+            channel,
+            listener: None,
+            control_listener: None,
+        };
+        (s, r)
+    }
 }
 
 pub struct Sender<T> {
@@ -81,7 +393,141 @@ pub struct Sender<T> {
 }
 
 impl<T> Sender<T> {
+    /// address of the channel. identical for all senders of the same channel.
+    pub(crate) fn channel_id(&self) -> usize {
+        RefCounter::as_ptr(&self.channel) as usize
+    }
+
+    /// send message regardless the capacity of channel unless overflow policy says otherwise.
     pub(crate) fn do_send(&self, msg: T) -> Result<(), T> {
+        match self.channel.overflow {
+            OverflowPolicy::Block => {}
+            OverflowPolicy::DropOldest => {
+                if self.channel.is_full() {
+                    self.channel.evict();
+                }
+            }
+            OverflowPolicy::Fail | OverflowPolicy::DropNewest => {
+                if self.channel.is_full() {
+                    self.channel.dead_letter(msg, DeadLetterReason::Overflow);
+                    return Ok(());
+                }
+            }
+        }
+
+        self.force_push(Lane::Normal, msg)
+    }
+
+    /// count of messages in channel.
+    pub(crate) fn len(&self) -> usize {
+        self.channel.in_queue.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.channel.cap.load(Ordering::Relaxed)
+    }
+
+    /// return true when channel has no available count. Unlike send operations it would not try
+    /// to grow the capacity.
+    pub(crate) fn is_full(&self) -> bool {
+        self.len() >= self.capacity()
+    }
+
+    /// send message to the control queue regardless the capacity of channel. The message would
+    /// be received before all messages in the other queues and is not counted as message of
+    /// channel.
+    pub(crate) fn do_send_control(&self, msg: T) -> Result<(), T> {
+        self.channel.on_push(&msg);
+        self.channel.control.push(msg).map(|()| {
+            // Notify receiver waiting for control message and all blocked streams.
+            self.channel.control_ops.notify(usize::MAX);
+            self.channel.stream_ops.notify(usize::MAX);
+        })
+    }
+
+    fn force_push(&self, lane: Lane, msg: T) -> Result<(), T> {
+        self.channel.in_queue.fetch_add(1, Ordering::Relaxed);
+        if let Some(budget) = self.channel.budget.as_ref() {
+            budget.force_reserve(&msg);
+        }
+        self.channel.on_push(&msg);
+        self.channel.queue(lane).push(msg).map(|()| {
+            // Notify all blocked streams.
+            self.channel.stream_ops.notify(usize::MAX);
+        })
+    }
+
+    /// try to send message without waiting. `OverflowPolicy::DropOldest` is applied when channel
+    /// is full. Other overflow policies are ignored and the message is given back.
+    pub(crate) fn try_send(&self, mut msg: T) -> Result<(), TrySendError<T>> {
+        loop {
+            msg = match self.try_push(msg) {
+                Err(TrySendError::Full(msg)) => msg,
+                res => return res,
+            };
+
+            if self.channel.overflow != OverflowPolicy::DropOldest || !self.channel.evict() {
+                return Err(TrySendError::Full(msg));
+            }
+        }
+    }
+
+    /// push message to normal queue when channel has available count and bytes.
+    fn try_push(&self, msg: T) -> Result<(), TrySendError<T>> {
+        if self.channel.queue.is_closed() {
+            return Err(TrySendError::Closed(msg));
+        }
+
+        let mut in_queue = self.channel.in_queue.load(Ordering::SeqCst);
+
+        loop {
+            let cap = self.channel.cap.load(Ordering::Relaxed);
+
+            if in_queue < cap {
+                // channel has available count. try to reserve bytes of message from the memory
+                // budget before take the count.
+                if !self.channel.reserve(&msg) {
+                    // memory budget is exceeded. treat it like a full channel.
+                    return Err(TrySendError::Full(msg));
+                }
+
+                match self.channel.in_queue.compare_exchange_weak(
+                    in_queue,
+                    in_queue + 1,
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(cur) => {
+                        return match self.push(msg) {
+                            Ok(_) => {
+                                // If the capacity is larger than 1, notify another blocked send operation.
+                                match cap.saturating_sub(cur) {
+                                    0 | 1 => {}
+                                    _ => self.channel.send_ops.notify(1),
+                                }
+                                Ok(())
+                            }
+                            Err(msg) => Err(TrySendError::Closed(msg)),
+                        };
+                    }
+                    Err(cur) => {
+                        if let Some(budget) = self.channel.budget.as_ref() {
+                            budget.release(&msg);
+                        }
+                        // another thread increment the counter already.
+                        // (This path should be very short)
+                        in_queue = cur;
+                    }
+                }
+            } else if !self.channel.grow(cap) {
+                // channel is full. try to grow it's capacity when it's adaptive.
+                return Err(TrySendError::Full(msg));
+            }
+        }
+    }
+
+    fn push(&self, msg: T) -> Result<(), T> {
+        self.channel.on_push(&msg);
         self.channel.queue.push(msg).map(|()| {
             // Notify all blocked streams.
             self.channel.stream_ops.notify(usize::MAX);
@@ -89,10 +535,132 @@ impl<T> Sender<T> {
     }
 
     pub(crate) fn send(&self, msg: T) -> SendFuture<'_, T> {
+        self._send(Lane::Normal, msg)
+    }
+
+    /// send message to the high priority queue regardless the capacity of channel. The message
+    /// would be received before all messages in the normal queue. The returned future resolves
+    /// immediately.
+    pub(crate) fn send_priority(&self, msg: T) -> SendFuture<'_, T> {
+        self._send(Lane::Priority, msg)
+    }
+
+    /// send message with `Sender::do_send_control`. The returned future resolves immediately.
+    pub(crate) fn send_control(&self, msg: T) -> SendFuture<'_, T> {
+        self._send(Lane::Control, msg)
+    }
+
+    /// a future resolves immediately for message already sent by `Sender::try_send`.
+    pub(crate) fn sent(&self) -> SendFuture<'_, T> {
+        SendFuture {
+            sender: self,
+            listener: None,
+            msg: None,
+            lane: Lane::Normal,
+        }
+    }
+
+    /// a future resolves when count of messages in channel is below low watermark.
+    /// resolve with error when channel is closed.
+    pub(crate) fn ready(&self) -> ReadyFuture<'_, T> {
+        ReadyFuture {
+            sender: self,
+            listener: None,
+        }
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.channel.queue.is_closed()
+    }
+
+    /// a future resolves when channel is closed.
+    pub(crate) fn closed(&self) -> ClosedFuture<'_, T> {
+        ClosedFuture {
+            sender: self,
+            listener: None,
+        }
+    }
+
+    // send message in slot to given lane. message is put back to slot when pending.
+    fn poll_send(
+        &self,
+        cx: &mut Context<'_>,
+        lane: Lane,
+        slot: &mut Option<T>,
+        listener: &mut Option<EventListener>,
+    ) -> Poll<Result<(), ActixAsyncError>> {
+        let mut msg = match slot.take() {
+            Some(msg) => msg,
+            // message is already sent.
+            None => return Poll::Ready(Ok(())),
+        };
+
+        // only normal queue is bounded.
+        match lane {
+            Lane::Control => {
+                let res = self.do_send_control(msg);
+                return Poll::Ready(res.map_err(|_| ActixAsyncError::Closed));
+            }
+            Lane::Priority => {
+                let res = self.force_push(lane, msg);
+                return Poll::Ready(res.map_err(|_| ActixAsyncError::Closed));
+            }
+            Lane::Normal => {}
+        }
+
+        loop {
+            msg = match self.try_push(msg) {
+                Ok(()) => return Poll::Ready(Ok(())),
+                // TODO: It's possible to give message's ownership back to caller.
+                Err(TrySendError::Closed(_)) => return Poll::Ready(Err(ActixAsyncError::Closed)),
+                Err(TrySendError::Full(msg)) => msg,
+            };
+
+            // Sending failed because channel is full. apply overflow policy.
+            match self.channel.overflow {
+                OverflowPolicy::Block => {}
+                OverflowPolicy::Fail => return Poll::Ready(Err(ActixAsyncError::MailboxFull)),
+                OverflowPolicy::DropNewest => {
+                    self.channel.dead_letter(msg, DeadLetterReason::Overflow);
+                    return Poll::Ready(Err(ActixAsyncError::MailboxFull));
+                }
+                OverflowPolicy::DropOldest => {
+                    if self.channel.evict() {
+                        continue;
+                    }
+                }
+            }
+
+            // now start listening for notifications or wait for one.
+            match listener.as_mut() {
+                None => {
+                    // Start listening and then try sending again. receiver could take message
+                    // before listening so the notification is not missed.
+                    *listener = Some(self.channel.send_ops.listen());
+                }
+                Some(l) => {
+                    // Wait for a notification.
+                    match Pin::new(l).poll(cx) {
+                        Poll::Ready(_) => {
+                            listener.take();
+                            continue;
+                        }
+                        Poll::Pending => {
+                            *slot = Some(msg);
+                            return Poll::Pending;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn _send(&self, lane: Lane, msg: T) -> SendFuture<'_, T> {
         SendFuture {
             sender: self,
             listener: None,
             msg: Some(msg),
+            lane,
         }
     }
 
@@ -146,6 +714,7 @@ pub struct SendFuture<'a, T> {
     sender: &'a Sender<T>,
     listener: Option<EventListener>,
     msg: Option<T>,
+    lane: Lane,
 }
 
 impl<T> Unpin for SendFuture<'_, T> {}
@@ -155,68 +724,111 @@ impl<T> Future for SendFuture<'_, T> {
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
+        this.sender
+            .poll_send(cx, this.lane, &mut this.msg, &mut this.listener)
+    }
+}
 
-        let msg = this.msg.take().unwrap();
+pub struct ReadyFuture<'a, T> {
+    sender: &'a Sender<T>,
+    listener: Option<EventListener>,
+}
 
-        let cap = this.sender.channel.cap;
-        let mut in_queue = this.sender.channel.in_queue.load(Ordering::Relaxed);
+impl<T> Unpin for ReadyFuture<'_, T> {}
+
+impl<T> Future for ReadyFuture<'_, T> {
+    type Output = Result<(), ActixAsyncError>;
 
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let channel = &this.sender.channel;
         loop {
-            if in_queue < cap {
-                match this.sender.channel.in_queue.compare_exchange_weak(
-                    in_queue,
-                    in_queue + 1,
-                    Ordering::SeqCst,
-                    Ordering::Relaxed,
-                ) {
-                    Ok(cur) => {
-                        return match this.sender.do_send(msg) {
-                            Ok(_) => {
-                                // If the capacity is larger than 1, notify another blocked send operation.
-                                match cap - cur {
-                                    1 => {}
-                                    _ => this.sender.channel.send_ops.notify(1),
-                                }
-                                Poll::Ready(Ok(()))
-                            }
-                            // TODO: It's possible to give message's ownership back to caller.
-                            Err(_msg) => Poll::Ready(Err(ActixAsyncError::Closed)),
-                        };
-                    }
-                    Err(cur) => {
-                        in_queue = cur;
-                        // another thread increment the counter already.
-                        // (This path should be very short)
-                        continue;
-                    }
-                }
+            if channel.queue.is_closed() {
+                this.listener = None;
+                return Poll::Ready(Err(ActixAsyncError::Closed));
+            }
+
+            if channel.is_ready() {
+                this.listener = None;
+                return Poll::Ready(Ok(()));
             }
 
-            // Sending failed because channel is full
-            // now start listening for notifications or wait for one.
             match this.listener.as_mut() {
-                None => {
-                    // Start listening and then try receiving again.
-                    this.listener = Some(this.sender.channel.send_ops.listen());
+                None => this.listener = Some(channel.ready_ops.listen()),
+                Some(listener) => {
+                    ready!(Pin::new(listener).poll(cx));
+                    this.listener = None;
                 }
-                Some(l) => {
-                    // Wait for a notification.
-                    match Pin::new(l).poll(cx) {
-                        Poll::Ready(_) => {
-                            this.listener.take();
-                            continue;
-                        }
-                        Poll::Pending => {
-                            this.msg = Some(msg);
-                            return Poll::Pending;
-                        }
-                    }
+            }
+        }
+    }
+}
+
+pub struct ClosedFuture<'a, T> {
+    sender: &'a Sender<T>,
+    listener: Option<EventListener>,
+}
+
+impl<T> Unpin for ClosedFuture<'_, T> {}
+
+impl<T> Future for ClosedFuture<'_, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        loop {
+            if this.sender.is_closed() {
+                this.listener = None;
+                return Poll::Ready(());
+            }
+
+            match this.listener.as_mut() {
+                None => this.listener = Some(this.sender.channel.close_ops.listen()),
+                Some(listener) => {
+                    ready!(Pin::new(listener).poll(cx));
+                    this.listener = None;
                 }
             }
         }
     }
 }
 
+/// owned sender sending one buffered message at a time.
+#[cfg(feature = "futures-sink")]
+pub(crate) struct SenderSink<T> {
+    sender: Sender<T>,
+    listener: Option<EventListener>,
+    msg: Option<T>,
+}
+
+#[cfg(feature = "futures-sink")]
+impl<T> SenderSink<T> {
+    pub(crate) fn new(sender: Sender<T>) -> Self {
+        Self {
+            sender,
+            listener: None,
+            msg: None,
+        }
+    }
+
+    /// buffer message. it would be sent by `SenderSink::poll_flush`.
+    pub(crate) fn start_send(&mut self, msg: T) {
+        debug_assert!(self.msg.is_none(), "SenderSink has buffered message");
+        self.msg = Some(msg);
+    }
+
+    /// send the buffered message. resolve when there is no buffered message.
+    pub(crate) fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), ActixAsyncError>> {
+        let res =
+            ready!(self
+                .sender
+                .poll_send(cx, Lane::Normal, &mut self.msg, &mut self.listener));
+        // drop the listener so it would not take the notification meant for other senders.
+        self.listener = None;
+        Poll::Ready(res)
+    }
+}
+
 pub(crate) struct WeakSender<T> {
     channel: WeakRefCounter<Channel<T>>,
 }
@@ -230,6 +842,11 @@ impl<T> Clone for WeakSender<T> {
 }
 
 impl<T> WeakSender<T> {
+    /// address of the channel. See `Sender::channel_id`.
+    pub(crate) fn channel_id(&self) -> usize {
+        WeakRefCounter::as_ptr(&self.channel) as usize
+    }
+
     pub fn upgrade(&self) -> Option<Sender<T>> {
         if let Some(channel) = WeakRefCounter::upgrade(&self.channel) {
             let mut count = channel.sender_count.load(Ordering::Relaxed);
@@ -254,19 +871,109 @@ impl<T> WeakSender<T> {
 pub(crate) struct Receiver<T> {
     channel: RefCounter<Channel<T>>,
     listener: Option<EventListener>,
+    control_listener: Option<EventListener>,
 }
 
 impl<T> Receiver<T> {
     pub(crate) fn try_recv(&self) -> Result<T, TryRecvError> {
-        self.channel.queue.pop().map(|msg| {
-            if self.channel.dequeue() {
-                // Notify a single blocked send operation. If the notified operation then sends a
-                // message or gets canceled, it will notify another blocked send operation.
-                self.channel.send_ops.notify(1);
+        if let Ok(msg) = self.channel.control.pop() {
+            return Ok(msg);
+        }
+
+        // control and priority queues are closed together with normal queue. it's safe to ignore
+        // their error and look into the normal queue.
+        let msg = self
+            .channel
+            .priority
+            .pop()
+            .or_else(|_| self.channel.queue.pop());
+
+        msg.map(|msg| self.received(msg))
+    }
+
+    fn received(&self, msg: T) -> T {
+        if self.channel.dequeue(&msg) {
+            // Notify a single blocked send operation. If the notified operation then sends a
+            // message or gets canceled, it will notify another blocked send operation.
+            self.channel.send_ops.notify(1);
+        }
+
+        if self.channel.is_ready() {
+            self.channel.ready_ops.notify(usize::MAX);
+        }
+
+        msg
+    }
+
+    /// receive message from control queue only. Pending when there is no control message or
+    /// channel is closed.
+    pub(crate) fn poll_control(&mut self, cx: &mut Context<'_>) -> Poll<T> {
+        loop {
+            if let Ok(msg) = self.channel.control.pop() {
+                self.control_listener = None;
+                return Poll::Ready(msg);
             }
 
-            msg
-        })
+            match self.control_listener.as_mut() {
+                None => self.control_listener = Some(self.channel.control_ops.listen()),
+                Some(listener) => {
+                    ready!(Pin::new(listener).poll(cx));
+                    self.control_listener = None;
+                    if self.channel.queue.is_closed() {
+                        return Poll::Pending;
+                    }
+                }
+            }
+        }
+    }
+
+    /// wait for message arriving at channel without receiving it.
+    /// resolve to false when channel is closed and empty.
+    pub(crate) fn poll_wait(&mut self, cx: &mut Context<'_>) -> Poll<bool> {
+        loop {
+            if self.channel.in_queue.load(Ordering::Acquire) > 0 {
+                self.listener = None;
+                return Poll::Ready(true);
+            }
+
+            if self.channel.queue.is_closed() {
+                self.listener = None;
+                return Poll::Ready(false);
+            }
+
+            match self.listener.as_mut() {
+                None => self.listener = Some(self.channel.stream_ops.listen()),
+                Some(listener) => {
+                    ready!(Pin::new(listener).poll(cx));
+                    self.listener = None;
+                }
+            }
+        }
+    }
+
+    /// count of messages in channel.
+    pub(crate) fn len(&self) -> usize {
+        self.channel.in_queue.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.channel.cap.load(Ordering::Relaxed)
+    }
+
+    /// change the capacity of channel. notify blocked send operations when capacity grows.
+    ///
+    /// Messages already in channel are kept when capacity shrinks below their count.
+    pub(crate) fn set_capacity(&self, cap: usize) {
+        assert!(cap > 0, "capacity cannot be zero");
+
+        let old = self.channel.cap.swap(cap, Ordering::SeqCst);
+        if cap > old {
+            self.channel.send_ops.notify(cap - old);
+
+            if self.channel.is_ready() {
+                self.channel.ready_ops.notify(usize::MAX);
+            }
+        }
     }
 
     pub(crate) fn as_sender(&self) -> Option<Sender<T>> {
@@ -335,6 +1042,11 @@ impl<T> Drop for Receiver<T> {
         // Decrement the receiver count and close the channel if it drops down to zero.
         if self.channel.receiver_count.fetch_sub(1, Ordering::AcqRel) == 1 {
             self.channel.close();
+
+            // messages remain in channel would never be received.
+            while let Ok(msg) = self.try_recv() {
+                self.channel.dead_letter(msg, DeadLetterReason::Stopped);
+            }
         }
     }
 }
@@ -357,6 +1069,7 @@ impl<T> Clone for Receiver<T> {
         Receiver {
             channel: self.channel.clone(),
             listener: None,
+            control_listener: None,
         }
     }
 }
@@ -418,4 +1131,79 @@ mod test {
         assert_eq!(rx.channel.sender_count.load(Ordering::SeqCst), 0);
         assert!(weak.upgrade().is_none());
     }
+
+    #[test]
+    fn adaptive_capacity() {
+        let (tx, rx) = ChannelBuilder::<u8>::new(2).adaptive(1, 8).build();
+
+        let cap = || tx.channel.cap.load(Ordering::SeqCst);
+
+        assert_eq!(cap(), 2);
+
+        // full channel would grow the capacity until it reaches max.
+        assert!(tx.channel.grow(2));
+        assert_eq!(cap(), 4);
+        assert!(tx.channel.grow(4));
+        assert!(!tx.channel.grow(8));
+        assert_eq!(cap(), 8);
+
+        // channel drained to empty would shrink the capacity until it reaches min.
+        for _ in 0..8 {
+            tx.do_send(1).ok().unwrap();
+            assert!(rx.try_recv().is_ok());
+        }
+        assert_eq!(cap(), 4);
+
+        // piling up messages would reset the shrink progress.
+        tx.do_send(1).ok().unwrap();
+        tx.do_send(1).ok().unwrap();
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_ok());
+
+        for _ in 0..2 {
+            tx.do_send(1).ok().unwrap();
+            assert!(rx.try_recv().is_ok());
+        }
+        assert_eq!(cap(), 4);
+
+        tx.do_send(1).ok().unwrap();
+        assert!(rx.try_recv().is_ok());
+        assert_eq!(cap(), 2);
+
+        for _ in 0..2 {
+            tx.do_send(1).ok().unwrap();
+            assert!(rx.try_recv().is_ok());
+        }
+        assert_eq!(cap(), 1);
+        assert_eq!(tx.channel.in_queue.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn memory_budget() {
+        let (tx, rx) = ChannelBuilder::<u8>::new(8)
+            .budget(2, |msg| *msg as usize)
+            .build();
+
+        let waker = futures_util::task::noop_waker();
+        let cx = &mut Context::from_waker(&waker);
+
+        let poll = |msg: u8, cx: &mut Context<'_>| {
+            let mut fut = tx.send(msg);
+            Pin::new(&mut fut).poll(cx).is_ready()
+        };
+
+        // message larger than budget can be sent to empty channel.
+        assert!(poll(3, cx));
+        assert!(!poll(1, cx));
+        assert!(rx.try_recv().is_ok());
+
+        assert!(poll(1, cx));
+        assert!(poll(1, cx));
+        assert!(!poll(1, cx));
+        assert!(rx.try_recv().is_ok());
+        assert!(poll(1, cx));
+
+        let budget = tx.channel.budget.as_ref().unwrap();
+        assert_eq!(budget.in_queue.load(Ordering::SeqCst), 2);
+    }
 }
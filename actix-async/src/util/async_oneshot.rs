@@ -5,6 +5,7 @@
 */
 
 use core::{
+    any::Any,
     cell::UnsafeCell,
     future::Future,
     mem::MaybeUninit,
@@ -14,6 +15,8 @@ use core::{
     task::{Context, Poll, Waker},
 };
 
+use alloc::boxed::Box;
+
 use crate::error::ActixAsyncError;
 use crate::util::smart_pointer::RefCounter;
 
@@ -30,8 +33,16 @@ pub(crate) struct Inner<T> {
     value: UnsafeCell<MaybeUninit<T>>,
     send: UnsafeCell<MaybeUninit<Waker>>,
     recv: UnsafeCell<MaybeUninit<Waker>>,
+    forward: UnsafeCell<MaybeUninit<Forward<T>>>,
 }
 
+// closure the value is forwarded to. See `OneshotReceiver::forward`.
+type Forward<T> = Box<dyn FnOnce(T) + Send>;
+
+const TIMEOUT: usize = 0b1000_0000;
+const DEFER: usize = 0b100_0000;
+const FORWARD: usize = 0b10_0000;
+const STARTED: usize = 0b1_0000;
 const CLOSED: usize = 0b1000;
 const SEND: usize = 0b0100;
 const RECV: usize = 0b0010;
@@ -44,6 +55,7 @@ impl<T> Inner<T> {
             value: UnsafeCell::new(MaybeUninit::uninit()),
             send: UnsafeCell::new(MaybeUninit::uninit()),
             recv: UnsafeCell::new(MaybeUninit::uninit()),
+            forward: UnsafeCell::new(MaybeUninit::uninit()),
         }
     }
 
@@ -87,9 +99,38 @@ impl<T> Inner<T> {
         State(self.state.fetch_or(READY, Ordering::AcqRel))
     }
 
+    // Sets the closure value is forwarded to.
+    pub(crate) fn set_forward(&self, f: Forward<T>) -> State {
+        let forward = self.forward.get();
+        unsafe { (*forward).as_mut_ptr().write(f) }
+        State(self.state.fetch_or(FORWARD, Ordering::AcqRel))
+    }
+
+    // Takes the closure value is forwarded to. It MUST be set and can only be taken by the
+    // side observing both the value and the closure are set. The flag is cleared so the
+    // closure is not dropped again when channel is dropped.
+    pub(crate) fn take_forward(&self) -> Forward<T> {
+        debug_assert!(self.state().forward());
+        let f = unsafe { (*self.forward.get()).as_ptr().read() };
+        self.state.fetch_and(!FORWARD, Ordering::AcqRel);
+        f
+    }
+
     pub(crate) fn close(&self) -> State {
         State(self.state.fetch_or(CLOSED, Ordering::AcqRel))
     }
+
+    pub(crate) fn start(&self) -> State {
+        State(self.state.fetch_or(STARTED, Ordering::AcqRel))
+    }
+
+    pub(crate) fn defer(&self) -> State {
+        State(self.state.fetch_or(DEFER, Ordering::AcqRel))
+    }
+
+    pub(crate) fn timeout(&self) -> State {
+        State(self.state.fetch_or(TIMEOUT | CLOSED, Ordering::AcqRel))
+    }
 }
 
 impl<T> Drop for Inner<T> {
@@ -106,6 +147,12 @@ impl<T> Drop for Inner<T> {
                 drop_in_place((&mut *self.send.get()).as_mut_ptr());
             }
         }
+        // Drop the closure if it's never taken.
+        if state.forward() {
+            unsafe {
+                drop_in_place((&mut *self.forward.get()).as_mut_ptr());
+            }
+        }
     }
 }
 
@@ -128,17 +175,74 @@ impl State {
     pub(crate) fn recv(&self) -> bool {
         (self.0 & RECV) == RECV
     }
+    pub(crate) fn started(&self) -> bool {
+        (self.0 & STARTED) == STARTED
+    }
+    pub(crate) fn forward(&self) -> bool {
+        (self.0 & FORWARD) == FORWARD
+    }
+    pub(crate) fn defer(&self) -> bool {
+        (self.0 & DEFER) == DEFER
+    }
+    pub(crate) fn timeout(&self) -> bool {
+        (self.0 & TIMEOUT) == TIMEOUT
+    }
 }
 
 #[derive(Debug)]
 pub struct OneshotSender<T> {
     inner: RefCounter<Inner<T>>,
     done: bool,
+    // sender taken over the channel from the original one. See `OneshotSender::defer`.
+    deferred: bool,
 }
 
 impl<T> OneshotSender<T> {
     pub(crate) fn new(inner: RefCounter<Inner<T>>) -> Self {
-        OneshotSender { inner, done: false }
+        OneshotSender {
+            inner,
+            done: false,
+            deferred: false,
+        }
+    }
+
+    /// type erased channel that can be turned into a deferred sender. See `defer`.
+    pub(crate) fn as_any(&self) -> RefCounter<dyn Any>
+    where
+        T: 'static,
+    {
+        self.inner.clone()
+    }
+
+    /// take over the channel from it's original sender. After that the value sent by original
+    /// sender is dropped and dropping it would not close the channel.
+    ///
+    /// return None when given channel is not the value type or it's already deferred.
+    pub(crate) fn defer(inner: &RefCounter<dyn Any>) -> Option<Self>
+    where
+        T: 'static,
+    {
+        let this = inner.downcast_ref::<Inner<T>>()?;
+        if this.defer().defer() {
+            return None;
+        }
+
+        let ptr = RefCounter::into_raw(inner.clone()) as *const Inner<T>;
+        // SAFETY:
+        //
+        // The type of pointer is checked by downcast_ref.
+        let inner = unsafe { RefCounter::from_raw(ptr) };
+
+        Some(OneshotSender {
+            inner,
+            done: false,
+            deferred: true,
+        })
+    }
+
+    // original sender is taken over by a deferred one.
+    fn is_taken(&self) -> bool {
+        !self.deferred && self.inner.state().defer()
     }
 
     #[inline(always)]
@@ -146,13 +250,26 @@ impl<T> OneshotSender<T> {
         self.inner.state().closed()
     }
 
+    /// mark the value is being produced. return false when receiver is already closed.
+    #[inline]
+    pub fn start(&self) -> bool {
+        !self.inner.start().closed()
+    }
+
     #[inline]
     pub fn send(mut self, value: T) -> Result<(), ActixAsyncError> {
         self.done = true;
+        if self.is_taken() {
+            return Ok(());
+        }
         let inner = &mut self.inner;
         let state = inner.set_value(value);
         if !state.closed() {
-            if state.recv() {
+            if state.forward() {
+                let f = inner.take_forward();
+                f(inner.take_value());
+                Ok(())
+            } else if state.recv() {
                 inner.recv().wake_by_ref();
                 Ok(())
             } else {
@@ -165,9 +282,24 @@ impl<T> OneshotSender<T> {
     }
 }
 
+impl<T> OneshotSender<T> {
+    /// close the channel without value because producing it took too long. receiver would
+    /// resolve with `ActixAsyncError::HandlerTimeout`.
+    pub(crate) fn timeout(mut self) {
+        self.done = true;
+        if self.is_taken() {
+            return;
+        }
+        let old = self.inner.timeout();
+        if !old.closed() && old.recv() {
+            self.inner.recv().wake_by_ref();
+        }
+    }
+}
+
 impl<T> Drop for OneshotSender<T> {
     fn drop(&mut self) {
-        if !self.done {
+        if !self.done && !self.is_taken() {
             let state = self.inner.state();
             if !state.closed() {
                 let old = self.inner.close();
@@ -190,9 +322,61 @@ impl<T> OneshotReceiver<T> {
         OneshotReceiver { inner, done: false }
     }
 
+    /// close the receiver when sender has not started producing the value.
+    /// return true when receiver is closed by this call.
+    pub(crate) fn expire(&mut self) -> bool {
+        let mut state = self.inner.state.load(Ordering::Acquire);
+        loop {
+            let old = State(state);
+            if old.started() || old.ready() || old.closed() {
+                return false;
+            }
+
+            match self.inner.state.compare_exchange_weak(
+                state,
+                state | CLOSED,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    self.done = true;
+                    if old.send() {
+                        self.inner.send().wake_by_ref();
+                    }
+                    return true;
+                }
+                Err(s) => state = s,
+            }
+        }
+    }
+
+    /// pass the value to given closure instead of receiving it. The closure is called by sender
+    /// when the value is sent or by this call when the value is already sent.
+    ///
+    /// The closure is dropped when sender is dropped without sending value.
+    pub(crate) fn forward<F>(mut self, f: F)
+    where
+        F: FnOnce(T) + Send + 'static,
+    {
+        self.done = true;
+        let state = self.inner.set_forward(Box::new(f));
+        if state.ready() && !state.closed() {
+            let f = self.inner.take_forward();
+            f(self.inner.take_value());
+        }
+    }
+
+    /// return true when value is received or sender is dropped.
+    pub(crate) fn is_closed(&self) -> bool {
+        let state = self.inner.state();
+        state.closed() || state.ready()
+    }
+
     fn handle_state(&mut self, state: State) -> Poll<Result<T, ActixAsyncError>> {
         if state.ready() {
             Poll::Ready(Ok(self.inner.take_value()))
+        } else if state.timeout() {
+            Poll::Ready(Err(ActixAsyncError::HandlerTimeout))
         } else if state.closed() {
             Poll::Ready(Err(ActixAsyncError::Closed))
         } else {
@@ -1,9 +1,17 @@
-pub(crate) use futures_core::{
-    ready,
-    stream::{LocalBoxStream, Stream},
+#[cfg(feature = "futures-core")]
+pub(crate) use futures_core::{ready, stream::LocalBoxStream};
+
+#[cfg(feature = "futures-core")]
+pub use futures_core::{
+    future::{BoxFuture, LocalBoxFuture},
+    stream::Stream,
 };
 
-pub use futures_core::future::{BoxFuture, LocalBoxFuture};
+#[cfg(not(feature = "futures-core"))]
+pub(crate) use self::minimal::{ready, LocalBoxStream};
+
+#[cfg(not(feature = "futures-core"))]
+pub use self::minimal::{BoxFuture, LocalBoxFuture, Stream};
 
 use core::{
     fmt,
@@ -66,3 +74,179 @@ pub async fn yield_now() {
 
     YieldNow { yielded: false }.await
 }
+
+pin_project_lite::pin_project! {
+    /// resolve the inner future or None when the timer fires first.
+    pub(crate) struct Timeout<Fut, T> {
+        #[pin]
+        fut: Fut,
+        #[pin]
+        timer: Option<T>,
+    }
+}
+
+impl<Fut, T> Timeout<Fut, T> {
+    pub(crate) fn new(fut: Fut, timer: Option<T>) -> Self {
+        Self { fut, timer }
+    }
+}
+
+impl<Fut, T> Future for Timeout<Fut, T>
+where
+    Fut: Future,
+    T: Future<Output = ()>,
+{
+    type Output = Option<Fut::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        if let Poll::Ready(res) = this.fut.poll(cx) {
+            return Poll::Ready(Some(res));
+        }
+
+        match this.timer.as_pin_mut() {
+            Some(timer) => timer.poll(cx).map(|_| None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// catch panic happen when polling the inner future.
+#[cfg(feature = "std")]
+pub(crate) struct CatchUnwind<Fut> {
+    fut: Fut,
+}
+
+#[cfg(feature = "std")]
+impl<Fut> CatchUnwind<Fut> {
+    pub(crate) fn new(fut: Fut) -> Self {
+        Self { fut }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Fut> Future for CatchUnwind<Fut>
+where
+    Fut: Future + Unpin,
+{
+    type Output = Result<Fut::Output, alloc::boxed::Box<dyn core::any::Any + Send>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let fut = &mut self.get_mut().fut;
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| Pin::new(fut).poll(cx))) {
+            Ok(res) => res.map(Ok),
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+}
+
+/// block current thread until the future resolves. the thread is parked when future is pending.
+#[cfg(feature = "std")]
+pub(crate) fn block_on<Fut: Future>(fut: Fut) -> Fut::Output {
+    use std::{
+        sync::Arc,
+        task::Wake,
+        thread::{self, Thread},
+    };
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let cx = &mut Context::from_waker(&waker);
+
+    let mut fut = alloc::boxed::Box::pin(fut);
+
+    loop {
+        match fut.as_mut().poll(cx) {
+            Poll::Ready(res) => return res,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Minimal replacement of the types and traits from futures-core crate.
+#[cfg(not(feature = "futures-core"))]
+mod minimal {
+    use core::{
+        future::Future,
+        ops::DerefMut,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use alloc::boxed::Box;
+
+    /// An owned dynamically typed `Future` for use in cases where you can't statically type your
+    /// result or need to add some indirection.
+    pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+    /// `BoxFuture`, but without the `Send` requirement.
+    pub type LocalBoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+    pub(crate) type LocalBoxStream<'a, T> = Pin<Box<dyn Stream<Item = T> + 'a>>;
+
+    /// A stream of values produced asynchronously.
+    ///
+    /// Mirror of `futures_core::stream::Stream`.
+    #[must_use = "streams do nothing unless polled"]
+    pub trait Stream {
+        type Item;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>;
+
+        #[inline]
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (0, None)
+        }
+    }
+
+    impl<S: ?Sized + Stream + Unpin> Stream for &mut S {
+        type Item = S::Item;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            S::poll_next(Pin::new(&mut **self), cx)
+        }
+    }
+
+    impl<P> Stream for Pin<P>
+    where
+        P: DerefMut + Unpin,
+        P::Target: Stream,
+    {
+        type Item = <P::Target as Stream>::Item;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.get_mut().as_mut().poll_next(cx)
+        }
+    }
+
+    impl<S: ?Sized + Stream + Unpin> Stream for Box<S> {
+        type Item = S::Item;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            Pin::new(&mut **self).poll_next(cx)
+        }
+    }
+
+    macro_rules! ready {
+        ($e:expr $(,)?) => {
+            match $e {
+                ::core::task::Poll::Ready(t) => t,
+                ::core::task::Poll::Pending => return ::core::task::Poll::Pending,
+            }
+        };
+    }
+
+    pub(crate) use ready;
+}
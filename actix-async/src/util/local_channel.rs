@@ -0,0 +1,202 @@
+/*
+   Single thread channel for senders living on the same thread of actor.
+   See `ActorBuilder::start_local`.
+*/
+
+use core::{
+    cell::RefCell,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use alloc::{collections::VecDeque, rc::Rc};
+
+use crate::error::ActixAsyncError;
+
+struct Inner<T> {
+    queue: VecDeque<T>,
+    cap: usize,
+    closed: bool,
+    recv: Option<Waker>,
+    // blocked send operations.
+    send: VecDeque<Waker>,
+    on_push: Option<OnPush<T>>,
+}
+
+// hook called with every message pushed to channel.
+type OnPush<T> = Rc<dyn Fn(&T)>;
+
+pub(crate) fn local_channel<T>(cap: usize) -> (LocalSender<T>, LocalReceiver<T>) {
+    assert!(cap > 0, "capacity cannot be zero");
+
+    let inner = Rc::new(RefCell::new(Inner {
+        queue: VecDeque::with_capacity(cap),
+        cap,
+        closed: false,
+        recv: None,
+        send: VecDeque::new(),
+        on_push: None,
+    }));
+
+    let tx = LocalSender {
+        inner: inner.clone(),
+    };
+    let rx = LocalReceiver { inner };
+
+    (tx, rx)
+}
+
+pub struct LocalSender<T> {
+    inner: Rc<RefCell<Inner<T>>>,
+}
+
+impl<T> LocalSender<T> {
+    /// hook called with every message before it's pushed to channel.
+    #[cfg(feature = "std")]
+    pub(crate) fn on_push<F>(&self, on_push: F)
+    where
+        F: Fn(&T) + 'static,
+    {
+        self.inner.borrow_mut().on_push = Some(Rc::new(on_push));
+    }
+
+    /// push message to channel regardless the capacity. message is given back when channel is
+    /// closed.
+    pub(crate) fn do_send(&self, msg: T) -> Result<(), T> {
+        if self.inner.borrow().closed {
+            return Err(msg);
+        }
+
+        self.push(msg);
+
+        Ok(())
+    }
+
+    pub(crate) fn send(&self, msg: T) -> LocalSendFuture<'_, T> {
+        LocalSendFuture {
+            sender: self,
+            msg: Some(msg),
+        }
+    }
+
+    fn push(&self, msg: T) {
+        // call hook without borrowing channel. it's free to send to the same channel.
+        let on_push = self.inner.borrow().on_push.clone();
+        if let Some(on_push) = on_push {
+            on_push(&msg);
+        }
+
+        let mut inner = self.inner.borrow_mut();
+        inner.queue.push_back(msg);
+        if let Some(waker) = inner.recv.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Clone for LocalSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for LocalSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LocalSender {{ .. }}")
+    }
+}
+
+pub struct LocalSendFuture<'a, T> {
+    sender: &'a LocalSender<T>,
+    msg: Option<T>,
+}
+
+impl<T> Unpin for LocalSendFuture<'_, T> {}
+
+impl<T> Future for LocalSendFuture<'_, T> {
+    type Output = Result<(), ActixAsyncError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut inner = this.sender.inner.borrow_mut();
+
+        if inner.closed {
+            drop(inner);
+            this.msg = None;
+            return Poll::Ready(Err(ActixAsyncError::Closed));
+        }
+
+        if inner.queue.len() < inner.cap {
+            drop(inner);
+            if let Some(msg) = this.msg.take() {
+                this.sender.push(msg);
+            }
+            return Poll::Ready(Ok(()));
+        }
+
+        if !inner.send.iter().any(|w| w.will_wake(cx.waker())) {
+            inner.send.push_back(cx.waker().clone());
+        }
+
+        Poll::Pending
+    }
+}
+
+pub(crate) struct LocalReceiver<T> {
+    inner: Rc<RefCell<Inner<T>>>,
+}
+
+impl<T> LocalReceiver<T> {
+    pub(crate) fn try_recv(&self) -> Option<T> {
+        let mut inner = self.inner.borrow_mut();
+        let msg = inner.queue.pop_front()?;
+
+        // wake one blocked send operation for the freed slot.
+        if let Some(waker) = inner.send.pop_front() {
+            waker.wake();
+        }
+
+        Some(msg)
+    }
+
+    pub(crate) fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<T> {
+        match self.try_recv() {
+            Some(msg) => Poll::Ready(msg),
+            None => {
+                self.inner.borrow_mut().recv = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    /// count of messages in channel.
+    pub(crate) fn len(&self) -> usize {
+        self.inner.borrow().queue.len()
+    }
+
+    /// close channel and wake up all blocked send operations.
+    pub(crate) fn close(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.closed = true;
+        inner.recv = None;
+        for waker in inner.send.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Drop for LocalReceiver<T> {
+    fn drop(&mut self) {
+        self.close();
+
+        // messages remain in channel would never be received. They are dropped on actor's thread
+        // and not passed to Actor::on_dead_letter as they can be !Send.
+        while let Some(msg) = self.try_recv() {
+            drop(msg);
+        }
+    }
+}
@@ -1,9 +1,19 @@
 mod async_channel;
 mod async_oneshot;
+mod local_channel;
 
 pub(crate) mod channel {
-    pub(crate) use super::async_channel::{channel, Receiver, SendFuture, Sender, WeakSender};
+    #[cfg(feature = "tokio-rt")]
+    pub(crate) use super::async_channel::channel;
+    #[cfg(feature = "futures-sink")]
+    pub(crate) use super::async_channel::SenderSink;
+    pub(crate) use super::async_channel::{
+        ChannelBuilder, Receiver, SendFuture, Sender, WeakSender,
+    };
     pub(crate) use super::async_oneshot::{oneshot, OneshotReceiver, OneshotSender};
+    pub(crate) use super::local_channel::{
+        local_channel, LocalReceiver, LocalSendFuture, LocalSender,
+    };
 }
 
 pub(crate) mod futures;